@@ -0,0 +1,544 @@
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+use blextism_schema::Schema;
+
+use crate::catalog::Catalog;
+use crate::ids::Ids;
+use crate::ir::Api;
+use crate::lint::{self, Lint};
+use crate::overlay::Overlay;
+use crate::select::Selection;
+use crate::{bindings, data_collections, tree, ts, versions, wit};
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Read the schema at `path` and apply `overlays` to it, in order.
+pub fn load_schema(path: &Path, overlays: &[PathBuf]) -> Result<Schema, Error> {
+    let file = std::fs::File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let mut schema: serde_json::Value = serde_json::from_reader(std::io::BufReader::new(file))?;
+    for overlay in overlays {
+        Overlay::load(overlay)?
+            .apply(&mut schema)
+            .map_err(|err| format!("{}: {}", overlay.display(), err))?;
+    }
+    let schema: Schema = serde_path_to_error::deserialize(schema)
+        .map_err(|err| format!("{}: $.{}: {}", path.display(), err.path(), err.inner()))?;
+    Ok(schema)
+}
+
+/// Generates bindings from one schema, or from several tagged with Blender
+/// versions:
+///
+/// ```no_run
+/// let bindings = blextism_bindgen::Builder::new()
+///     .schema("schema.json")
+///     .catalog("catalog.toml")
+///     .overlay("overlays/fixes.toml")
+///     .generate()?;
+/// bindings.write_to_file("bindings.rs")?;
+/// # Ok::<(), blextism_bindgen::Error>(())
+/// ```
+///
+/// With several schemas, whatever isn't the same in all of them is gated
+/// behind a `blextism_blender = "X.Y"` cfg; see the README.
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    schemas: Vec<(Option<String>, PathBuf)>,
+    overlays: Vec<PathBuf>,
+    catalog: Option<PathBuf>,
+    ids: Option<PathBuf>,
+    selection: Selection,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a schema, for the Blender version genschema recorded in it.
+    pub fn schema(mut self, path: impl Into<PathBuf>) -> Self {
+        self.schemas.push((None, path.into()));
+        self
+    }
+
+    /// Add a schema for Blender `version` (`"4.0"`), whatever it records.
+    pub fn versioned_schema(mut self, version: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.schemas.push((Some(version.into()), path.into()));
+        self
+    }
+
+    /// Apply a schema overlay to every schema, after those added before it.
+    pub fn overlay(mut self, path: impl Into<PathBuf>) -> Self {
+        self.overlays.push(path.into());
+        self
+    }
+
+    /// Also bind the Python-level members listed in this catalog.
+    pub fn catalog(mut self, path: impl Into<PathBuf>) -> Self {
+        self.catalog = Some(path.into());
+        self
+    }
+
+    /// Keep the ids in the table an earlier build's [`Bindings::ids`] wrote
+    /// here: members in it keep their id, and no other member takes one.
+    pub fn ids(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ids = Some(path.into());
+        self
+    }
+
+    /// Bind only the classes matching `pattern` (and the classes they
+    /// need), plus any other allowed ones. `*` matches any run of
+    /// characters: `"*Node*"`.
+    pub fn allow_class(mut self, pattern: impl Into<String>) -> Self {
+        self.selection.allow_classes.push(pattern.into());
+        self
+    }
+
+    /// Don't bind the classes matching `pattern`, nor members that need them.
+    pub fn deny_class(mut self, pattern: impl Into<String>) -> Self {
+        self.selection.deny_classes.push(pattern.into());
+        self
+    }
+
+    /// Bind only the `bpy.ops` modules matching `pattern`, plus any other
+    /// allowed ones.
+    pub fn allow_operators(mut self, pattern: impl Into<String>) -> Self {
+        self.selection.allow_operators.push(pattern.into());
+        self
+    }
+
+    /// Don't bind the `bpy.ops` modules matching `pattern`.
+    pub fn deny_operators(mut self, pattern: impl Into<String>) -> Self {
+        self.selection.deny_operators.push(pattern.into());
+        self
+    }
+
+    /// Bind only the `bpy.data` collections matching `pattern` (and their
+    /// classes), plus any other allowed ones.
+    pub fn allow_data(mut self, pattern: impl Into<String>) -> Self {
+        self.selection.allow_data.push(pattern.into());
+        self
+    }
+
+    /// Don't bind the `bpy.data` collections matching `pattern`.
+    pub fn deny_data(mut self, pattern: impl Into<String>) -> Self {
+        self.selection.deny_data.push(pattern.into());
+        self
+    }
+
+    /// Check every schema for the problems [`LintKind`](crate::LintKind)
+    /// describes, without generating anything.
+    pub fn lints(&self) -> Result<Vec<SchemaLint>, Error> {
+        Ok(self.schema_lints(&self.load()?))
+    }
+
+    pub fn generate(self) -> Result<Bindings, Error> {
+        let mut schemas = self.load()?;
+        let lints = self.schema_lints(&schemas);
+
+        // One table across every version, so a member has the same id in
+        // each of them.
+        let ids = Ids::new(
+            schemas.iter().map(|(_, _, schema, catalog)| (&schema.classes[..], catalog)),
+            &self.previous_ids()?,
+        );
+        let data: BTreeSet<String> = schemas
+            .iter()
+            .flat_map(|(_, _, schema, _)| data_collections(&schema.classes))
+            .map(String::from)
+            .collect();
+
+        let file = if schemas.len() == 1 {
+            let (_, _, schema, catalog) = schemas.remove(0);
+            bindings(schema, &catalog, &ids)?
+        } else {
+            let mut tagged = Vec::with_capacity(schemas.len());
+            for (path, version, schema, catalog) in schemas {
+                let version = version.ok_or_else(|| {
+                    format!("{}: no Blender version; give it with the schema", path.display())
+                })?;
+                tagged.push((versions::parse(&version)?, version, schema, catalog));
+            }
+            tagged.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+            if let Some(pair) = tagged.windows(2).find(|xs| xs[0].0 == xs[1].0) {
+                return Err(format!("two schemas for {}", pair[0].1).into());
+            }
+
+            let tags: Vec<_> = tagged.iter().map(|(_, version, _, _)| version.clone()).collect();
+            let files = tagged
+                .into_iter()
+                .map(|(_, _, schema, catalog)| bindings(schema, &catalog, &ids))
+                .collect::<Result<Vec<_>, _>>()?;
+            versions::merge(&tags, files)
+        };
+
+        let inputs = self
+            .schemas
+            .into_iter()
+            .map(|(_, path)| path)
+            .chain(self.overlays)
+            .chain(self.catalog)
+            .chain(self.ids)
+            .collect();
+        Ok(Bindings {
+            file,
+            lints,
+            ids: ids.table(),
+            data: data.into_iter().collect(),
+            inputs,
+        })
+    }
+
+    /// A WIT package mirroring the bindings, for the component model; see
+    /// the README. WIT is generated from one schema at a time.
+    pub fn generate_wit(self) -> Result<String, Error> {
+        Ok(wit::wit(&self.api("WIT")?))
+    }
+
+    /// TypeScript declarations mirroring the bindings, and the JavaScript
+    /// module that implements them for extism's JS PDK: the contents of
+    /// `bpy.d.ts` and `bpy.js`. They're generated from one schema at a time.
+    pub fn generate_ts(self) -> Result<(std::string::String, std::string::String), Error> {
+        Ok(ts::ts(&self.api("TypeScript")?))
+    }
+
+    fn schema_lints(&self, schemas: &[Loaded]) -> Vec<SchemaLint> {
+        let mut lints = Vec::new();
+        for (path, version, schema, catalog) in schemas {
+            lints.extend(lint::lint(schema, catalog).into_iter().map(|lint| SchemaLint {
+                schema: path.to_path_buf(),
+                version: version.clone().filter(|_| self.schemas.len() > 1),
+                lint,
+            }));
+        }
+        lints
+    }
+
+    /// The API the backends other than Rust's generate from, which take a
+    /// single schema.
+    fn api(&self, backend: &str) -> Result<Api, Error> {
+        let mut schemas = self.load()?;
+        if schemas.len() != 1 {
+            return Err(format!("{} is generated from one schema at a time", backend).into());
+        }
+        let (_, version, mut schema, catalog) = schemas.remove(0);
+        schema.version = version.map(|xs| xs.as_str().into());
+        let ids = Ids::new([(&schema.classes[..], &catalog)], &self.previous_ids()?);
+        Ok(Api::new(&schema, &catalog, &ids))
+    }
+
+    fn previous_ids(&self) -> Result<Ids, Error> {
+        match &self.ids {
+            Some(path) => Ok(Ids::load(path)?),
+            None => Ok(Ids::default()),
+        }
+    }
+
+    /// Load every schema, with the overlays applied and only what's
+    /// selected left, each with the catalog pared down to match.
+    fn load(&self) -> Result<Vec<Loaded<'_>>, Error> {
+        if self.schemas.is_empty() {
+            return Err("no schema to generate bindings from".into());
+        }
+
+        let mut schemas = Vec::with_capacity(self.schemas.len());
+        for (tag, path) in &self.schemas {
+            let schema = load_schema(path, &self.overlays)?;
+            let version = tag
+                .clone()
+                .or_else(|| schema.version.as_ref().map(|xs| xs.to_string()));
+            schemas.push((path, version, schema));
+        }
+
+        let catalog = match &self.catalog {
+            Some(path) => Catalog::load(path)?,
+            None => Catalog::default(),
+        };
+        catalog.check(&|name| schemas.iter().any(|(_, _, schema)| schema.class(name).is_some()))?;
+
+        // Each schema keeps its own catalog, as what's selected can differ
+        // between versions.
+        let schemas = schemas
+            .into_iter()
+            .map(|(path, version, mut schema)| {
+                let mut catalog = catalog.clone();
+                self.selection.apply(&mut schema, &mut catalog);
+                (path, version, schema, catalog)
+            })
+            .collect();
+        Ok(schemas)
+    }
+}
+
+/// A schema [`Builder::load`] read: its path, version, and the schema and
+/// catalog with the selection applied.
+type Loaded<'a> = (&'a PathBuf, Option<String>, Schema, Catalog);
+
+/// A problem [`lint`](crate::LintKind) found in one of the schemas.
+#[derive(Debug, Clone)]
+pub struct SchemaLint {
+    pub schema: PathBuf,
+    /// The schema's Blender version, when bindings are generated from
+    /// several.
+    pub version: Option<String>,
+    pub lint: Lint,
+}
+
+impl std::fmt::Display for SchemaLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}: {}", version, self.lint),
+            None => write!(f, "{}", self.lint),
+        }
+    }
+}
+
+/// Generated bindings, and what went into them.
+pub struct Bindings {
+    file: syn::File,
+    lints: Vec<SchemaLint>,
+    ids: serde_json::Value,
+    data: Vec<String>,
+    inputs: Vec<PathBuf>,
+}
+
+impl Bindings {
+    /// Problems with the schemas; the bindings are generated regardless.
+    pub fn lints(&self) -> &[SchemaLint] {
+        &self.lints
+    }
+
+    /// The stable id of every class member bound, as `--ids` writes it.
+    pub fn ids(&self) -> &serde_json::Value {
+        &self.ids
+    }
+
+    /// The `bpy.data` collections the bindings read from the `bpy.data`
+    /// config, as `--data` writes them. The host puts a pointer to each in the
+    /// config, next to `"context"` and `"data"`.
+    pub fn data(&self) -> &[String] {
+        &self.data
+    }
+
+    /// Every file the bindings were generated from.
+    pub fn inputs(&self) -> &[PathBuf] {
+        &self.inputs
+    }
+
+    /// From a build script: have cargo rerun it when any input changes.
+    pub fn emit_rerun_if_changed(&self) {
+        for path in &self.inputs {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Write the bindings as a tree of modules under `dir`, for a crate to
+    /// declare with `mod <dir>;`:
+    ///
+    /// - `mod.rs`, declaring `bpy`;
+    /// - `bpy/mod.rs`, with `bpy/data.rs` and `bpy/context.rs`;
+    /// - `bpy/types/mod.rs`, with one file per class family (a class and
+    ///   every class under it, up to `bpy_struct` or `ID`) and
+    ///   `bpy/types/rna.rs`;
+    /// - `bpy/ops/mod.rs`, with one file per `bpy.ops` module.
+    ///
+    /// Families are re-exported from `bpy::types`, so paths are the same as
+    /// in a single file. A module merged bindings have a copy of per Blender
+    /// version gets a file per copy: `types/mod.rs`, then `types-2/mod.rs`
+    /// and so on, named with `#[path]`.
+    ///
+    /// Files are only written if their contents changed. The files written are listed in a manifest in `dir`, so the
+    /// next run can remove those it no longer writes; nothing else under
+    /// `dir` is touched. A non-empty `dir` without a manifest is refused
+    /// rather than written into.
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        let dir = dir.as_ref();
+        let manifest = dir.join(MANIFEST);
+        let previous: Vec<PathBuf> = match std::fs::read_to_string(&manifest) {
+            // Only paths below `dir`, whatever the manifest says.
+            Ok(listed) => listed
+                .lines()
+                .map(Path::new)
+                .filter(|xs| xs.components().all(|xs| matches!(xs, std::path::Component::Normal(_))))
+                .map(|xs| dir.join(xs))
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                if std::fs::read_dir(dir).is_ok_and(|mut xs| xs.next().is_some()) {
+                    return Err(std::io::Error::other(format!(
+                        "{} isn't empty and has no {}; refusing to write bindings into it",
+                        dir.display(),
+                        MANIFEST
+                    )));
+                }
+                Vec::new()
+            }
+            Err(err) => return Err(err),
+        };
+
+        let files: Vec<(PathBuf, syn::File)> = tree::split(self.file.clone())
+            .into_iter()
+            .map(|(path, file)| (dir.join(path), file))
+            .collect();
+        for (path, file) in &files {
+            write_formatted(path, file)?;
+        }
+
+        let listed: String = files
+            .iter()
+            .filter_map(|(path, _)| path.strip_prefix(dir).ok())
+            .map(|xs| format!("{}\n", xs.display()))
+            .collect();
+        std::fs::write(&manifest, listed)?;
+
+        let written: HashSet<&Path> = files.iter().map(|(path, _)| path.as_path()).collect();
+        let stale = previous.iter().filter(|xs| !written.contains(xs.as_path()));
+        remove_stale(dir, stale)
+    }
+}
+
+impl std::fmt::Display for Bindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&prettyplease::unparse(&self.file))
+    }
+}
+
+/// Format `file` and write it to `path`, unless it already holds that.
+fn write_formatted(path: &Path, file: &syn::File) -> std::io::Result<()> {
+    let formatted = prettyplease::unparse(file);
+    if std::fs::read_to_string(path).is_ok_and(|xs| xs == formatted) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, formatted)
+}
+
+/// The file [`Bindings::write_to_dir`] lists the files it wrote in, one
+/// path relative to the directory per line.
+const MANIFEST: &str = ".bindgen-manifest";
+
+/// Remove the `stale` files an earlier run wrote under `dir`, and any
+/// directories below `dir` that leaves empty.
+fn remove_stale<'a>(dir: &Path, stale: impl Iterator<Item = &'a PathBuf>) -> std::io::Result<()> {
+    for path in stale {
+        match std::fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        let parents = path.ancestors().skip(1).take_while(|xs| *xs != dir);
+        for parent in parents {
+            if std::fs::read_dir(parent).map_or(true, |mut xs| xs.next().is_some()) {
+                break;
+            }
+            std::fs::remove_dir(parent)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings() -> Bindings {
+        Bindings {
+            file: syn::parse_quote! {
+                pub mod bpy {
+                    pub mod types {}
+                    pub mod ops {}
+                }
+            },
+            lints: Vec::new(),
+            ids: serde_json::Value::Null,
+            data: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blextism-bindgen-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn write_to_dir_removes_only_what_it_wrote() {
+        let dir = scratch("stale");
+        bindings().write_to_dir(&dir).unwrap();
+        assert!(dir.join("mod.rs").exists());
+
+        // A file an earlier run wrote, and one it didn't.
+        std::fs::create_dir_all(dir.join("bpy/old")).unwrap();
+        std::fs::write(dir.join("bpy/old/gone.rs"), "").unwrap();
+        std::fs::write(dir.join("lib.rs"), "").unwrap();
+        let manifest = std::fs::read_to_string(dir.join(MANIFEST)).unwrap();
+        std::fs::write(dir.join(MANIFEST), format!("{}bpy/old/gone.rs\n../outside.rs\n", manifest)).unwrap();
+
+        bindings().write_to_dir(&dir).unwrap();
+        assert!(!dir.join("bpy/old").exists());
+        assert!(dir.join("lib.rs").exists());
+        assert!(dir.join("mod.rs").exists());
+        assert!(!std::fs::read_to_string(dir.join(MANIFEST)).unwrap().contains("gone.rs"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_to_dir_refuses_a_foreign_dir() {
+        let dir = scratch("foreign");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("lib.rs"), "").unwrap();
+
+        assert!(bindings().write_to_dir(&dir).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn data_lists_the_collections_of_every_schema() {
+        let dir = scratch("data");
+        std::fs::create_dir_all(&dir).unwrap();
+        let collection = |name: &str| {
+            serde_json::json!({ "collection": {
+                "identifier": name,
+                "description": "",
+                "type": "COLLECTION",
+                "unit": "NONE",
+                "subtype": "NONE",
+                "is_required": false,
+                "is_runtime": false,
+                "is_output": false,
+                "is_never_none": true,
+                "fixed_type": "Object",
+                "collection": null,
+            } })
+        };
+        let schema = |collections: &[&str]| {
+            let properties: serde_json::Map<_, _> =
+                collections.iter().map(|name| (name.to_string(), collection(name))).collect();
+            serde_json::json!({
+                "classes": [
+                    { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+                    { "name": "Object", "parent": "bpy_struct", "properties": {}, "methods": {} },
+                    { "name": "BlendData", "parent": "bpy_struct", "properties": properties, "methods": {} },
+                ],
+                "operators": {},
+            })
+        };
+        std::fs::write(dir.join("3.6.json"), schema(&["objects"]).to_string()).unwrap();
+        std::fs::write(dir.join("4.0.json"), schema(&["objects", "grease_pencils"]).to_string()).unwrap();
+
+        let bindings = Builder::new()
+            .versioned_schema("3.6", dir.join("3.6.json"))
+            .versioned_schema("4.0", dir.join("4.0.json"))
+            .generate()
+            .unwrap();
+        assert_eq!(bindings.data(), ["grease_pencils", "objects"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use heck::ToUpperCamelCase;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde::Deserialize;
+
+/// Signatures for members the RNA schema can't describe: methods and
+/// properties defined in Python (`bpy_types.py`) or by `bpy_struct` itself,
+/// such as `Mesh.from_pydata`, `Object.children` or `bpy_struct.driver_add`.
+///
+/// The catalog is a TOML file keyed by class, then by member:
+///
+/// ```toml
+/// [Mesh.methods.from_pydata]
+/// description = "Make a mesh from a list of vertices/edges/faces."
+/// params = [
+///     { name = "vertices", type = "float[][]" },
+///     { name = "edges", type = "int[][]" },
+///     { name = "faces", type = "int[][]" },
+///     { name = "shade_flat", type = "bool", optional = true },
+/// ]
+///
+/// [Object.properties.children]
+/// description = "All the children of this object."
+/// type = "Object[]"
+/// readonly = true
+/// ```
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(transparent)]
+pub struct Catalog {
+    pub classes: BTreeMap<String, CatalogClass>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogClass {
+    #[serde(default)]
+    pub methods: BTreeMap<String, CatalogMethod>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, CatalogProperty>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogMethod {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub params: Vec<CatalogParam>,
+    pub returns: Option<TypeRef>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: TypeRef,
+    #[serde(default)]
+    pub description: String,
+    /// Optional parameters are passed as keywords, like optional RNA
+    /// parameters.
+    #[serde(default)]
+    pub optional: bool,
+    /// A buffer the method fills in place, like `foreach_get`'s `seq`. The
+    /// host sends it back after the call and the binding returns it.
+    #[serde(default)]
+    pub out: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogProperty {
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type")]
+    pub ty: TypeRef,
+    #[serde(default)]
+    pub readonly: bool,
+}
+
+/// A catalog type: `bool`, `int`, `float`, `str`, `any` or a `bpy.types`
+/// class name, followed by any number of `[]`, and optionally `?` for a
+/// value that may be `None`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub enum TypeRef {
+    Bool,
+    Int,
+    Float,
+    Str,
+    Any,
+    Struct(String),
+    Array(Box<TypeRef>),
+    Optional(Box<TypeRef>),
+}
+
+impl TryFrom<String> for TypeRef {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (base, optional) = match value.strip_suffix('?') {
+            Some(base) => (base, true),
+            None => (value.as_str(), false),
+        };
+
+        let mut depth = 0;
+        let mut base = base;
+        while let Some(inner) = base.strip_suffix("[]") {
+            base = inner;
+            depth += 1;
+        }
+
+        let mut ty = match base {
+            "bool" => TypeRef::Bool,
+            "int" => TypeRef::Int,
+            "float" => TypeRef::Float,
+            "str" => TypeRef::Str,
+            "any" => TypeRef::Any,
+            xs if !xs.is_empty() && xs.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+                TypeRef::Struct(xs.to_string())
+            }
+            _ => return Err(format!("invalid catalog type {:?}", value)),
+        };
+
+        for _ in 0..depth {
+            ty = TypeRef::Array(Box::new(ty));
+        }
+        if optional {
+            ty = TypeRef::Optional(Box::new(ty));
+        }
+        Ok(ty)
+    }
+}
+
+impl std::fmt::Display for TypeRef {
+    /// As the catalog writes it: `Object[]?`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeRef::Bool => f.write_str("bool"),
+            TypeRef::Int => f.write_str("int"),
+            TypeRef::Float => f.write_str("float"),
+            TypeRef::Str => f.write_str("str"),
+            TypeRef::Any => f.write_str("any"),
+            TypeRef::Struct(name) => f.write_str(name),
+            TypeRef::Array(inner) => write!(f, "{}[]", inner),
+            TypeRef::Optional(inner) => write!(f, "{}?", inner),
+        }
+    }
+}
+
+impl TypeRef {
+    /// Every class this type refers to.
+    pub fn structs(&self) -> Vec<&str> {
+        match self {
+            TypeRef::Struct(name) => vec![name.as_str()],
+            TypeRef::Array(inner) | TypeRef::Optional(inner) => inner.structs(),
+            _ => vec![],
+        }
+    }
+
+    /// The Rust type a binding takes this as. Structs are taken as `BpyPtr`,
+    /// like RNA pointer parameters.
+    pub fn as_param(&self) -> TokenStream {
+        match self {
+            TypeRef::Str => quote! { &str },
+            TypeRef::Array(inner) => {
+                let inner = match inner.as_ref() {
+                    TypeRef::Str => quote! { &str },
+                    inner => inner.as_owned(),
+                };
+                quote! { &[#inner] }
+            }
+            TypeRef::Optional(inner) => {
+                let inner = inner.as_param();
+                quote! { Option<#inner> }
+            }
+            xs => xs.as_owned(),
+        }
+    }
+
+    /// The Rust type a binding returns this as.
+    pub fn as_return(&self) -> TokenStream {
+        match self {
+            TypeRef::Struct(name) => {
+                let ident = format_ident!("{}", name.to_upper_camel_case());
+                quote! { Box<dyn #ident + Send + Sync> }
+            }
+            TypeRef::Array(inner) => {
+                let inner = inner.as_return();
+                quote! { Vec<#inner> }
+            }
+            TypeRef::Optional(inner) => {
+                let inner = inner.as_return();
+                quote! { Option<#inner> }
+            }
+            xs => xs.as_owned(),
+        }
+    }
+
+    /// The type the host's response deserializes into before structs are
+    /// boxed; see [`Self::convert`].
+    pub fn as_wire(&self) -> TokenStream {
+        match self {
+            TypeRef::Array(inner) => {
+                let inner = inner.as_wire();
+                quote! { Vec<#inner> }
+            }
+            TypeRef::Optional(inner) => {
+                let inner = inner.as_wire();
+                quote! { Option<#inner> }
+            }
+            xs => xs.as_owned(),
+        }
+    }
+
+    /// Convert `expr`, of type [`Self::as_wire`], to [`Self::as_return`].
+    pub fn convert(&self, expr: TokenStream) -> TokenStream {
+        if self.structs().is_empty() {
+            return expr;
+        }
+
+        match self {
+            TypeRef::Struct(name) => {
+                let ident = format_ident!("{}", name.to_upper_camel_case());
+                quote! { Box::new(#expr) as Box<dyn #ident + Send + Sync> }
+            }
+            TypeRef::Array(inner) => {
+                let inner = inner.convert(quote! { xs });
+                quote! { #expr.into_iter().map(|xs| #inner).collect::<Vec<_>>() }
+            }
+            TypeRef::Optional(inner) => {
+                let inner = inner.convert(quote! { xs });
+                quote! { #expr.map(|xs| #inner) }
+            }
+            _ => expr,
+        }
+    }
+
+    fn as_owned(&self) -> TokenStream {
+        match self {
+            TypeRef::Bool => quote! { bool },
+            TypeRef::Int => quote! { i64 },
+            TypeRef::Float => quote! { f64 },
+            TypeRef::Str => quote! { String },
+            TypeRef::Any => quote! { serde_json::Value },
+            TypeRef::Struct(_) => quote! { BpyPtr },
+            TypeRef::Array(inner) => {
+                let inner = inner.as_owned();
+                quote! { Vec<#inner> }
+            }
+            TypeRef::Optional(inner) => {
+                let inner = inner.as_owned();
+                quote! { Option<#inner> }
+            }
+        }
+    }
+}
+
+impl Catalog {
+    pub fn load(path: &Path) -> Result<Self, std::string::String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn class(&self, name: &str) -> Option<&CatalogClass> {
+        self.classes.get(name)
+    }
+
+    /// Drop the classes `keep` rejects, and members that refer to them.
+    pub fn retain(&mut self, keep: &dyn Fn(&str) -> bool) {
+        self.classes.retain(|class, _| keep(class));
+        for members in self.classes.values_mut() {
+            members.methods.retain(|_, method| {
+                method
+                    .params
+                    .iter()
+                    .map(|xs| &xs.ty)
+                    .chain(method.returns.as_ref())
+                    .all(|ty| ty.structs().into_iter().all(keep))
+            });
+            members
+                .properties
+                .retain(|_, property| property.ty.structs().into_iter().all(keep));
+        }
+    }
+
+    /// Check that every class the catalog names or refers to is in the schema.
+    pub fn check(&self, known: &dyn Fn(&str) -> bool) -> Result<(), std::string::String> {
+        for (class, members) in &self.classes {
+            if !known(class) {
+                return Err(format!("catalog: unknown class {:?}", class));
+            }
+
+            let types = members
+                .methods
+                .iter()
+                .flat_map(|(name, method)| {
+                    method
+                        .params
+                        .iter()
+                        .map(|xs| &xs.ty)
+                        .chain(method.returns.as_ref())
+                        .map(move |ty| (name, ty))
+                })
+                .chain(members.properties.iter().map(|(name, prop)| (name, &prop.ty)));
+
+            for (member, ty) in types {
+                if let Some(missing) = ty.structs().into_iter().find(|xs| !known(xs)) {
+                    return Err(format!(
+                        "catalog: {}.{} refers to unknown class {:?}",
+                        class, member, missing
+                    ));
+                }
+            }
+
+            for (name, method) in &members.methods {
+                let outs: Vec<_> = method.params.iter().filter(|xs| xs.out).collect();
+                let problem = match outs.as_slice() {
+                    [] => None,
+                    [out] if out.optional => Some("has an optional `out` parameter"),
+                    [_] if method.returns.is_some() => Some("has both `returns` and an `out` parameter"),
+                    [_] => None,
+                    _ => Some("has more than one `out` parameter"),
+                };
+                if let Some(problem) = problem {
+                    return Err(format!("catalog: {}.{} {}", class, name, problem));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use blextism_schema::{BpyProperty, BpyStructure, Schema};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One difference between two schemas, displayed as `+ class Foo`,
+/// `- property Object.bar` or `~ operator object.delete: parameters`.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// `class`, `property`, `method`, `enum item` or `operator`.
+    pub what: &'static str,
+    pub name: String,
+    /// For changes, which fields differ.
+    pub detail: Option<String>,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        };
+        write!(f, "{} {} {}", sign, self.what, self.name)?;
+        if let Some(detail) = &self.detail {
+            write!(f, ": {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    fn push(&mut self, kind: ChangeKind, what: &'static str, name: String, detail: Option<String>) {
+        self.changes.push(Change {
+            kind,
+            what,
+            name,
+            detail,
+        });
+    }
+
+    /// Report members added to, removed from or changed between `old` and
+    /// `new`, calling `each` on the members both have.
+    fn members<'a, T>(
+        &mut self,
+        what: &'static str,
+        owner: &str,
+        old: &'a BTreeMap<impl AsRef<str> + Ord, T>,
+        new: &'a BTreeMap<impl AsRef<str> + Ord, T>,
+        mut each: impl FnMut(&mut Self, String, &'a T, &'a T),
+    ) {
+        let old: BTreeMap<&str, &T> = old.iter().map(|(key, xs)| (key.as_ref(), xs)).collect();
+        let new: BTreeMap<&str, &T> = new.iter().map(|(key, xs)| (key.as_ref(), xs)).collect();
+        for (key, lhs) in &old {
+            let name = format!("{}.{}", owner, key);
+            match new.get(key) {
+                Some(rhs) => each(self, name, lhs, rhs),
+                None => self.push(ChangeKind::Removed, what, name, None),
+            }
+        }
+        for key in new.keys().filter(|xs| !old.contains_key(*xs)) {
+            self.push(ChangeKind::Added, what, format!("{}.{}", owner, key), None);
+        }
+    }
+
+    fn class(&mut self, old: &BpyStructure, new: &BpyStructure) {
+        let mut fields = Vec::new();
+        if old.parent != new.parent {
+            fields.push(format!("parent {} -> {}", old.parent, new.parent));
+        }
+        if old.bases != new.bases {
+            fields.push("bases".to_string());
+        }
+        if old.mixin != new.mixin {
+            fields.push("mixin".to_string());
+        }
+        if !fields.is_empty() {
+            self.push(ChangeKind::Changed, "class", old.name.to_string(), Some(fields.join(", ")));
+        }
+
+        self.members("property", &old.name, &old.properties, &new.properties, |diff, name, lhs, rhs| {
+            diff.property(name, lhs, rhs)
+        });
+        self.members("method", &old.name, &old.methods, &new.methods, |diff, name, lhs, rhs| {
+            if let Some(fields) = changed_fields(lhs, rhs) {
+                diff.push(ChangeKind::Changed, "method", name, Some(fields));
+            }
+        });
+    }
+
+    fn property(&mut self, name: String, old: &BpyProperty, new: &BpyProperty) {
+        if let (BpyProperty::Enum { items: lhs, .. }, BpyProperty::Enum { items: rhs, .. }) = (old, new) {
+            let lhs: BTreeMap<&str, _> = lhs.iter().map(|xs| (xs.id.as_str(), xs)).collect();
+            let rhs: BTreeMap<&str, _> = rhs.iter().map(|xs| (xs.id.as_str(), xs)).collect();
+            self.members("enum item", &name, &lhs, &rhs, |diff, name, lhs, rhs| {
+                if let Some(fields) = changed_fields(lhs, rhs) {
+                    diff.push(ChangeKind::Changed, "enum item", name, Some(fields));
+                }
+            });
+        }
+        if let Some(fields) = changed_fields(old, new) {
+            self.push(ChangeKind::Changed, "property", name, Some(fields));
+        }
+    }
+}
+
+/// Compare two schemas: classes, and their properties, methods and enum
+/// items, then operators.
+pub fn diff(old: &Schema, new: &Schema) -> Vec<Change> {
+    let mut diff = Diff::default();
+    let old_classes: BTreeMap<&str, &BpyStructure> =
+        old.classes.iter().map(|xs| (xs.name.as_str(), xs)).collect();
+    let new_classes: BTreeMap<&str, &BpyStructure> =
+        new.classes.iter().map(|xs| (xs.name.as_str(), xs)).collect();
+
+    for (name, class) in &old_classes {
+        match new_classes.get(name) {
+            Some(other) => diff.class(class, other),
+            None => diff.push(ChangeKind::Removed, "class", name.to_string(), None),
+        }
+    }
+    for name in new_classes.keys().filter(|xs| !old_classes.contains_key(*xs)) {
+        diff.push(ChangeKind::Added, "class", name.to_string(), None);
+    }
+
+    let modules: BTreeSet<&str> = old.operators.keys().chain(new.operators.keys()).map(|xs| xs.as_str()).collect();
+    let empty = BTreeMap::new();
+    for opmod in modules {
+        let lhs = old.operators.get(opmod).unwrap_or(&empty);
+        let rhs = new.operators.get(opmod).unwrap_or(&empty);
+        diff.members("operator", opmod, lhs, rhs, |diff, name, lhs, rhs| {
+            if let Some(fields) = changed_fields(lhs, rhs) {
+                diff.push(ChangeKind::Changed, "operator", name, Some(fields));
+            }
+        });
+    }
+    diff.changes
+}
+
+/// The fields that differ between `old` and `new`, by their JSON form; or
+/// `int -> float` if they're different kinds of property. Enum items are
+/// compared on their own, so they don't count.
+fn changed_fields(old: &impl Serialize, new: &impl Serialize) -> Option<String> {
+    let old = serde_json::to_value(old).ok()?;
+    let new = serde_json::to_value(new).ok()?;
+    if old == new {
+        return None;
+    }
+
+    let (old, new) = match (single_entry(&old), single_entry(&new)) {
+        (Some((lhs, _)), Some((rhs, _))) if lhs != rhs => return Some(format!("{} -> {}", lhs, rhs)),
+        (Some((_, lhs)), Some((_, rhs))) => (lhs, rhs),
+        _ => (&old, &new),
+    };
+    // RNA methods keep their fields under `item`.
+    let (old, new) = match (old.get("item"), new.get("item")) {
+        (Some(lhs), Some(rhs)) => (lhs, rhs),
+        _ => (old, new),
+    };
+
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return Some("value".to_string());
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    let fields: Vec<&str> = keys
+        .into_iter()
+        .filter(|key| key.as_str() != "items" && old.get(*key) != new.get(*key))
+        .map(|xs| xs.as_str())
+        .collect();
+    (!fields.is_empty()).then(|| fields.join(", "))
+}
+
+/// `{"int": {..}}` -> `("int", {..})`: how properties are tagged.
+fn single_entry(value: &Value) -> Option<(&str, &Value)> {
+    match value {
+        Value::Object(map) if map.len() == 1 => map
+            .iter()
+            .next()
+            .filter(|(_, xs)| xs.is_object())
+            .map(|(key, xs)| (key.as_str(), xs)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn property(kind: &str, identifier: &str, extra: Value) -> Value {
+        let mut item = json!({
+            "identifier": identifier,
+            "description": "",
+            "type": kind.to_uppercase(),
+            "unit": "NONE",
+            "subtype": "NONE",
+            "is_required": false,
+            "is_runtime": false,
+            "is_output": false,
+            "is_never_none": false,
+        });
+        item.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        json!({ kind: item })
+    }
+
+    fn schema(classes: Value, operators: Value) -> Schema {
+        serde_json::from_value(json!({ "classes": classes, "operators": operators })).unwrap()
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_members() {
+        let mode = |items: &[&str]| {
+            let items: Vec<_> = items
+                .iter()
+                .enumerate()
+                .map(|(value, id)| json!({ "id": id, "name": id, "value": value, "description": "" }))
+                .collect();
+            property("enum", "mode", json!({ "items": items, "default": "OBJECT" }))
+        };
+        let range = json!({ "default": 0, "hard_min": 0, "hard_max": 100, "soft_min": 0, "soft_max": 100 });
+        let old = schema(
+            json!([
+                { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+                { "name": "Object", "parent": "bpy_struct", "methods": {}, "properties": {
+                    "mode": mode(&["OBJECT", "EDIT", "SCULPT"]),
+                    "pass_index": property("int", "pass_index", range.clone()),
+                    "grease_pencil": property("bool", "grease_pencil", json!({ "type": "BOOLEAN", "default": false })),
+                } },
+                { "name": "GPencil", "parent": "bpy_struct", "properties": {}, "methods": {} },
+            ]),
+            json!({ "object": { "delete": { "description": "Delete", "parameters": [] } } }),
+        );
+        let new = schema(
+            json!([
+                { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+                { "name": "Object", "parent": "ID", "methods": {}, "properties": {
+                    "mode": mode(&["OBJECT", "EDIT", "PAINT_GREASE_PENCIL"]),
+                    "pass_index": property("float", "pass_index", range.clone()),
+                } },
+                { "name": "GreasePencil", "parent": "bpy_struct", "properties": {}, "methods": {} },
+            ]),
+            json!({
+                "object": { "delete": { "description": "Delete selected objects", "parameters": [] } },
+                "grease_pencil": { "delete": { "description": "Delete", "parameters": [] } },
+            }),
+        );
+
+        let changes: Vec<String> = diff(&old, &new).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                "- class GPencil",
+                "~ class Object: parent bpy_struct -> ID",
+                "- property Object.grease_pencil",
+                "- enum item Object.mode.SCULPT",
+                "+ enum item Object.mode.PAINT_GREASE_PENCIL",
+                "~ property Object.pass_index: int -> float",
+                "+ class GreasePencil",
+                "+ operator grease_pencil.delete",
+                "~ operator object.delete: description",
+            ]
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use blextism_schema::{BpyMethod, BpyStructure};
+use serde::Deserialize;
+
+use crate::catalog::Catalog;
+
+/// Members every class has whatever the schema says, which the generated
+/// reset and is-set methods call.
+const BUILTINS: [(&str, &str); 2] = [("bpy_struct", "property_unset"), ("bpy_struct", "is_property_set")];
+
+/// The 32-bit FNV-1a hash of `class.member`: the id [`Ids`] gives that member
+/// unless another member hashes to the same value.
+pub fn member_id(class: &str, member: &str) -> u32 {
+    const OFFSET: u32 = 0x811c_9dc5;
+    const PRIME: u32 = 0x0100_0193;
+
+    class
+        .bytes()
+        .chain(std::iter::once(b'.'))
+        .chain(member.bytes())
+        .fold(OFFSET, |hash, byte| (hash ^ byte as u32).wrapping_mul(PRIME))
+}
+
+/// The stable id of every property and RNA function in a set of schemas
+/// (one per Blender version), every member of their catalogs, and the
+/// `bpy_struct` functions the bindings call on any class.
+///
+/// A member's id is its [`member_id`], so ids depend only on the names
+/// involved and survive regenerating the bindings against a newer schema; a
+/// host can keep a table from one build and use it with another. Members
+/// whose hashes collide are the exception: the first, in class and member
+/// order, keeps the hash and the others take the next free ids after it. A
+/// member added later could then take an id an older one was probed to, so
+/// the [`table`](Self::table) from the last build is passed back in: every
+/// member in it keeps its id, and no other member takes one of its ids.
+#[derive(Debug, Default)]
+pub struct Ids {
+    members: BTreeMap<(std::string::String, std::string::String), (u32, std::string::String)>,
+}
+
+impl Ids {
+    pub fn new<'a>(schemas: impl IntoIterator<Item = (&'a [BpyStructure], &'a Catalog)>, previous: &Ids) -> Self {
+        // The first schema to list a member decides its kind.
+        let mut kinds = BTreeMap::new();
+        for (classes, catalog) in schemas {
+            for class in classes {
+                for (member, kind) in members(class, catalog) {
+                    kinds.entry((class.name.to_string(), member.to_string())).or_insert(kind);
+                }
+            }
+        }
+        for (class, member) in BUILTINS {
+            kinds.entry((class.to_string(), member.to_string())).or_insert("python-method");
+        }
+
+        // Members the schemas no longer have stay in the table, so their ids
+        // aren't given to anything else.
+        let mut members = previous.members.clone();
+        let mut taken: BTreeSet<u32> = members.values().map(|(id, _)| *id).collect();
+        let mut probed = Vec::new();
+        for (key, kind) in kinds {
+            if let Some((_, known)) = members.get_mut(&key) {
+                *known = kind.to_string();
+                continue;
+            }
+            let id = member_id(&key.0, &key.1);
+            if taken.insert(id) {
+                members.insert(key, (id, kind.to_string()));
+            } else {
+                probed.push((key, kind, id));
+            }
+        }
+        // Only once every uncontested hash is claimed, so that a collision
+        // never moves a member that isn't part of it.
+        for (key, kind, mut id) in probed {
+            while !taken.insert(id) {
+                id = id.wrapping_add(1);
+            }
+            members.insert(key, (id, kind.to_string()));
+        }
+
+        Self { members }
+    }
+
+    /// Read a table `--ids` wrote.
+    pub fn load(path: &Path) -> Result<Self, std::string::String> {
+        #[derive(Deserialize)]
+        struct Entry {
+            id: u32,
+            class: std::string::String,
+            member: std::string::String,
+            kind: std::string::String,
+        }
+
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let entries: Vec<Entry> = serde_json::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let mut ids = BTreeMap::new();
+        let mut members = BTreeMap::new();
+        for entry in entries {
+            let name = format!("{}.{}", entry.class, entry.member);
+            if let Some(other) = ids.insert(entry.id, name.clone()) {
+                return Err(format!("{}: {} and {} both have id {}", path.display(), other, name, entry.id));
+            }
+            members.insert((entry.class, entry.member), (entry.id, entry.kind));
+        }
+        Ok(Self { members })
+    }
+
+    /// The id of `class.member`.
+    ///
+    /// Panics if the member isn't in any of the schemas the table was built
+    /// from: it would have no id a host could look up.
+    pub fn get(&self, class: &str, member: &str) -> u32 {
+        self.members
+            .get(&(class.to_string(), member.to_string()))
+            .map(|(id, _)| *id)
+            .unwrap_or_else(|| panic!("no member id for {}.{}", class, member))
+    }
+
+    /// The table `--ids` writes: one entry per member, sorted by class and
+    /// member.
+    pub fn table(&self) -> serde_json::Value {
+        self.members
+            .iter()
+            .map(|((class, member), (id, kind))| {
+                serde_json::json!({
+                    "id": id,
+                    "class": class,
+                    "member": member,
+                    "kind": kind,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The members of `class` that get an id, with their kind. Catalog members
+/// the schema already describes are bound from the schema, and listed once.
+fn members<'a>(class: &'a BpyStructure, catalog: &'a Catalog) -> impl Iterator<Item = (&'a str, &'static str)> {
+    let properties = class
+        .properties
+        .keys()
+        .map(|member| (member.as_str(), "property"));
+    let functions = class
+        .methods
+        .iter()
+        .filter(|(_, method)| matches!(method, BpyMethod::Rna { .. }))
+        .map(|(member, _)| (member.as_str(), "function"));
+
+    let in_schema = |member: &str| {
+        class.properties.contains_key(member) || matches!(class.methods.get(member), Some(BpyMethod::Rna { .. }))
+    };
+    let python = catalog.class(&class.name).into_iter().flat_map(move |members| {
+        let methods = members
+            .methods
+            .keys()
+            .map(|member| (member.as_str(), "python-method"));
+        let properties = members
+            .properties
+            .keys()
+            .map(|member| (member.as_str(), "python-property"));
+        methods.chain(properties).filter(move |(member, _)| !in_schema(member))
+    });
+
+    properties.chain(functions).chain(python)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str, functions: &[&str]) -> BpyStructure {
+        let function = serde_json::json!({
+            "type": "rna",
+            "item": { "description": "", "use_self": true, "use_self_type": false, "parameters": [] },
+        });
+        let methods: serde_json::Map<_, _> = functions
+            .iter()
+            .map(|name| (name.to_string(), function.clone()))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "parent": "bpy_struct",
+            "properties": {},
+            "methods": methods,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn colliding_members_get_distinct_ids() {
+        // Both hash to 0xe096c382.
+        assert_eq!(member_id("Object", "prop_229599"), member_id("Object", "prop_432382"));
+
+        let classes = [class("Object", &["prop_229599", "prop_432382", "select_get"])];
+        let ids = Ids::new([(&classes[..], &Catalog::default())], &Ids::default());
+
+        assert_eq!(ids.get("Object", "prop_229599"), 0xe096_c382);
+        assert_eq!(ids.get("Object", "prop_432382"), 0xe096_c383);
+        assert_eq!(ids.get("Object", "select_get"), member_id("Object", "select_get"));
+    }
+
+    #[test]
+    fn ids_in_an_earlier_table_never_move() {
+        let path = std::env::temp_dir().join(format!("blextism-bindgen-ids-{}.json", std::process::id()));
+        let catalog = Catalog::default();
+        let v1 = [class("Object", &["prop_432382", "select_get"])];
+        let first = Ids::new([(&v1[..], &catalog)], &Ids::default());
+        std::fs::write(&path, first.table().to_string()).unwrap();
+        let previous = Ids::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // On its own, the newcomer would take the hash, as it sorts first.
+        let v2 = [class("Object", &["prop_229599", "prop_432382"])];
+        assert_eq!(Ids::new([(&v2[..], &catalog)], &Ids::default()).get("Object", "prop_229599"), 0xe096_c382);
+
+        let second = Ids::new([(&v2[..], &catalog)], &previous);
+        assert_eq!(second.get("Object", "prop_432382"), 0xe096_c382);
+        assert_eq!(second.get("Object", "prop_229599"), 0xe096_c383);
+        // A member the schema dropped keeps its id, so nothing else takes it.
+        assert_eq!(second.get("Object", "select_get"), first.get("Object", "select_get"));
+    }
+
+    #[test]
+    fn tables_with_a_repeated_id_are_refused() {
+        let path = std::env::temp_dir().join(format!("blextism-bindgen-dup-{}.json", std::process::id()));
+        let entry = |member: &str| serde_json::json!({ "id": 1, "class": "Object", "member": member, "kind": "property" });
+        std::fs::write(&path, serde_json::json!([entry("name"), entry("type")]).to_string()).unwrap();
+        let err = Ids::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.ends_with("Object.name and Object.type both have id 1"), "{}", err);
+    }
+
+    #[test]
+    fn table_covers_every_schema_and_builtins() {
+        let v1 = [class("Object", &["select_get"])];
+        let v2 = [class("Object", &["select_get", "select_set"])];
+        let catalog = Catalog::default();
+        let ids = Ids::new([(&v1[..], &catalog), (&v2[..], &catalog)], &Ids::default());
+
+        let table = ids.table();
+        let members: Vec<_> = table
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|xs| format!("{}.{}", xs["class"].as_str().unwrap(), xs["member"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            members,
+            ["Object.select_get", "Object.select_set", "bpy_struct.is_property_set", "bpy_struct.property_unset"]
+        );
+        assert_eq!(table[3]["id"], ids.get("bpy_struct", "property_unset"));
+    }
+}
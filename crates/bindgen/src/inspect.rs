@@ -0,0 +1,538 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use blextism_schema::{BpyMethod, BpyProperty, BpyStructure, Schema};
+use heck::ToUpperCamelCase;
+use syn::{File, ImplItem, Item, TraitItem};
+
+use crate::catalog::{Catalog, CatalogClass};
+use crate::ids::Ids;
+use crate::names::{Member, OperatorNames, SchemaNames};
+use crate::Error;
+
+/// Describes what the schema says about a class, member or operator, next to
+/// what bindgen generates for it.
+pub struct Inspector {
+    schema: Schema,
+    catalog: Catalog,
+    names: SchemaNames,
+    file: File,
+}
+
+impl Inspector {
+    /// Generate bindings for `schema` (with the catalog at `catalog`, if
+    /// any) to look members up in.
+    pub fn new(schema: Schema, catalog: Option<&Path>) -> Result<Self, Error> {
+        let catalog = match catalog {
+            Some(path) => Catalog::load(path)?,
+            None => Catalog::default(),
+        };
+        catalog.check(&|name| schema.class(name).is_some())?;
+        let ids = Ids::new([(&schema.classes[..], &catalog)], &Ids::default());
+        let file = crate::bindings(schema.clone(), &catalog, &ids)?;
+        let names = SchemaNames::new(&schema.classes, &catalog);
+        Ok(Inspector { schema, catalog, names, file })
+    }
+
+    /// Describe what `query` names:
+    ///
+    /// - `Object`: a class;
+    /// - `Object.location`: a property or method, of the class or one it
+    ///   extends;
+    /// - `ops.object.select_all`: an operator (`ops.object` lists them);
+    /// - `enum:RenderSettings.engine`: an enum property's items.
+    pub fn inspect(&self, query: &str) -> Result<String, Error> {
+        let mut out = String::new();
+        if let Some(path) = query.strip_prefix("enum:") {
+            let (class, key) = path.split_once('.').ok_or("expected enum:Class.property")?;
+            let (owner, property) = self.property(class, key)?;
+            let BpyProperty::Enum { items, default, .. } = property else {
+                return Err(format!("{}.{} is a {} property, not an enum", class, key, kind(property)).into());
+            };
+            writeln!(out, "{}.{}: {} items", class, key, items.len())?;
+            for item in items {
+                let mark = if default.as_deref() == Some(item.id.as_str()) { " (default)" } else { "" };
+                writeln!(out, "  {} = {}{}: {}", item.id, item.value, mark, item.name)?;
+                if !item.description.is_empty() {
+                    writeln!(out, "      {}", item.description)?;
+                }
+            }
+            self.member_bindings(&mut out, owner, key)?;
+        } else if let Some(path) = query.strip_prefix("ops.") {
+            self.operator(&mut out, path)?;
+        } else {
+            match query.split_once('.') {
+                Some((class, key)) => self.member(&mut out, class, key)?,
+                None => self.class(&mut out, query)?,
+            }
+        }
+        Ok(out)
+    }
+
+    fn structure(&self, class: &str) -> Result<&BpyStructure, Error> {
+        self.schema
+            .class(class)
+            .ok_or_else(|| format!("no class {:?} in the schema", class).into())
+    }
+
+    /// `class` and then the classes it extends, nearest first.
+    fn lineage<'a>(&'a self, class: &'a BpyStructure) -> Vec<&'a BpyStructure> {
+        let mut lineage = vec![class];
+        let mut at = 0;
+        while let Some(class) = lineage.get(at) {
+            let supers: Vec<_> = class.supers().filter_map(|xs| self.schema.class(xs)).collect();
+            for xs in supers {
+                if !lineage.iter().any(|ys| ys.name == xs.name) {
+                    lineage.push(xs);
+                }
+            }
+            at += 1;
+        }
+        lineage
+    }
+
+    /// The schema property `class.key`, and the class that declares it.
+    fn property(&self, class: &str, key: &str) -> Result<(&BpyStructure, &BpyProperty), Error> {
+        self.lineage(self.structure(class)?)
+            .into_iter()
+            .find_map(|owner| owner.properties.get(key).map(|xs| (owner, xs)))
+            .ok_or_else(|| format!("{} has no property {:?}", class, key).into())
+    }
+
+    fn class(&self, out: &mut String, class: &str) -> Result<(), Error> {
+        let structure = self.structure(class)?;
+        let kind = if structure.mixin { "mixin" } else { "class" };
+        writeln!(out, "{} {}", kind, structure.name)?;
+        writeln!(out, "  extends: {}", structure.supers().collect::<Vec<_>>().join(", "))?;
+        let properties: Vec<&str> = structure.properties.keys().map(|xs| xs.as_str()).collect();
+        let methods: Vec<&str> = structure.methods.keys().map(|xs| xs.as_str()).collect();
+        writeln!(out, "  properties: {}", properties.join(", "))?;
+        writeln!(out, "  methods: {}", methods.join(", "))?;
+        if let Some(catalog) = self.catalog.class(class) {
+            let members: Vec<&str> = catalog
+                .properties
+                .keys()
+                .chain(catalog.methods.keys())
+                .map(|xs| xs.as_str())
+                .collect();
+            writeln!(out, "  catalog: {}", members.join(", "))?;
+        }
+
+        if let Some(item) = self.trait_(class) {
+            let mut item = item.clone();
+            item.attrs.clear();
+            item.items.clear();
+            writeln!(out)?;
+            write_items(out, "bpy::types", vec![Item::Trait(item)]);
+        }
+        Ok(())
+    }
+
+    fn member(&self, out: &mut String, class: &str, key: &str) -> Result<(), Error> {
+        let lineage = self.lineage(self.structure(class)?);
+        for owner in &lineage {
+            let inherited = if owner.name != class {
+                format!(" (from {})", owner.name)
+            } else {
+                String::new()
+            };
+            if let Some(property) = owner.properties.get(key) {
+                writeln!(out, "{}.{}: {} property{}", class, key, kind(property), inherited)?;
+                describe(out, property)?;
+                return self.member_bindings(out, owner, key);
+            }
+            if let Some(method) = owner.methods.get(key) {
+                writeln!(out, "{}.{}: method{}", class, key, inherited)?;
+                match method {
+                    BpyMethod::Rna {
+                        description,
+                        use_self,
+                        parameters,
+                        ..
+                    } => {
+                        line(out, "description", description)?;
+                        if !use_self {
+                            writeln!(out, "  class-level")?;
+                        }
+                        parameter_list(out, parameters)?;
+                    }
+                    _ if self.catalog.class(&owner.name).is_some_and(|xs| xs.methods.contains_key(key)) => {
+                        catalog_method(out, self.catalog.class(&owner.name), key)?;
+                    }
+                    _ => {
+                        writeln!(out, "  Python-level, and not in the catalog: not bound")?;
+                        return Ok(());
+                    }
+                }
+                return self.member_bindings(out, owner, key);
+            }
+            if let Some(catalog) = self.catalog.class(&owner.name) {
+                if let Some(property) = catalog.properties.get(key) {
+                    writeln!(out, "{}.{}: catalog property{}", class, key, inherited)?;
+                    line(out, "description", &property.description)?;
+                    writeln!(out, "  type: {}", property.ty)?;
+                    if property.readonly {
+                        writeln!(out, "  flags: read-only")?;
+                    }
+                    return self.member_bindings(out, owner, key);
+                }
+                if catalog.methods.contains_key(key) {
+                    writeln!(out, "{}.{}: catalog method{}", class, key, inherited)?;
+                    catalog_method(out, Some(catalog), key)?;
+                    return self.member_bindings(out, owner, key);
+                }
+            }
+        }
+        Err(format!("{} has no member {:?}", class, key).into())
+    }
+
+    /// Print what `owner` binds for its member `key`: trait methods, and
+    /// class-level functions and constants.
+    fn member_bindings(&self, out: &mut String, owner: &BpyStructure, key: &str) -> Result<(), Error> {
+        // Inherited members are bound on the trait of the class that
+        // introduces them.
+        let member = if owner.properties.contains_key(key) { Member::Property } else { Member::Method };
+        let owner = match self.names.class(&owner.name).inherited_from(member, key) {
+            Some(introduced_by) => self.structure(introduced_by)?,
+            None => owner,
+        };
+        let names = self.names.class(&owner.name);
+        let bound: Vec<String> = names
+            .iter()
+            .filter(|(_, _, member)| *member == key)
+            .map(|(name, _, _)| name.to_string())
+            .collect();
+        if bound.is_empty() {
+            writeln!(out, "\n  not bound")?;
+            return Ok(());
+        }
+        // `default_const` names a property's default after its getter.
+        let constant = owner
+            .properties
+            .contains_key(key)
+            .then(|| format!("{}_DEFAULT", names.getter(key).to_string().trim_start_matches("r#").to_uppercase()));
+        let is_bound = |ident: &syn::Ident| {
+            let ident = ident.to_string();
+            let ident = ident.trim_start_matches("r#");
+            bound.iter().any(|xs| xs == ident) || constant.as_deref() == Some(ident)
+        };
+
+        writeln!(out)?;
+        if let Some(item) = self.trait_(&owner.name) {
+            let mut item = item.clone();
+            item.attrs.clear();
+            item.items.retain(|xs| matches!(xs, TraitItem::Fn(xs) if is_bound(&xs.sig.ident)));
+            for member in &mut item.items {
+                if let TraitItem::Fn(xs) = member {
+                    xs.attrs.clear();
+                }
+            }
+            if !item.items.is_empty() {
+                write_items(out, "bpy::types", vec![Item::Trait(item)]);
+            }
+        }
+        if let Some(item) = self.rna_impl(&owner.name) {
+            let mut item = item.clone();
+            item.items.retain(|xs| match xs {
+                ImplItem::Fn(xs) => is_bound(&xs.sig.ident),
+                ImplItem::Const(xs) => is_bound(&xs.ident),
+                _ => false,
+            });
+            for member in &mut item.items {
+                match member {
+                    ImplItem::Fn(xs) => {
+                        xs.attrs.clear();
+                        xs.block.stmts.clear();
+                    }
+                    ImplItem::Const(xs) => xs.attrs.clear(),
+                    _ => {}
+                }
+            }
+            if !item.items.is_empty() {
+                write_items(out, "bpy::types", vec![Item::Impl(item)]);
+            }
+        }
+        Ok(())
+    }
+
+    fn operator(&self, out: &mut String, path: &str) -> Result<(), Error> {
+        let (opmod, name) = match path.split_once('.') {
+            Some((opmod, name)) => (opmod, Some(name)),
+            None => (path, None),
+        };
+        let operators = self
+            .schema
+            .operators
+            .get(opmod)
+            .ok_or_else(|| format!("no operator module {:?} in the schema", opmod))?;
+        let Some(name) = name else {
+            writeln!(out, "ops.{}: {} operators", opmod, operators.len())?;
+            for name in operators.keys() {
+                writeln!(out, "  {}", name)?;
+            }
+            return Ok(());
+        };
+        let operator = operators
+            .get(name)
+            .ok_or_else(|| format!("no operator {:?} in ops.{}", name, opmod))?;
+
+        writeln!(out, "ops.{}.{}: operator", opmod, name)?;
+        line(out, "description", &operator.description)?;
+        parameter_list(out, &operator.parameters)?;
+
+        let names = OperatorNames::new(&self.schema.operators);
+        let (module, function) = (names.module(opmod), names.operator(opmod, name));
+        let found = module_items(&self.file.items, &["bpy", "ops", &module.to_string()]).and_then(|items| {
+            items.iter().find_map(|item| match item {
+                Item::Fn(xs) if xs.sig.ident == function => Some(xs),
+                _ => None,
+            })
+        });
+        if let Some(item) = found {
+            let mut item = item.clone();
+            item.attrs.clear();
+            item.block.stmts.clear();
+            writeln!(out)?;
+            write_items(out, &format!("bpy::ops::{}", module), vec![Item::Fn(item)]);
+        }
+        Ok(())
+    }
+
+    fn trait_(&self, class: &str) -> Option<&syn::ItemTrait> {
+        let ident = class.to_upper_camel_case();
+        module_items(&self.file.items, &["bpy", "types"])?
+            .iter()
+            .find_map(|item| match item {
+                Item::Trait(xs) if xs.ident == ident => Some(xs),
+                _ => None,
+            })
+    }
+
+    /// `impl rna::Class`, which holds class-level functions and constants.
+    fn rna_impl(&self, class: &str) -> Option<&syn::ItemImpl> {
+        let ident = class.to_upper_camel_case();
+        module_items(&self.file.items, &["bpy", "types"])?
+            .iter()
+            .find_map(|item| match item {
+                Item::Impl(xs) if xs.trait_.is_none() => match &*xs.self_ty {
+                    syn::Type::Path(ty) if ty.path.segments.last().is_some_and(|xs| xs.ident == ident) => Some(xs),
+                    _ => None,
+                },
+                _ => None,
+            })
+    }
+}
+
+/// The items of the module at `path` under `items`.
+fn module_items<'a>(items: &'a [Item], path: &[&str]) -> Option<&'a [Item]> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(items);
+    };
+    items.iter().find_map(|item| match item {
+        Item::Mod(xs) if xs.ident == first => module_items(&xs.content.as_ref()?.1, rest),
+        _ => None,
+    })
+}
+
+/// Print `items`, found in `module`. Function bodies (emptied by the caller)
+/// are left out.
+fn write_items(out: &mut String, module: &str, items: Vec<Item>) {
+    let file = File {
+        shebang: None,
+        attrs: Vec::new(),
+        items,
+    };
+    let _ = writeln!(out, "  // in {}", module);
+    for line in prettyplease::unparse(&file).lines() {
+        let is_fn = line.trim_start().starts_with("fn ") || line.trim_start().starts_with("pub fn ");
+        let line = match line.strip_suffix(" {}") {
+            Some(signature) if is_fn || signature.trim_start().starts_with(')') => format!("{};", signature),
+            _ => line.to_string(),
+        };
+        let _ = writeln!(out, "  {}", line);
+    }
+}
+
+fn line(out: &mut String, label: &str, value: &str) -> std::fmt::Result {
+    if value.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "  {}: {}", label, value)
+}
+
+/// The property's type, as the schema tags it: `float[]`, `enum`.
+fn kind(property: &BpyProperty) -> String {
+    serde_json::to_value(property)
+        .ok()
+        .and_then(|xs| xs.as_object()?.keys().next().cloned())
+        .unwrap_or_default()
+}
+
+/// A schema enum, as the schema writes it: `TRANSLATION`.
+fn tag(value: &impl serde::Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|xs| xs.as_str().map(|xs| xs.to_string()))
+        .unwrap_or_default()
+}
+
+/// Everything the schema says about `property`.
+fn describe(out: &mut String, property: &BpyProperty) -> std::fmt::Result {
+    let item = property.as_item();
+    line(out, "name", item.name.as_deref().unwrap_or_default())?;
+    line(out, "description", item.description.as_deref().unwrap_or_default())?;
+    writeln!(out, "  subtype: {}, unit: {}", tag(&item.subtype), tag(&item.unit))?;
+
+    match property {
+        BpyProperty::Boolean { default, .. } => writeln!(out, "  default: {}", default)?,
+        BpyProperty::BooleanArray { array, .. } => {
+            writeln!(out, "  length: {}, dimensions: {:?}", array.length, array.dimensions)?;
+            writeln!(out, "  default: {:?}", array.default)?;
+        }
+        BpyProperty::Int { number, default, .. } => {
+            range(out, number)?;
+            writeln!(out, "  default: {}", default)?;
+        }
+        BpyProperty::IntArray { array, number, .. } => {
+            writeln!(out, "  length: {}, dimensions: {:?}", array.length, array.dimensions)?;
+            range(out, number)?;
+            writeln!(out, "  default: {:?}", array.default)?;
+        }
+        BpyProperty::Float { number, default, .. } => {
+            range(out, number)?;
+            writeln!(out, "  default: {}", default)?;
+        }
+        BpyProperty::FloatArray { array, number, .. } => {
+            writeln!(out, "  length: {}, dimensions: {:?}", array.length, array.dimensions)?;
+            range(out, number)?;
+            writeln!(out, "  default: {:?}", array.default)?;
+        }
+        BpyProperty::String { length_max, default, .. } => {
+            if *length_max > 0 {
+                writeln!(out, "  max length: {}", length_max)?;
+            }
+            writeln!(out, "  default: {:?}", default.as_deref())?;
+        }
+        BpyProperty::Enum { items, default, .. } => {
+            let ids: Vec<&str> = items.iter().map(|xs| xs.id.as_str()).collect();
+            writeln!(out, "  items: {}", ids.join(", "))?;
+            writeln!(out, "  default: {:?}", default.as_deref())?;
+        }
+        BpyProperty::Pointer {
+            fixed_type,
+            unresolved_type,
+            ..
+        } => {
+            writeln!(out, "  type: {}", fixed_type)?;
+            if let Some(unresolved) = unresolved_type {
+                writeln!(out, "  reported type: {} (not in bpy.types)", unresolved)?;
+            }
+        }
+        BpyProperty::Collection {
+            fixed_type,
+            collection,
+            unresolved_type,
+            ..
+        } => {
+            writeln!(out, "  type: {}", fixed_type)?;
+            if let Some(collection) = collection {
+                writeln!(out, "  collection: {}", collection)?;
+            }
+            if let Some(unresolved) = unresolved_type {
+                writeln!(out, "  reported type: {} (not in bpy.types)", unresolved)?;
+            }
+        }
+    }
+
+    let flags: Vec<&str> = [
+        (item.is_readonly, "read-only"),
+        (item.is_animatable, "animatable"),
+        (item.is_overridable, "overridable"),
+        (item.is_never_none, "never none"),
+        (item.is_required, "required"),
+        (item.is_argument_optional, "optional argument"),
+        (item.is_runtime, "runtime"),
+        (item.is_output, "output"),
+    ]
+    .into_iter()
+    .filter_map(|(set, flag)| set.then_some(flag))
+    .collect();
+    if !flags.is_empty() {
+        writeln!(out, "  flags: {}", flags.join(", "))?;
+    }
+    Ok(())
+}
+
+fn range<T: std::fmt::Display>(out: &mut String, number: &blextism_schema::BpyPropertyNumber<T>) -> std::fmt::Result {
+    writeln!(
+        out,
+        "  range: {}..={} (soft {}..={})",
+        number.hard_min, number.hard_max, number.soft_min, number.soft_max
+    )
+}
+
+/// One line per parameter: `name: kind = default [flags]`.
+fn parameter_list(out: &mut String, parameters: &[BpyProperty]) -> std::fmt::Result {
+    if parameters.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "  parameters:")?;
+    for parameter in parameters {
+        let item = parameter.as_item();
+        let detail = match parameter {
+            BpyProperty::Pointer { fixed_type, .. } | BpyProperty::Collection { fixed_type, .. } => {
+                format!(" {}", fixed_type)
+            }
+            BpyProperty::Enum { items, .. } => {
+                let ids: Vec<&str> = items.iter().map(|xs| xs.id.as_str()).collect();
+                format!(" {{{}}}", ids.join(", "))
+            }
+            _ => String::new(),
+        };
+        let mut flags = Vec::new();
+        if item.is_output {
+            flags.push("output");
+        } else if item.is_required {
+            flags.push("required");
+        }
+        if item.is_never_none {
+            flags.push("never none");
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(", "))
+        };
+        writeln!(out, "    {}: {}{}{}", item.identifier, kind(parameter), detail, flags)?;
+    }
+    Ok(())
+}
+
+fn catalog_method(out: &mut String, catalog: Option<&CatalogClass>, key: &str) -> std::fmt::Result {
+    let Some(method) = catalog.and_then(|xs| xs.methods.get(key)) else {
+        return Ok(());
+    };
+    line(out, "description", &method.description)?;
+    if !method.params.is_empty() {
+        writeln!(out, "  parameters:")?;
+    }
+    for param in &method.params {
+        let mut flags = Vec::new();
+        if param.optional {
+            flags.push("optional");
+        }
+        if param.out {
+            flags.push("out");
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(", "))
+        };
+        writeln!(out, "    {}: {}{}", param.name, param.ty, flags)?;
+    }
+    if let Some(returns) = &method.returns {
+        writeln!(out, "  returns: {}", returns)?;
+    }
+    Ok(())
+}
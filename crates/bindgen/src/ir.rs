@@ -0,0 +1,454 @@
+use std::collections::HashSet;
+
+use blextism_schema::{BpyMethod, BpyProperty, BpyPropertyEnumItem, BpyStructure, Schema};
+use heck::ToSnekCase;
+use smartstring::alias::String;
+
+use crate::catalog::{Catalog, TypeRef};
+use crate::ids::Ids;
+use crate::names::SchemaNames;
+
+/// The `bpy` API as the backends for other languages see it: every member
+/// named as in Rust, with the host call that implements it and the types it
+/// takes and returns. The WIT and TypeScript backends both render this, so
+/// they bind the same members, the same way, as the Rust bindings.
+pub struct Api {
+    /// The Blender version, `"4.0"`.
+    pub version: Option<String>,
+    /// Every class but `type`, in schema order.
+    pub classes: Vec<Class>,
+    pub operators: Vec<OperatorModule>,
+}
+
+pub struct Class {
+    /// The class's name in `bpy.types`.
+    pub name: String,
+    /// The classes it extends that are in [`Api::classes`], parent first.
+    pub bases: Vec<String>,
+    pub members: Vec<Member>,
+}
+
+pub struct Member {
+    /// The member's name in the Rust bindings, without `r#`.
+    pub name: std::string::String,
+    pub description: String,
+    /// The attribute or method the host is asked for.
+    pub key: String,
+    /// The id sent as `@id`; see [`Ids`].
+    pub id: u32,
+    pub kind: MemberKind,
+}
+
+pub enum MemberKind {
+    /// `getattr(self, key)`.
+    Get { ty: Ty },
+    /// `setattr(self, key, value)`.
+    Set { ty: Ty },
+    /// `self.property_unset(key)`.
+    Reset,
+    /// `self.is_property_set(key)`.
+    IsSet,
+    /// `self.key(*args, **kwargs)`, with the required parameters passed
+    /// positionally and the rest as keywords.
+    Method {
+        receiver: Receiver,
+        params: Vec<Param>,
+        returns: Option<Ty>,
+        /// The index of the positional argument the host fills in and
+        /// responds with, sent as `@out`.
+        out: Option<usize>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Receiver {
+    Instance,
+    /// A class-level function, called on `bpy.types.<Class>` and sent with
+    /// `@class`.
+    Class,
+    /// A class-level function that receives the class it's called on, which
+    /// can be a subclass.
+    ClassOf,
+}
+
+pub struct Param {
+    /// The parameter's name on the host, which is also its keyword.
+    pub name: String,
+    pub description: String,
+    pub ty: Ty,
+    pub required: bool,
+}
+
+/// Only pointers can be `None`: the host answers for any other property
+/// with a value.
+#[derive(Clone)]
+pub enum Ty {
+    Bool,
+    Int,
+    /// An integer that can't go below zero.
+    UInt,
+    Float,
+    String,
+    /// Anything, as JSON.
+    Json,
+    List(Box<Ty>),
+    Optional(Box<Ty>),
+    Enum(Enum),
+    /// An instance of the class; `bpy_struct` if the schema doesn't have it.
+    Struct(String),
+    /// A `bpy_prop_collection` of `item`, with the methods of `class`.
+    Collection {
+        item: String,
+        class: Option<String>,
+    },
+}
+
+/// An enum property's items. The getter and setter of one property share
+/// a `path`, so backends can define the type once.
+#[derive(Clone)]
+pub struct Enum {
+    /// Where the enum is from: the class, the method if it's a parameter,
+    /// and the property.
+    pub path: Vec<String>,
+    pub description: String,
+    /// Empty when the items are only known at runtime.
+    pub items: Vec<BpyPropertyEnumItem>,
+    /// Whether values are sets of the items.
+    pub is_flag: bool,
+}
+
+pub struct OperatorModule {
+    /// The module's name in `bpy.ops`.
+    pub name: String,
+    /// What the host is asked for.
+    pub key: std::string::String,
+    pub operators: Vec<Operator>,
+}
+
+pub struct Operator {
+    /// The name to bind, which overlays can change.
+    pub name: String,
+    pub key: std::string::String,
+    pub description: String,
+    /// Every parameter is passed as a keyword.
+    pub params: Vec<Param>,
+}
+
+/// The names taken in one namespace of a generated interface.
+#[derive(Default)]
+pub struct Names {
+    taken: HashSet<std::string::String>,
+}
+
+impl Names {
+    /// Take `wanted`, or `{wanted}2` (and so on) if it's taken.
+    pub fn claim(&mut self, wanted: &str) -> std::string::String {
+        let mut name = wanted.to_string();
+        let mut n = 2;
+        while !self.taken.insert(name.clone()) {
+            name = format!("{}{}", wanted, n);
+            n += 1;
+        }
+        name
+    }
+}
+
+impl Api {
+    pub fn new(schema: &Schema, catalog: &Catalog, ids: &Ids) -> Self {
+        let known: HashSet<&str> = schema
+            .classes
+            .iter()
+            .filter(|xs| xs.name != "type")
+            .map(|xs| xs.name.as_str())
+            .collect();
+        let names = SchemaNames::new(&schema.classes, catalog);
+        let lower = Lower { known: &known, catalog, names: &names, ids };
+
+        let classes = schema
+            .classes
+            .iter()
+            .filter(|xs| xs.name != "type")
+            .map(|class| lower.class(class))
+            .collect();
+
+        let operators = schema
+            .operators
+            .iter()
+            .map(|(opmod, operators)| OperatorModule {
+                name: opmod.clone(),
+                key: opmod.as_str().to_snek_case(),
+                operators: operators
+                    .iter()
+                    .map(|(key, operator)| Operator {
+                        name: operator.identifier.clone().unwrap_or_else(|| key.clone()),
+                        key: key.as_str().to_snek_case(),
+                        description: operator.description.clone(),
+                        params: operator
+                            .parameters
+                            .iter()
+                            .filter(|xs| !xs.is_output())
+                            .map(|parameter| lower.param(parameter, &[]))
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Api {
+            version: schema.version.clone(),
+            classes,
+            operators,
+        }
+    }
+
+    pub fn class(&self, name: &str) -> Option<&Class> {
+        self.classes.iter().find(|xs| xs.name == name)
+    }
+}
+
+/// Turns schema and catalog types into [`Ty`]s.
+struct Lower<'a> {
+    known: &'a HashSet<&'a str>,
+    catalog: &'a Catalog,
+    names: &'a SchemaNames,
+    ids: &'a Ids,
+}
+
+impl Lower<'_> {
+    fn class(&self, class: &BpyStructure) -> Class {
+        let names = self.names.class(&class.name);
+        let name = |ident: proc_macro2::Ident| ident.to_string().trim_start_matches("r#").to_string();
+        let unset_id = self.ids.get("bpy_struct", "property_unset");
+        let is_set_id = self.ids.get("bpy_struct", "is_property_set");
+
+        let mut members = Vec::new();
+        for (key, property) in &class.properties {
+            let item = property.as_item();
+            let path = [class.name.clone(), key.clone()];
+            let id = self.ids.get(&class.name, key);
+            members.push(Member {
+                name: name(names.getter(key)),
+                description: item.description.clone().unwrap_or_default(),
+                key: key.clone(),
+                id,
+                kind: MemberKind::Get {
+                    ty: self.ty(property, &path, true),
+                },
+            });
+            if item.is_readonly {
+                continue;
+            }
+            members.push(Member {
+                name: name(names.setter(key)),
+                description: String::new(),
+                key: key.clone(),
+                id,
+                kind: MemberKind::Set {
+                    ty: self.ty(property, &path, true),
+                },
+            });
+            members.push(Member {
+                name: name(names.reset(key)),
+                description: String::new(),
+                key: key.clone(),
+                id: unset_id,
+                kind: MemberKind::Reset,
+            });
+            members.push(Member {
+                name: name(names.is_set(key)),
+                description: String::new(),
+                key: key.clone(),
+                id: is_set_id,
+                kind: MemberKind::IsSet,
+            });
+        }
+
+        for (key, method) in &class.methods {
+            let BpyMethod::Rna {
+                description,
+                use_self,
+                use_self_type,
+                parameters,
+                ..
+            } = method
+            else {
+                continue;
+            };
+            // As in Rust, functions with several outputs aren't bound.
+            if parameters.iter().filter(|xs| xs.is_output()).count() > 1 {
+                continue;
+            }
+            let path = [class.name.clone(), key.clone()];
+            let mut params = Vec::new();
+            let mut returns = None;
+            for parameter in parameters {
+                if parameter.is_output() {
+                    let path = [&path[..], std::slice::from_ref(&parameter.as_item().identifier)].concat();
+                    returns = Some(self.ty(parameter, &path, true));
+                } else {
+                    params.push(self.param(parameter, &path));
+                }
+            }
+            members.push(Member {
+                name: name(names.method(key)),
+                description: description.clone(),
+                key: key.as_str().to_snek_case().into(),
+                id: self.ids.get(&class.name, key),
+                kind: MemberKind::Method {
+                    receiver: match (use_self, use_self_type) {
+                        (true, _) => Receiver::Instance,
+                        (false, false) => Receiver::Class,
+                        (false, true) => Receiver::ClassOf,
+                    },
+                    params,
+                    returns,
+                    out: None,
+                },
+            });
+        }
+
+        if let Some(catalog) = self.catalog.class(&class.name) {
+            let in_schema = |name: &str| {
+                class.properties.contains_key(name) || matches!(class.methods.get(name), Some(BpyMethod::Rna { .. }))
+            };
+            for (key, method) in catalog.methods.iter().filter(|(xs, _)| !in_schema(xs)) {
+                let params: Vec<Param> = method
+                    .params
+                    .iter()
+                    .map(|param| Param {
+                        name: param.name.as_str().into(),
+                        description: param.description.as_str().into(),
+                        ty: self.type_ref(&param.ty),
+                        required: !param.optional,
+                    })
+                    .collect();
+                // A method that fills a buffer returns it, as in Rust.
+                let positional: Vec<_> = method.params.iter().filter(|xs| !xs.optional).collect();
+                let out = positional.iter().position(|xs| xs.out);
+                let returns = match out {
+                    Some(idx) => Some(&positional[idx].ty),
+                    None => method.returns.as_ref(),
+                };
+                members.push(Member {
+                    name: name(names.method(key)),
+                    description: method.description.as_str().into(),
+                    key: key.as_str().into(),
+                    id: self.ids.get(&class.name, key),
+                    kind: MemberKind::Method {
+                        receiver: Receiver::Instance,
+                        params,
+                        returns: returns.map(|xs| self.type_ref(xs)),
+                        out,
+                    },
+                });
+            }
+            for (key, property) in catalog.properties.iter().filter(|(xs, _)| !in_schema(xs)) {
+                let id = self.ids.get(&class.name, key);
+                members.push(Member {
+                    name: name(names.getter(key)),
+                    description: property.description.as_str().into(),
+                    key: key.as_str().into(),
+                    id,
+                    kind: MemberKind::Get {
+                        ty: self.type_ref(&property.ty),
+                    },
+                });
+                if !property.readonly {
+                    members.push(Member {
+                        name: name(names.setter(key)),
+                        description: String::new(),
+                        key: key.as_str().into(),
+                        id,
+                        kind: MemberKind::Set {
+                            ty: self.type_ref(&property.ty),
+                        },
+                    });
+                }
+            }
+        }
+
+        Class {
+            name: class.name.clone(),
+            bases: class
+                .supers()
+                .filter(|xs| self.known.contains(xs))
+                .map(|xs| xs.into())
+                .collect(),
+            members,
+        }
+    }
+
+    fn param(&self, parameter: &BpyProperty, path: &[String]) -> Param {
+        let item = parameter.as_item();
+        let path = [path, std::slice::from_ref(&item.identifier)].concat();
+        Param {
+            name: item.identifier.clone(),
+            description: item.description.clone().unwrap_or_default(),
+            ty: self.ty(parameter, &path, false),
+            required: item.is_required,
+        }
+    }
+
+    /// The type of `property`; pointers are optional if `nullable` and the
+    /// schema says they can be `None`. Integers are unsigned when they
+    /// can't go below zero, as in Rust.
+    fn ty(&self, property: &BpyProperty, path: &[String], nullable: bool) -> Ty {
+        let unsigned = |min: i64, soft_min: i64| min == soft_min && soft_min == 0;
+        match property {
+            BpyProperty::Boolean { .. } => Ty::Bool,
+            BpyProperty::BooleanArray { .. } => Ty::List(Box::new(Ty::Bool)),
+            BpyProperty::Int { number, .. } if unsigned(number.hard_min, number.soft_min) => Ty::UInt,
+            BpyProperty::Int { .. } => Ty::Int,
+            BpyProperty::IntArray { number, .. } if unsigned(number.hard_min, number.soft_min) => {
+                Ty::List(Box::new(Ty::UInt))
+            }
+            BpyProperty::IntArray { .. } => Ty::List(Box::new(Ty::Int)),
+            BpyProperty::Float { .. } => Ty::Float,
+            BpyProperty::FloatArray { .. } => Ty::List(Box::new(Ty::Float)),
+            BpyProperty::String { .. } => Ty::String,
+            BpyProperty::Enum { item, items, is_flag, .. } => Ty::Enum(Enum {
+                path: path.to_vec(),
+                description: item.description.clone().unwrap_or_default(),
+                items: items.clone(),
+                is_flag: *is_flag,
+            }),
+            BpyProperty::Pointer { item, fixed_type, .. } => {
+                let ty = Ty::Struct(self.struct_(fixed_type));
+                if nullable && !item.is_never_none {
+                    Ty::Optional(Box::new(ty))
+                } else {
+                    ty
+                }
+            }
+            BpyProperty::Collection {
+                fixed_type, collection, ..
+            } => Ty::Collection {
+                item: self.struct_(fixed_type),
+                class: collection.clone().filter(|xs| self.known.contains(xs.as_str())),
+            },
+        }
+    }
+
+    fn type_ref(&self, ty: &TypeRef) -> Ty {
+        match ty {
+            TypeRef::Bool => Ty::Bool,
+            TypeRef::Int => Ty::Int,
+            TypeRef::Float => Ty::Float,
+            TypeRef::Str => Ty::String,
+            TypeRef::Any => Ty::Json,
+            TypeRef::Struct(class) => Ty::Struct(self.struct_(class)),
+            TypeRef::Array(inner) => Ty::List(Box::new(self.type_ref(inner))),
+            TypeRef::Optional(inner) => Ty::Optional(Box::new(self.type_ref(inner))),
+        }
+    }
+
+    /// `class`, or `bpy_struct` for classes the schema doesn't have, as in
+    /// Rust.
+    fn struct_(&self, class: &str) -> String {
+        if self.known.contains(class) {
+            class.into()
+        } else {
+            "bpy_struct".into()
+        }
+    }
+}
@@ -0,0 +1,1282 @@
+//! Generate Rust bindings to Blender's Python API from the schema
+//! `genschema.py` reflects out of Blender. [`Builder`] drives it, from the
+//! `blextism-bindgen` CLI or from a build script.
+
+mod builder;
+mod catalog;
+pub mod diff;
+mod ids;
+pub mod inspect;
+mod ir;
+mod lint;
+mod names;
+mod overlay;
+mod select;
+mod tree;
+mod ts;
+mod versions;
+mod wit;
+
+pub use builder::{load_schema, Bindings, Builder, Error, SchemaLint};
+pub use lint::{Lint, LintKind};
+
+use heck::{ToSnekCase, ToUpperCamelCase};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+use blextism_schema::*;
+use catalog::{Catalog, CatalogClass};
+use ids::Ids;
+use names::{ClassNames, DataNames, Member, OperatorNames, SchemaNames};
+use smartstring::alias::String;
+use std::collections::{BTreeMap, HashSet};
+
+trait PropertyCodegen {
+    fn as_method_parameter(&self, argname: &Ident, extra_items: &mut Vec<TokenStream>) -> TokenStream;
+    fn as_setter_parameter_type(&self, extra_items: &mut Vec<TokenStream>) -> TokenStream;
+    fn as_return_type(
+        &self,
+        extra_items: &mut Vec<TokenStream>,
+        defined: &mut HashSet<std::string::String>,
+    ) -> TokenStream;
+    fn as_parsed_intermediate_value(&self) -> TokenStream;
+}
+
+impl PropertyCodegen for BpyProperty {
+    fn as_method_parameter(&self, argname: &Ident, _extra_items: &mut Vec<TokenStream>) -> TokenStream {
+        let tk = match self {
+            BpyProperty::Boolean { .. } => quote! { bool },
+            BpyProperty::BooleanArray { item: _, array: _ } => quote! { &[bool] },
+            BpyProperty::Int { number, .. } => {
+                if number.hard_min == number.soft_min && number.soft_min == 0 {
+                    quote! { u64 }
+                } else {
+                    quote! { i64 }
+                }
+            }
+            BpyProperty::IntArray {
+                item: _,
+                array: _,
+                number,
+            } => {
+                if number.hard_min == number.soft_min && number.soft_min == 0 {
+                    quote! { Vec<u64> }
+                } else {
+                    quote! { Vec<i64> }
+                }
+            }
+            BpyProperty::Float { .. } => quote! { f64 },
+            BpyProperty::FloatArray {
+                item: _,
+                array: _,
+                number: _,
+            } => quote! { &[f64] },
+            BpyProperty::String {
+                item: _,
+                length_max: _,
+                default: _,
+            } => quote! { &str },
+            BpyProperty::Enum { .. } => {
+                // for now, enums are strings.
+                quote! { &str }
+            }
+
+            BpyProperty::Pointer {
+                item: _,
+                fixed_type: _,
+                ..
+            } => {
+                quote! { BpyPtr }
+            }
+            BpyProperty::Collection {
+                item: _,
+                fixed_type: _,
+                collection: _,
+                ..
+            } => {
+                quote! { BpyPtr }
+            }
+        };
+
+        if !self.as_item().is_argument_optional {
+            quote! { #argname: #tk }
+        } else {
+            quote! { #argname: Option<#tk> }
+        }
+    }
+
+    fn as_setter_parameter_type(&self, _extra_items: &mut Vec<TokenStream>) -> TokenStream {
+        let tk = match self {
+            BpyProperty::Boolean { .. } => quote! { bool },
+            BpyProperty::BooleanArray { item: _, array: _ } => quote! { &[bool] },
+            BpyProperty::Int { number, .. } => {
+                if number.hard_min == number.soft_min && number.soft_min == 0 {
+                    quote! { u64 }
+                } else {
+                    quote! { i64 }
+                }
+            }
+            BpyProperty::IntArray {
+                item: _,
+                array: _,
+                number,
+            } => {
+                if number.hard_min == number.soft_min && number.soft_min == 0 {
+                    quote! { Vec<u64> }
+                } else {
+                    quote! { Vec<i64> }
+                }
+            }
+            BpyProperty::Float { .. } => quote! { f64 },
+            BpyProperty::FloatArray {
+                item: _,
+                array: _,
+                number: _,
+            } => quote! { &[f64] },
+            BpyProperty::String {
+                item: _,
+                length_max: _,
+                default: _,
+            } => quote! { &str },
+            BpyProperty::Enum { .. } => {
+                // for now, enums are strings.
+                quote! { &str }
+            }
+
+            BpyProperty::Pointer {
+                item: _,
+                fixed_type: _,
+                ..
+            } => {
+                quote! { BpyPtr }
+            }
+            BpyProperty::Collection {
+                item: _,
+                fixed_type: _,
+                collection: _,
+                ..
+            } => {
+                quote! { BpyPtr }
+            }
+        };
+
+        // `None` clears a pointer. Other properties return to their default
+        // through the generated `reset_*` method instead.
+        match self {
+            BpyProperty::Pointer { item, .. } if !item.is_never_none => quote! { Option<#tk> },
+            _ => tk,
+        }
+    }
+
+    fn as_return_type(
+        &self,
+        extra_items: &mut Vec<TokenStream>,
+        defined: &mut HashSet<std::string::String>,
+    ) -> TokenStream {
+        let tk = match self {
+            BpyProperty::Boolean { .. } => quote! { bool },
+            BpyProperty::BooleanArray { item: _, array: _ } => quote! { Vec<bool> },
+            BpyProperty::Int { number, .. } => {
+                if number.hard_min == number.soft_min && number.soft_min == 0 {
+                    quote! { u64 }
+                } else {
+                    quote! { i64 }
+                }
+            }
+            BpyProperty::IntArray {
+                item: _,
+                array: _,
+                number,
+            } => {
+                if number.hard_min == number.soft_min && number.soft_min == 0 {
+                    quote! { Vec<u64> }
+                } else {
+                    quote! { Vec<i64> }
+                }
+            }
+            BpyProperty::Float { .. } => quote! { f64 },
+            BpyProperty::FloatArray {
+                item: _,
+                array: _,
+                number: _,
+            } => quote! { Vec<f64> },
+            BpyProperty::String {
+                item: _,
+                length_max: _,
+                default: _,
+            } => quote! { String },
+            BpyProperty::Enum { .. } => {
+                // for now, enums are strings.
+                quote! { String }
+            }
+
+            BpyProperty::Pointer {
+                item: _,
+                fixed_type,
+                ..
+            } => {
+                let ident = format_ident!("{}", fixed_type.as_str().to_upper_camel_case());
+                quote! { Box<dyn #ident + Send + Sync> }
+            }
+            BpyProperty::Collection {
+                item: _,
+                fixed_type,
+                collection,
+                ..
+            } => {
+                let collection_constraint = collection
+                    .as_ref()
+                    .map(|c| {
+                        let ident = format_ident!("{}", c.as_str().to_upper_camel_case());
+                        quote! { : #ident + BpyPropCollection }
+                    })
+                    .unwrap_or_else(|| {
+                        quote! { : BpyStruct + BpyPropCollection }
+                    });
+
+                let target_type = format_ident!("{}", fixed_type.as_str().to_upper_camel_case());
+                let return_type_str = format!(
+                    "BpyPropCollection_{}",
+                    collection.as_ref().unwrap_or(fixed_type)
+                )
+                .to_upper_camel_case();
+                let return_type_ident = format_ident!("{}", return_type_str);
+
+                // TODO: how to push the target_type back up?
+                if !defined.contains(&return_type_str) {
+                    defined.insert(return_type_str);
+                    extra_items.push(quote! {
+                        pub trait #return_type_ident #collection_constraint {
+                            fn get(&self, key: &str) -> Option<Box<dyn #target_type + Send + Sync>>;
+                            fn keys(&self) -> Vec<String>;
+                            fn values(&self) -> Vec<Box<dyn #target_type + Send + Sync>>;
+                            fn items(&self) -> Vec<(String, Box<dyn #target_type + Send + Sync>)>;
+                        }
+
+                        impl #return_type_ident for BpyPtr {
+                            fn get(&self, key: &str) -> Option<Box<dyn #target_type + Send + Sync>> {
+                                get(self, key)
+                                    .map(Box::new)
+                                    .map(|bx| bx as Box<dyn #target_type + Send + Sync>)
+                            }
+                            fn keys(&self) -> Vec<String> {
+                                keys(self)
+                            }
+
+                            fn values(&self) -> Vec<Box<dyn #target_type + Send + Sync>> {
+                                values(self).into_iter().map(|xs| Box::new(xs) as Box<dyn #target_type + Send + Sync>).collect()
+                            }
+
+                            fn items(&self) -> Vec<(String, Box<dyn #target_type + Send + Sync>)> {
+                                items(self).into_iter().map(|(k, xs)| (k, Box::new(xs) as Box<dyn #target_type + Send + Sync>)).collect()
+                            }
+                        }
+                    });
+                }
+
+                quote! { Box<dyn #return_type_ident + Send + Sync> }
+            }
+        };
+
+        if self.as_item().is_never_none {
+            tk
+        } else {
+            quote! { Option<#tk> }
+        }
+    }
+
+    fn as_parsed_intermediate_value(&self) -> TokenStream {
+        match self {
+            BpyProperty::Boolean { .. }
+            | BpyProperty::Int { .. }
+            | BpyProperty::Float { .. }
+            | BpyProperty::String { .. }
+            | BpyProperty::BooleanArray { .. }
+            | BpyProperty::IntArray { .. }
+            | BpyProperty::FloatArray { .. } => {
+                quote! {
+                    serde_json::from_value(bpy_output).expect("expected to deserialize appropriately")
+                }
+            }
+            BpyProperty::Pointer { item, fixed_type, .. } => {
+                let target_type = format_ident!("{}", fixed_type.as_str().to_upper_camel_case());
+                if item.is_never_none {
+                    quote! {
+                        let result: BpyPtr = serde_json::from_value(bpy_output).expect("TKTK(improve this msg): expected an floating-point value");
+                        Box::new(result) as Box<dyn #target_type + Send + Sync>
+                    }
+                } else {
+                    quote! {
+                        let result: Option<BpyPtr> = serde_json::from_value(bpy_output).expect("TKTK(improve this msg): expected an floating-point value");
+                        match result {
+                            Some(xs) => Some(Box::new(xs) as Box<dyn #target_type + Send + Sync>),
+                            None => None,
+                        }
+                    }
+                }
+            }
+
+            BpyProperty::Collection {
+                item,
+                fixed_type,
+                collection,
+                ..
+            } => {
+                let _target_type = format_ident!("{}", fixed_type.as_str().to_upper_camel_case());
+                let return_type_str = format!(
+                    "BpyPropCollection_{}",
+                    collection.as_ref().unwrap_or(fixed_type)
+                )
+                .to_upper_camel_case();
+                let return_type_ident = format_ident!("{}", return_type_str);
+
+                if item.is_never_none {
+                    quote! {
+                        let result: BpyPtr = serde_json::from_value(bpy_output).expect("TKTK(improve this msg): expected an floating-point value");
+                        Box::new(result) as Box<dyn #return_type_ident + Send + Sync>
+                    }
+                } else {
+                    quote! {
+                        let result: Option<BpyPtr> = serde_json::from_value(bpy_output).expect("TKTK(improve this msg): expected an floating-point value");
+
+                        match result {
+                            Some(xs) => Some(Box::new(xs) as Box<dyn #return_type_ident + Send + Sync>),
+                            None => None,
+                        }
+                    }
+                }
+            }
+
+            BpyProperty::Enum { .. } => {
+                quote! { serde_json::from_value(bpy_output).expect("expected to deserialize appropriately") }
+            }
+        }
+    }
+}
+
+fn method_codegen(
+    class: &str,
+    methods: &BTreeMap<String, BpyMethod>,
+    names: &ClassNames,
+    ids: &Ids,
+    defined: &mut HashSet<std::string::String>,
+    _name: &Ident,
+) -> (TokenStream, TokenStream, TokenStream, TokenStream) {
+    let mut impl_members: Vec<TokenStream> = Vec::with_capacity(16);
+    let mut trait_members: Vec<TokenStream> = Vec::with_capacity(16);
+    let mut extra_items: Vec<TokenStream> = Vec::with_capacity(16);
+    let mut class_members: Vec<TokenStream> = Vec::with_capacity(4);
+
+    for (func_name, method) in methods {
+        match method {
+            BpyMethod::Rna {
+                description,
+                use_self,
+                use_self_type,
+                parameters,
+                ..
+            } => {
+                let id = ids.get(class, func_name.as_str());
+
+                let (outputs, inputs): (Vec<_>, Vec<_>) =
+                    parameters.iter().partition(|xs| xs.is_output());
+
+                if outputs.len() > 1 {
+                    continue;
+                }
+                // Bound on the trait that introduces it, which this one extends.
+                if names.inherited_from(Member::Method, func_name).is_some() {
+                    continue;
+                }
+
+                let func_name_ident = names.method(func_name);
+                let alias = names::alias(func_name, &func_name_ident);
+                let func_name = func_name.as_str().to_snek_case();
+
+                let (params, kwargs): (Vec<_>, Vec<_>) =
+                    inputs.into_iter().partition(|xs| xs.as_item().is_required);
+
+                let param_names = names::params(
+                    params.iter().map(|prop| prop.as_item().identifier.as_str()),
+                    !kwargs.is_empty(),
+                );
+                let mut params_list: Vec<TokenStream> = params
+                    .iter()
+                    .zip(&param_names)
+                    .map(|(prop, argname)| prop.as_method_parameter(argname, &mut extra_items))
+                    .collect();
+
+                let kwargs_bpy = if kwargs.is_empty() {
+                    quote! { None }
+                } else {
+                    quote! { Some(kwargs) }
+                };
+
+                let description = if !kwargs.is_empty() {
+                    let kwargs_docs = kwargs
+                        .iter()
+                        .map(|prop| {
+                            let item = prop.as_item();
+                            format!(
+                                "- {}: {}",
+                                item.identifier,
+                                item.description.clone().unwrap_or_default()
+                            )
+                        })
+                        .reduce(|lhs, rhs| format!("{}\n{}", lhs, rhs))
+                        .unwrap_or_default();
+
+                    format!("{}\n{}", description, kwargs_docs)
+                } else {
+                    description.as_str().to_string()
+                };
+
+                if !kwargs.is_empty() {
+                    params_list.push(quote! { kwargs: Kwargs });
+                }
+
+                let into_pyargs: TokenStream = param_names.iter()
+                    .fold(TokenStream::new(), |stream, tk| { quote! { #stream serde_json::to_value(#tk).expect("pyarg must be serializable"), } });
+
+                let return_type = if outputs.is_empty() {
+                    quote! {}
+                } else {
+                    let output = outputs[0].as_return_type(&mut extra_items, defined);
+                    quote! {
+                        -> #output
+                    }
+                };
+
+                let (assign_to, from_serde_value) = if outputs.is_empty() {
+                    (quote! {}, quote! {})
+                } else {
+                    (
+                        quote! { let bpy_output = },
+                        outputs[0].as_parsed_intermediate_value(),
+                    )
+                };
+
+                // Class-level functions have no `self`; the host calls them on
+                // `bpy.types.<class>`. Those that receive the class they are
+                // called on take it as a type parameter, so subclasses can be
+                // passed in.
+                if !use_self {
+                    let (generics, class_name) = if *use_self_type {
+                        (quote! { <C: RnaClass> }, quote! { C::NAME })
+                    } else {
+                        (quote! {}, quote! { #class })
+                    };
+
+                    class_members.push(quote! {
+                        #[doc = #description]
+                        #alias
+                        pub fn #func_name_ident #generics(#(#params_list),*) #return_type {
+                            let bpy_input = PyArgs::class_argv(#class_name, vec![#into_pyargs], #kwargs_bpy).with_id(#id);
+                            #assign_to invoke_bpy_callmethod(#func_name, bpy_input);
+                            #from_serde_value
+                        }
+                    });
+                    continue;
+                }
+
+                trait_members.push(quote! {
+                    #[doc = #description]
+                    #alias
+                    fn #func_name_ident(&self, #(#params_list),*) #return_type;
+                });
+
+                impl_members.push(quote! {
+                    fn #func_name_ident(&self, #(#params_list),*) #return_type {
+                        let bpy_input = PyArgs::argv(Some(self), vec![#into_pyargs], #kwargs_bpy).with_id(#id);
+                        #assign_to invoke_bpy_callmethod(#func_name, bpy_input);
+                        #from_serde_value
+                    }
+                });
+            }
+            BpyMethod::Builtin => {}
+            BpyMethod::MethodDescriptor => {}
+            BpyMethod::PropertyDeferred => {}
+            BpyMethod::Function => {}
+            BpyMethod::Method => {}
+        }
+    }
+
+    (
+        extra_items.into_iter().collect(),
+        trait_members.into_iter().collect(),
+        impl_members.into_iter().collect(),
+        class_members.into_iter().collect(),
+    )
+}
+
+fn property_codegen(
+    class: &str,
+    properties: &BTreeMap<String, BpyProperty>,
+    names: &ClassNames,
+    ids: &Ids,
+    defined: &mut HashSet<std::string::String>,
+) -> (TokenStream, TokenStream, TokenStream, TokenStream) {
+    let mut impl_members: Vec<TokenStream> = Vec::with_capacity(16);
+    let mut trait_members: Vec<TokenStream> = Vec::with_capacity(16);
+    let mut extra_items: Vec<TokenStream> = Vec::with_capacity(16);
+    let mut defaults: Vec<TokenStream> = Vec::with_capacity(16);
+    let unset_id = ids.get("bpy_struct", "property_unset");
+    let is_set_id = ids.get("bpy_struct", "is_property_set");
+
+    for (func_name, property) in properties {
+        let func_name = func_name.as_str();
+        let id = ids.get(class, func_name);
+
+        let getter = names.getter(func_name);
+        let alias = names::alias(func_name, &getter);
+        let return_type = property.as_return_type(&mut extra_items, defined);
+
+        let description = property
+            .as_item()
+            .description
+            .as_ref()
+            .map(|xs| xs.as_str());
+        let description = if let Some(desc) = description {
+            quote! { #[doc = #desc] }
+        } else {
+            quote! {}
+        };
+
+        defaults.extend(default_const(class, func_name, &getter, property));
+        // Bound on the trait that introduces it, which this one extends.
+        if names.inherited_from(Member::Property, func_name).is_some() {
+            continue;
+        }
+
+        let parser = property.as_parsed_intermediate_value();
+        // Pointers, collections and read-only values may be served from the
+        // pdk's opt-in cache.
+        let getattr = if property.is_struct() || property.as_item().is_readonly {
+            quote! { invoke_bpy_getattr_cached }
+        } else {
+            quote! { invoke_bpy_getattr }
+        };
+
+        trait_members.push(quote! {
+            #description
+            #alias
+            fn #getter(&self) -> #return_type;
+        });
+
+        // impl for BpyPtr
+        impl_members.push(quote! {
+            fn #getter(&self) -> #return_type {
+                let args = PyArgs::new(self).with_id(#id);
+
+                let bpy_output = #getattr(#func_name, args);
+                #parser
+            }
+        });
+
+        if property.as_item().is_readonly {
+            continue;
+        }
+
+        let setter = names.setter(func_name);
+        let setter_param = property.as_setter_parameter_type(&mut extra_items);
+        trait_members.push(quote! {
+            fn #setter(&self, arg: #setter_param);
+        });
+        impl_members.push(quote! {
+            fn #setter(&self, arg: #setter_param) {
+                let args = PyArgs::arg1(self, arg).with_id(#id);
+
+                invoke_bpy_setattr(#func_name, args);
+            }
+        });
+
+        let reset = names.reset(func_name);
+        let is_set = names.is_set(func_name);
+        let reset_doc = format!("Return `{}` to its default (`property_unset`).", func_name);
+        let is_set_doc = format!(
+            "Whether `{}` has been set, rather than holding its default (`is_property_set`).",
+            func_name
+        );
+        trait_members.push(quote! {
+            #[doc = #reset_doc]
+            fn #reset(&self);
+            #[doc = #is_set_doc]
+            fn #is_set(&self) -> bool;
+        });
+        impl_members.push(quote! {
+            fn #reset(&self) {
+                let args = PyArgs::arg1(self, #func_name).with_id(#unset_id);
+
+                invoke_bpy_callmethod("property_unset", args);
+            }
+
+            fn #is_set(&self) -> bool {
+                let args = PyArgs::arg1(self, #func_name).with_id(#is_set_id);
+
+                let bpy_output = invoke_bpy_callmethod("is_property_set", args);
+                serde_json::from_value(bpy_output).expect("expected to deserialize appropriately")
+            }
+        });
+    }
+
+    (
+        extra_items.into_iter().collect(),
+        trait_members.into_iter().collect(),
+        impl_members.into_iter().collect(),
+        defaults.into_iter().collect(),
+    )
+}
+
+/// `class.key`'s schema default as a constant on `rna::<class>`, typed like
+/// its setter's argument. Pointers and collections have no default to give,
+/// nor do strings and enums without one.
+fn default_const(class: &str, key: &str, getter: &Ident, property: &BpyProperty) -> Option<TokenStream> {
+    let unsigned = |number: &BpyPropertyNumber<i64>| number.hard_min == number.soft_min && number.soft_min == 0;
+    // Floats are written the way the schema has them, not widened from f32.
+    let float = |x: f64| x.is_finite().then(|| Literal::f64_unsuffixed(x));
+    let f32_float = |x: &f32| float(x.to_string().parse().ok()?);
+
+    let (ty, value) = match property {
+        BpyProperty::Boolean { default, .. } => (quote! { bool }, quote! { #default }),
+        BpyProperty::BooleanArray { array, .. } => {
+            let values = &array.default;
+            (quote! { &[bool] }, quote! { &[#(#values),*] })
+        }
+        BpyProperty::Int { number, default, .. } if unsigned(number) => {
+            let default = u64::try_from(*default).ok()?;
+            (quote! { u64 }, quote! { #default })
+        }
+        BpyProperty::Int { default, .. } => (quote! { i64 }, quote! { #default }),
+        BpyProperty::IntArray { array, number, .. } if unsigned(number) => {
+            let values = array
+                .default
+                .iter()
+                .map(|xs| u64::try_from(*xs).ok())
+                .collect::<Option<Vec<_>>>()?;
+            (quote! { &[u64] }, quote! { &[#(#values),*] })
+        }
+        BpyProperty::IntArray { array, .. } => {
+            let values = &array.default;
+            (quote! { &[i64] }, quote! { &[#(#values),*] })
+        }
+        BpyProperty::Float { default, .. } => {
+            let default = float(*default)?;
+            (quote! { f64 }, quote! { #default })
+        }
+        BpyProperty::FloatArray { array, .. } => {
+            let values = array.default.iter().map(f32_float).collect::<Option<Vec<_>>>()?;
+            (quote! { &[f64] }, quote! { &[#(#values),*] })
+        }
+        BpyProperty::String { default, .. } | BpyProperty::Enum { default, .. } => {
+            let default = default.as_ref()?.as_str();
+            (quote! { &str }, quote! { #default })
+        }
+        BpyProperty::Pointer { .. } | BpyProperty::Collection { .. } => return None,
+    };
+
+    let name = format_ident!("{}_DEFAULT", getter.to_string().trim_start_matches("r#").to_uppercase());
+    let doc = format!("The default of `{}.{}`.", class, key);
+    Some(quote! {
+        #[doc = #doc]
+        pub const #name: #ty = #value;
+    })
+}
+
+/// Bindings for the Python-level members `catalog` lists for `structure`.
+/// Members the schema already describes are left to the RNA bindings.
+fn catalog_codegen(
+    structure: &BpyStructure,
+    catalog: &CatalogClass,
+    names: &ClassNames,
+    ids: &Ids,
+) -> (TokenStream, TokenStream) {
+    let class = structure.name.as_str();
+    let mut impl_members: Vec<TokenStream> = Vec::with_capacity(8);
+    let mut trait_members: Vec<TokenStream> = Vec::with_capacity(8);
+
+    let in_schema = |name: &str| {
+        structure.properties.contains_key(name) || matches!(structure.methods.get(name), Some(BpyMethod::Rna { .. }))
+    };
+
+    for (name, method) in catalog.methods.iter().filter(|(name, _)| !in_schema(name)) {
+        let id = ids.get(class, name);
+        let func_name = name.as_str();
+        let func_name_ident = names.method(name);
+        let alias = names::alias(name, &func_name_ident);
+
+        let (kwargs, params): (Vec<_>, Vec<_>) = method.params.iter().partition(|xs| xs.optional);
+
+        let param_names = names::params(params.iter().map(|param| param.name.as_str()), !kwargs.is_empty());
+        let mut params_list: Vec<TokenStream> = params
+            .iter()
+            .zip(&param_names)
+            .map(|(param, ident)| {
+                let ty = param.ty.as_param();
+                quote! { #ident: #ty }
+            })
+            .collect();
+        let into_pyargs: Vec<TokenStream> = param_names
+            .iter()
+            .map(|ident| quote! { serde_json::to_value(#ident).expect("pyarg must be serializable") })
+            .collect();
+
+        let kwargs_bpy = if kwargs.is_empty() {
+            quote! { None }
+        } else {
+            params_list.push(quote! { kwargs: Kwargs });
+            quote! { Some(kwargs) }
+        };
+
+        let description = params
+            .iter()
+            .chain(kwargs.iter())
+            .filter(|param| !param.description.is_empty())
+            .map(|param| format!("- {}: {}", param.name, param.description))
+            .fold(method.description.clone(), |lhs, rhs| format!("{}\n{}", lhs, rhs));
+
+        let out = params.iter().position(|xs| xs.out);
+        let (with_out, returns) = match out {
+            Some(idx) => {
+                let idx = idx as u32;
+                (quote! { .with_out(#idx) }, Some(&params[idx as usize].ty))
+            }
+            None => (quote! {}, method.returns.as_ref()),
+        };
+
+        let (return_type, parse) = match returns {
+            Some(ty) => {
+                let return_type = ty.as_return();
+                let wire = ty.as_wire();
+                let convert = ty.convert(quote! { result });
+                (
+                    quote! { -> #return_type },
+                    quote! {
+                        let result: #wire = serde_json::from_value(bpy_output).expect("expected to deserialize appropriately");
+                        #convert
+                    },
+                )
+            }
+            None => (quote! {}, quote! { let _ = bpy_output; }),
+        };
+
+        trait_members.push(quote! {
+            #[doc = #description]
+            #alias
+            fn #func_name_ident(&self, #(#params_list),*) #return_type;
+        });
+
+        impl_members.push(quote! {
+            fn #func_name_ident(&self, #(#params_list),*) #return_type {
+                let bpy_input = PyArgs::argv(Some(self), vec![#(#into_pyargs),*], #kwargs_bpy).with_id(#id)#with_out;
+                let bpy_output = invoke_bpy_callmethod(#func_name, bpy_input);
+                #parse
+            }
+        });
+    }
+
+    for (name, property) in catalog.properties.iter().filter(|(name, _)| !in_schema(name)) {
+        let id = ids.get(class, name);
+        let func_name = name.as_str();
+        let getter = names.getter(name);
+        let alias = names::alias(name, &getter);
+        let description = property.description.as_str();
+        let return_type = property.ty.as_return();
+        let wire = property.ty.as_wire();
+        let convert = property.ty.convert(quote! { result });
+
+        trait_members.push(quote! {
+            #[doc = #description]
+            #alias
+            fn #getter(&self) -> #return_type;
+        });
+
+        impl_members.push(quote! {
+            fn #getter(&self) -> #return_type {
+                let args = PyArgs::new(self).with_id(#id);
+
+                let bpy_output = invoke_bpy_getattr(#func_name, args);
+                let result: #wire = serde_json::from_value(bpy_output).expect("expected to deserialize appropriately");
+                #convert
+            }
+        });
+
+        if !property.readonly {
+            let setter = names.setter(name);
+            let setter_param = property.ty.as_param();
+
+            trait_members.push(quote! {
+                fn #setter(&self, arg: #setter_param);
+            });
+
+            impl_members.push(quote! {
+                fn #setter(&self, arg: #setter_param) {
+                    let args = PyArgs::arg1(self, arg).with_id(#id);
+
+                    invoke_bpy_setattr(#func_name, args);
+                }
+            });
+        }
+    }
+
+    (
+        trait_members.into_iter().collect(),
+        impl_members.into_iter().collect(),
+    )
+}
+
+/// The trait for `structure`, and the unit struct naming its class in
+/// `bpy::types::rna`. Classes with several bases get a supertrait for each
+/// one in `known`.
+fn structure_to_syntax(
+    structure: BpyStructure,
+    catalog: &Catalog,
+    names: &ClassNames,
+    ids: &Ids,
+    known: &HashSet<std::string::String>,
+    defined: &mut HashSet<std::string::String>,
+) -> (TokenStream, TokenStream) {
+    if structure.name == "type" {
+        return (quote! {}, quote! {});
+    }
+
+    let structure_name = structure.name.as_str().to_upper_camel_case();
+    let name = format_ident!("{}", structure_name);
+    let supers: Vec<Ident> = structure
+        .supers()
+        .filter(|xs| known.contains(*xs))
+        .map(|xs| format_ident!("{}", xs.to_upper_camel_case()))
+        .collect();
+
+    let (mut extra_items, mut trait_members, mut impl_members, defaults) =
+        property_codegen(&structure.name, &structure.properties, names, ids, defined);
+    let (e, t, i, class_members) =
+        method_codegen(&structure.name, &structure.methods, names, ids, defined, &name);
+
+    extra_items.extend(e);
+    trait_members.extend(t);
+    impl_members.extend(i);
+
+    if let Some(catalog) = catalog.class(&structure.name) {
+        let (t, i) = catalog_codegen(&structure, catalog, names, ids);
+        trait_members.extend(t);
+        impl_members.extend(i);
+    }
+
+    if structure_name.as_str() == "BpyStruct" {
+        trait_members.extend(quote! {
+            /// Unbox a dynamic pointer. Useful for re-casting to a different trait object.
+            fn to_bpy_ptr(&self) -> BpyPtr;
+        });
+
+        impl_members.extend(quote! {
+            fn to_bpy_ptr(&self) -> BpyPtr {
+                self.clone()
+            }
+        });
+    }
+
+    let parent = if !supers.is_empty() {
+        quote! { : #(#supers)+* }
+    } else {
+        quote! { : std::fmt::Debug + private::Sealed }
+    };
+
+    let class = structure.name.as_str();
+    let class_doc = format!("The `bpy.types.{}` class.", class);
+    let property_meta = structure.properties.iter().map(|(key, property)| {
+        let key = key.as_str();
+        let item = property.as_item();
+        let (is_readonly, is_animatable, is_overridable) =
+            (item.is_readonly, item.is_animatable, item.is_overridable);
+        quote! {
+            PropertyMeta { name: #key, is_readonly: #is_readonly, is_animatable: #is_animatable, is_overridable: #is_overridable }
+        }
+    });
+
+    // Mixins aren't in `bpy.types`, so there's nothing to name.
+    let rna_item = if structure.mixin {
+        quote! {}
+    } else {
+        quote! {
+            #[doc = #class_doc]
+            #[derive(Debug, Clone, Copy)]
+            pub struct #name;
+
+            impl RnaClass for #name {
+                const NAME: &'static str = #class;
+                const PROPERTIES: &'static [PropertyMeta] = &[#(#property_meta),*];
+            }
+        }
+    };
+
+    // Defaults go on the unit struct rather than the trait, which has to
+    // stay usable as `dyn`.
+    let defaults = if structure.mixin { quote! {} } else { defaults };
+    let class_impl = if class_members.is_empty() && defaults.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl rna::#name {
+                #defaults
+                #class_members
+            }
+        }
+    };
+
+    let items = quote! {
+        #extra_items
+
+        pub trait #name #parent {
+            #trait_members
+        }
+
+        impl #name for BpyPtr {
+            #impl_members
+        }
+
+        #class_impl
+    };
+
+    (items, rna_item)
+}
+
+fn ops_codegen(ops: BTreeMap<String, BTreeMap<String, BpyOperator>>) -> TokenStream {
+    let mut tkstream = TokenStream::new();
+    let _extra_items: Vec<TokenStream> = Vec::with_capacity(16);
+    let names = OperatorNames::new(&ops);
+    for (mod_name, items) in ops.into_iter() {
+        let mod_name_str = mod_name.as_str().to_snek_case();
+        let mod_name_ident = names.module(&mod_name);
+
+        let mut ops = TokenStream::new();
+        for (op_name, descriptor) in items.into_iter() {
+            let op_name_str = op_name.as_str().to_snek_case();
+            let op_name_ident = names.operator(&mod_name, &op_name);
+            let alias = names::alias(&op_name, &op_name_ident);
+            let inputs: Vec<_> = descriptor
+                .parameters
+                .iter()
+                .filter(|xs| !xs.is_output())
+                .collect();
+
+            let params_docs = inputs
+                .iter()
+                .map(|prop| {
+                    let item = prop.as_item();
+                    format!(
+                        "- {}: {}",
+                        item.identifier,
+                        item.description.clone().unwrap_or_default()
+                    )
+                })
+                .reduce(|lhs, rhs| format!("{}\n{}", lhs, rhs))
+                .unwrap_or_default();
+
+            let description = format!("{}\n{}", descriptor.description, params_docs);
+
+            ops.extend(quote! {
+                #[doc = #description]
+                #alias
+                pub fn #op_name_ident (params: impl Into<Kwargs>) -> serde_json::Value {
+                    let args = PyArgs::argv(None, Vec::new(), Some(params.into()));
+                    invoke_bpy_operator(#mod_name_str, #op_name_str, args)
+                }
+            })
+        }
+
+        tkstream.extend(quote! {
+            pub mod #mod_name_ident {
+                use super::*;
+                #ops
+            }
+        });
+    }
+    tkstream
+}
+
+/// The `BlendData` collections `bpy::data` binds: the keys, besides
+/// `"context"` and `"data"`, that it reads from the `bpy.data` config.
+fn data_collections(classes: &[BpyStructure]) -> impl Iterator<Item = &str> {
+    classes
+        .iter()
+        .filter(|xs| xs.name == "BlendData")
+        .flat_map(|xs| xs.properties.iter())
+        .filter(|(_, property)| matches!(property, BpyProperty::Collection { .. }))
+        .map(|(name, _)| name.as_str())
+}
+
+/// Generate `bpy::data` from the `BlendData` class.
+///
+/// Collections are read straight from the `bpy.data` config payload, which
+/// carries one pointer per `BlendData` collection (plus `"context"` and
+/// `"data"`, the `BlendData` itself). Every other member goes through
+/// [`blend_data()`]: scalar properties and RNA functions delegate to the
+/// generated `BlendData` trait, and Python-level functions such as
+/// `orphans_purge` are called dynamically.
+fn data_codegen(
+    blend_data: Option<&BpyStructure>,
+    names: &SchemaNames,
+    defined: &mut HashSet<std::string::String>,
+) -> TokenStream {
+    let Some(blend_data) = blend_data else {
+        return quote! {};
+    };
+
+    let mut tkstream = quote! {
+        /// `bpy.data` itself.
+        pub fn blend_data() -> Box<dyn super::types::BlendData + Send + Sync> {
+            Box::new(BpyPtr::from_raw(bpy_data_ptr("data")))
+        }
+    };
+
+    // Module functions share the `BlendData` trait's names, so they can
+    // delegate to it. Dynamic functions take what's left.
+    let names = names.class(&blend_data.name);
+    let functions = DataNames::new(blend_data, names);
+
+    // `as_return_type` only emits the collection traits the first time; the
+    // `BlendData` structure has already defined them.
+    let mut extra_items = Vec::new();
+    for (name, property) in &blend_data.properties {
+        let description = property
+            .as_item()
+            .description
+            .as_ref()
+            .map(|xs| {
+                let xs = xs.as_str();
+                quote! { #[doc = #xs] }
+            })
+            .unwrap_or_default();
+
+        if let BpyProperty::Collection {
+            fixed_type,
+            collection,
+            ..
+        } = property
+        {
+            let func_ident = names.getter(name);
+            let target = format_ident!(
+                "{}",
+                format!("BpyPropCollection_{}", collection.as_ref().unwrap_or(fixed_type))
+                    .to_upper_camel_case()
+            );
+            let name = name.as_str();
+            tkstream.extend(quote! {
+                #description
+                pub fn #func_ident() -> Box<dyn super::types::#target + Send + Sync> {
+                    Box::new(BpyPtr::from_raw(bpy_data_ptr(#name)))
+                }
+            });
+            continue;
+        }
+
+        let getter = names.getter(name);
+        let return_type = property.as_return_type(&mut extra_items, defined);
+        tkstream.extend(quote! {
+            #description
+            pub fn #getter() -> #return_type {
+                blend_data().#getter()
+            }
+        });
+
+        if !property.as_item().is_readonly {
+            let setter = names.setter(name);
+            let setter_param = property.as_setter_parameter_type(&mut extra_items);
+            let (reset, is_set) = (names.reset(name), names.is_set(name));
+            tkstream.extend(quote! {
+                pub fn #setter(arg: #setter_param) {
+                    blend_data().#setter(arg)
+                }
+
+                pub fn #reset() {
+                    blend_data().#reset()
+                }
+
+                pub fn #is_set() -> bool {
+                    blend_data().#is_set()
+                }
+            });
+        }
+    }
+
+    for (name, method) in &blend_data.methods {
+        match method {
+            // Class-level functions are bound on `types::rna::BlendData`.
+            BpyMethod::Rna { use_self: false, .. } => {}
+            BpyMethod::Rna {
+                description,
+                parameters,
+                ..
+            } => {
+                let (outputs, inputs): (Vec<_>, Vec<_>) =
+                    parameters.iter().partition(|xs| xs.is_output());
+                if outputs.len() > 1 {
+                    continue;
+                }
+                let func_ident = names.method(name);
+                let alias = names::alias(name, &func_ident);
+                let (params, kwargs): (Vec<_>, Vec<_>) =
+                    inputs.into_iter().partition(|xs| xs.as_item().is_required);
+
+                let mut args = names::params(
+                    params.iter().map(|prop| prop.as_item().identifier.as_str()),
+                    !kwargs.is_empty(),
+                );
+                let mut decls: Vec<_> = params
+                    .iter()
+                    .zip(&args)
+                    .map(|(prop, argname)| prop.as_method_parameter(argname, &mut extra_items))
+                    .collect();
+                if !kwargs.is_empty() {
+                    decls.push(quote! { kwargs: Kwargs });
+                    args.push(format_ident!("kwargs"));
+                }
+
+                let return_type = outputs
+                    .first()
+                    .map(|output| {
+                        let output = output.as_return_type(&mut extra_items, defined);
+                        quote! { -> #output }
+                    })
+                    .unwrap_or_default();
+
+                let description = description.as_str();
+                tkstream.extend(quote! {
+                    #[doc = #description]
+                    #alias
+                    pub fn #func_ident(#(#decls),*) #return_type {
+                        blend_data().#func_ident(#(#args),*)
+                    }
+                });
+            }
+            BpyMethod::Function | BpyMethod::Method | BpyMethod::Builtin | BpyMethod::MethodDescriptor => {
+                let doc = format!(
+                    "`bpy.data.{}(*args, **kwargs)`. This function has no RNA signature, so it is called dynamically.",
+                    name
+                );
+                let func_ident = functions.function(name);
+                let alias = names::alias(name, &func_ident);
+                let name = name.as_str();
+                tkstream.extend(quote! {
+                    #[doc = #doc]
+                    #alias
+                    pub fn #func_ident<R: serde::de::DeserializeOwned>(
+                        args: impl serde::Serialize,
+                        kwargs: impl Into<Kwargs>,
+                    ) -> Result<R, serde_json::Error> {
+                        BpyPtr::from_raw(bpy_data_ptr("data")).call(#name, args, kwargs)
+                    }
+                });
+            }
+            BpyMethod::PropertyDeferred => {}
+        }
+    }
+
+    tkstream
+}
+
+/// The bindings for one schema.
+fn bindings(schema: Schema, catalog: &Catalog, ids: &Ids) -> Result<syn::File, Error> {
+    let Schema { classes, operators, .. } = schema;
+
+    let blend_data = classes.iter().find(|xs| xs.name == "BlendData").cloned();
+    // `type` gets no trait, so classes directly under it are top-level.
+    let known: HashSet<_> = classes
+        .iter()
+        .filter(|xs| xs.name != "type")
+        .map(|xs| xs.name.to_string())
+        .collect();
+    let names = SchemaNames::new(&classes, catalog);
+    let mut defined = HashSet::new();
+    let (results, rna_items): (TokenStream, TokenStream) = classes
+        .into_iter()
+        .rev()
+        .map(|xs| {
+            let names = names.class(&xs.name);
+            structure_to_syntax(xs, catalog, names, ids, &known, &mut defined)
+        })
+        .unzip();
+
+    let bpy_data_impls = data_codegen(blend_data.as_ref(), &names, &mut defined);
+    let bpy_ops: TokenStream = ops_codegen(operators);
+
+    // Outer attributes only, so the file can be `include!`d from `OUT_DIR`.
+    let module = quote! {
+        #[allow(unknown_lints)]
+        #[allow(clippy::all)]
+        pub mod bpy {
+            use smartstring::alias::String;
+            // Not all of these are used by bindings for part of `bpy`.
+            #[allow(unused_imports)]
+            use crate::{ BpyPtr, PyArgs, Kwargs, RnaClass, PropertyMeta, bpy_data_ptr, invoke_bpy_setattr, invoke_bpy_getattr, invoke_bpy_getattr_cached, invoke_bpy_callmethod, invoke_bpy_operator };
+
+            mod private {
+                pub trait Sealed {}
+            }
+
+            impl private::Sealed for BpyPtr {}
+
+            pub mod types {
+                use super::*;
+                fn get(ptr: &BpyPtr, key: &str) -> Option<BpyPtr> {
+                    let args = PyArgs::arg1(ptr, key);
+                    let result = invoke_bpy_callmethod("get", args);
+                    let result: Option<BpyPtr> = serde_json::from_value(result).expect("TKTK");
+                    result
+                }
+
+                fn keys(ptr: &BpyPtr) -> Vec<String> {
+                    let args = PyArgs::new(ptr);
+                    let result = invoke_bpy_callmethod("keys", args);
+                    let result: Vec<String> = serde_json::from_value(result).expect("TKTK");
+                    result
+                }
+
+                fn values(ptr: &BpyPtr) -> Vec<BpyPtr> {
+                    let args = PyArgs::new(ptr);
+                    let result = invoke_bpy_callmethod("values", args);
+                    let result: Vec<BpyPtr> = serde_json::from_value(result).expect("TKTK");
+                    result
+                }
+
+                fn items(ptr: &BpyPtr) -> Vec<(String, BpyPtr)> {
+                    let args = PyArgs::new(ptr);
+                    let result = invoke_bpy_callmethod("items", args);
+                    let result: Vec<(String, BpyPtr)> = serde_json::from_value(result).expect("TKTK");
+                    result
+                }
+
+                #results
+
+                /// One unit struct per class, for calling class-level functions
+                /// such as `rna::BpyStruct::bl_rna_get_subclass_py`.
+                pub mod rna {
+                    use super::*;
+
+                    #rna_items
+                }
+            }
+
+            pub mod data {
+                use super::*;
+                // Parameter and return types are written as they are in `types`.
+                #[allow(unused_imports)]
+                use super::types::*;
+
+                #bpy_data_impls
+            }
+
+            pub mod ops {
+                #[allow(unused_imports)]
+                use super::*;
+
+                #bpy_ops
+            }
+
+            pub fn context() -> Box<dyn types::Context + Send + Sync> {
+                Box::new(BpyPtr::from_raw(bpy_data_ptr("context")))
+            }
+        }
+    };
+
+    Ok(syn::parse2(module)?)
+}
@@ -0,0 +1,411 @@
+use std::collections::{BTreeMap, HashMap};
+
+use blextism_schema::{BpyMethod, BpyProperty, BpyStructure, Schema};
+use heck::ToUpperCamelCase;
+
+use crate::catalog::Catalog;
+use crate::names::{DataNames, Member, OperatorNames, SchemaNames};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintKind {
+    /// A parent, pointer or collection type that names no class.
+    DanglingType,
+    /// A pointer or collection whose type wasn't in `bpy.types`, so genschema
+    /// fell back to `bpy_struct`.
+    FallbackType,
+    /// A collection property without the name of its collection class, so its
+    /// bindings lack the collection's own methods.
+    MissingCollection,
+    /// Two classes that generate the same trait name.
+    DuplicateIdentifier,
+    /// A member bound under another name because its own was taken, in its
+    /// class's trait or one of its supertraits.
+    NameCollision,
+    /// A class with several bases, none of them in `bpy.types`, which
+    /// genschema couldn't place in the class tree.
+    MultiBase,
+    /// A method bindgen generates nothing for.
+    SkippedMethod,
+    /// A catalog entry for a member the schema already describes, which
+    /// bindgen ignores in favor of the schema.
+    ShadowedCatalog,
+}
+
+impl LintKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintKind::DanglingType => "dangling-type",
+            LintKind::FallbackType => "fallback-type",
+            LintKind::MissingCollection => "missing-collection",
+            LintKind::DuplicateIdentifier => "duplicate-identifier",
+            LintKind::NameCollision => "name-collision",
+            LintKind::MultiBase => "multi-base",
+            LintKind::SkippedMethod => "skipped-method",
+            LintKind::ShadowedCatalog => "shadowed-catalog",
+        }
+    }
+}
+
+/// One problem with the schema, located by its JSON path.
+#[derive(Debug, Clone)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.kind.as_str(), self.path, self.message)
+    }
+}
+
+/// Python-level methods bindgen writes by hand rather than from the schema.
+const HAND_WRITTEN: &[(&str, &str)] = &[
+    ("bpy_prop_collection", "get"),
+    ("bpy_prop_collection", "keys"),
+    ("bpy_prop_collection", "values"),
+    ("bpy_prop_collection", "items"),
+];
+
+struct Linter<'a> {
+    classes: HashMap<&'a str, &'a BpyStructure>,
+    lints: Vec<Lint>,
+}
+
+impl<'a> Linter<'a> {
+    fn push(&mut self, kind: LintKind, path: String, message: String) {
+        self.lints.push(Lint { kind, path, message });
+    }
+
+    fn is_class(&self, name: &str) -> bool {
+        self.classes.contains_key(name)
+    }
+
+    fn property(&mut self, path: &str, owner: &str, property: &BpyProperty) {
+        let (fixed_type, collection, unresolved) = match property {
+            BpyProperty::Pointer {
+                fixed_type,
+                unresolved_type,
+                ..
+            } => (fixed_type, None, unresolved_type),
+            BpyProperty::Collection {
+                fixed_type,
+                collection,
+                unresolved_type,
+            ..
+            } => {
+                if collection.is_none() {
+                    self.push(
+                        LintKind::MissingCollection,
+                        path.to_string(),
+                        format!("{} is a collection of {} with no collection class", owner, fixed_type),
+                    );
+                }
+                (fixed_type, collection.as_ref(), unresolved_type)
+            }
+            _ => return,
+        };
+
+        if let Some(unresolved) = unresolved {
+            self.push(
+                LintKind::FallbackType,
+                format!("{}.fixed_type", path),
+                format!("{} points at {}, which is not in bpy.types; bound as bpy_struct", owner, unresolved),
+            );
+        }
+        for (key, ty) in [("fixed_type", Some(fixed_type)), ("collection", collection)] {
+            let Some(ty) = ty else { continue };
+            if !self.is_class(ty) {
+                self.push(
+                    LintKind::DanglingType,
+                    format!("{}.{}", path, key),
+                    format!("{} refers to unknown class {:?}", owner, ty),
+                );
+            }
+        }
+    }
+}
+
+/// Check `schema` for the problems [`LintKind`] describes.
+pub fn lint(schema: &Schema, catalog: &Catalog) -> Vec<Lint> {
+    let mut linter = Linter {
+        classes: schema.classes.iter().map(|xs| (xs.name.as_str(), xs)).collect(),
+        lints: Vec::new(),
+    };
+
+    let mut rust_names: BTreeMap<String, usize> = BTreeMap::new();
+    let names = SchemaNames::new(&schema.classes, catalog);
+
+    for (idx, class) in schema.classes.iter().enumerate() {
+        if class.name == "type" {
+            continue;
+        }
+        let path = format!("$.classes[{}]", idx);
+
+        if let Some(other) = rust_names.insert(class.name.as_str().to_upper_camel_case(), idx) {
+            linter.push(
+                LintKind::DuplicateIdentifier,
+                path.clone(),
+                format!(
+                    "{} and {} both generate the trait {}",
+                    schema.classes[other].name,
+                    class.name,
+                    class.name.as_str().to_upper_camel_case()
+                ),
+            );
+        }
+
+        if !matches!(class.parent.as_str(), "object" | "type") && !linter.is_class(&class.parent) {
+            linter.push(
+                LintKind::DanglingType,
+                format!("{}.parent", path),
+                format!("{} extends unknown class {:?}", class.name, class.parent),
+            );
+        }
+        for (base_idx, base) in class.bases.iter().enumerate() {
+            if *base != class.parent && !linter.is_class(base) {
+                linter.push(
+                    LintKind::DanglingType,
+                    format!("{}.bases[{}]", path, base_idx),
+                    format!("{} extends unknown class {:?}", class.name, base),
+                );
+            }
+        }
+
+        for (key, property) in &class.properties {
+            let owner = format!("{}.{}", class.name, key);
+            linter.property(&format!("{}.properties.{}", path, key), &owner, property);
+        }
+
+        let catalog = catalog.class(&class.name);
+        for (key, method) in &class.methods {
+            let method_path = format!("{}.methods.{}", path, key);
+            let owner = format!("{}.{}", class.name, key);
+            match method {
+                BpyMethod::Rna { parameters, .. } => {
+                    for (param_idx, param) in parameters.iter().enumerate() {
+                        let param_path = format!("{}.item.parameters[{}]", method_path, param_idx);
+                        linter.property(&param_path, &owner, param);
+                    }
+                    let outputs = parameters.iter().filter(|xs| xs.is_output()).count();
+                    if outputs > 1 {
+                        linter.push(
+                            LintKind::SkippedMethod,
+                            method_path,
+                            format!("{} returns {} values; only one is supported", owner, outputs),
+                        );
+                    }
+                }
+                _ if catalog.is_some_and(|xs| xs.methods.contains_key(key.as_str())) => {}
+                _ if HAND_WRITTEN.contains(&(class.name.as_str(), key.as_str())) => {}
+                // `bpy::data` wraps these as dynamic calls.
+                BpyMethod::Builtin | BpyMethod::MethodDescriptor | BpyMethod::Function | BpyMethod::Method
+                    if class.name == "BlendData" => {}
+                other => linter.push(
+                    LintKind::SkippedMethod,
+                    method_path,
+                    format!("{} is a Python-level {} with no catalog entry", owner, kind_name(other)),
+                ),
+            }
+        }
+
+        let member_path = |member: Member, key: &str| match member {
+            Member::Property if class.properties.contains_key(key) => format!("{}.properties.{}", path, key),
+            Member::Method if class.methods.contains_key(key) => format!("{}.methods.{}", path, key),
+            Member::Property => format!("catalog {}.properties.{}", class.name, key),
+            _ => format!("catalog {}.methods.{}", class.name, key),
+        };
+
+        let own = names.class(&class.name);
+        for renamed in &own.renamed {
+            linter.push(
+                LintKind::NameCollision,
+                member_path(renamed.member, &renamed.key),
+                format!(
+                    "{}.{} is bound as `{}`; `{}` is taken by {}",
+                    class.name, renamed.key, renamed.to, renamed.from, renamed.taken_by
+                ),
+            );
+        }
+        if class.name == "BlendData" {
+            for renamed in DataNames::new(class, own).renamed {
+                linter.push(
+                    LintKind::NameCollision,
+                    format!("{}.methods.{}", path, renamed.key),
+                    format!(
+                        "bpy.data.{} is bound as `{}`; `{}` is taken by {}",
+                        renamed.key, renamed.to, renamed.from, renamed.taken_by
+                    ),
+                );
+            }
+        }
+
+        let shadowed = catalog.into_iter().flat_map(|catalog| {
+            let properties = catalog.properties.keys().map(|key| ("properties", key));
+            properties.chain(catalog.methods.keys().map(|key| ("methods", key)))
+        });
+        for (member, key) in shadowed {
+            if class.properties.contains_key(key.as_str())
+                || matches!(class.methods.get(key.as_str()), Some(BpyMethod::Rna { .. }))
+            {
+                linter.push(
+                    LintKind::ShadowedCatalog,
+                    format!("catalog {}.{}.{}", class.name, member, key),
+                    format!("{}.{} is described by the schema; the catalog entry is ignored", class.name, key),
+                );
+            }
+        }
+    }
+
+    for (opmod, operators) in &schema.operators {
+        for (name, operator) in operators {
+            for (idx, param) in operator.parameters.iter().enumerate() {
+                let path = format!("$.operators.{}.{}.parameters[{}]", opmod, name, idx);
+                let owner = format!("bpy.ops.{}.{}", opmod, name);
+                linter.property(&path, &owner, param);
+            }
+        }
+    }
+
+    for renamed in OperatorNames::new(&schema.operators).renamed {
+        let path = match renamed.key.split_once('.') {
+            Some((opmod, name)) => format!("$.operators.{}.{}", opmod, name),
+            None => format!("$.operators.{}", renamed.key),
+        };
+        linter.push(
+            LintKind::NameCollision,
+            path,
+            format!(
+                "bpy.ops.{} is bound as `{}`; `{}` is taken by {}",
+                renamed.key, renamed.to, renamed.from, renamed.taken_by
+            ),
+        );
+    }
+
+    for (idx, class) in schema.unsupported.iter().enumerate() {
+        linter.push(
+            LintKind::MultiBase,
+            format!("$.unsupported[{}]", idx),
+            format!("{} has bases {}; it is not bound", class.name, class.bases.join(", ")),
+        );
+    }
+
+    linter.lints.sort_by_key(|xs| xs.kind);
+    linter.lints
+}
+
+fn kind_name(method: &BpyMethod) -> &'static str {
+    match method {
+        BpyMethod::Rna { .. } => "rna function",
+        BpyMethod::Builtin => "builtin_function_or_method",
+        BpyMethod::MethodDescriptor => "method_descriptor",
+        BpyMethod::PropertyDeferred => "_PropertyDeferred",
+        BpyMethod::Function => "function",
+        BpyMethod::Method => "method",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn boolean(identifier: &str, description: &str) -> serde_json::Value {
+        json!({ "bool": {
+            "identifier": identifier,
+            "description": description,
+            "type": "BOOLEAN",
+            "unit": "NONE",
+            "subtype": "NONE",
+            "is_required": false,
+            "is_runtime": false,
+            "is_output": false,
+            "is_never_none": false,
+            "default": false,
+        } })
+    }
+
+    #[test]
+    fn inherited_members_collide_only_when_changed() {
+        // Only a change to the bound signature makes it another member.
+        let mut readonly = boolean("is_evaluated", "Whether this is evaluated");
+        readonly["bool"]["is_readonly"] = json!(true);
+        let schema: Schema = serde_json::from_value(json!({
+            "classes": [
+                { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+                { "name": "ID", "parent": "bpy_struct", "methods": {}, "properties": {
+                    "tag": boolean("tag", "Tools can use this to tag data"),
+                    "is_evaluated": boolean("is_evaluated", "Whether this is evaluated"),
+                } },
+                { "name": "Object", "parent": "ID", "methods": {}, "properties": {
+                    "tag": boolean("tag", "Tools can use this to tag objects"),
+                    "is_evaluated": readonly,
+                } },
+            ],
+            "operators": {},
+        }))
+        .unwrap();
+
+        let collisions: Vec<_> = lint(&schema, &Catalog::default())
+            .into_iter()
+            .filter(|xs| xs.kind == LintKind::NameCollision)
+            .map(|xs| (xs.path, xs.message))
+            .collect();
+        // Its setter, reset and is-set are named after the renamed getter.
+        assert_eq!(
+            collisions,
+            [(
+                "$.classes[2].properties.is_evaluated".to_string(),
+                "Object.is_evaluated is bound as `is_evaluated_prop`; `is_evaluated` is taken by property \
+                 `is_evaluated` of ID"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn codegen_fallbacks_are_lints() {
+        let schema: Schema = serde_json::from_value(json!({
+            "classes": [
+                { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+                { "name": "BlendData", "parent": "bpy_struct", "properties": {
+                    "user_map": boolean("user_map", "Not really"),
+                }, "methods": {
+                    "user_map": { "type": "function" },
+                } },
+                { "name": "Object", "parent": "bpy_struct", "methods": {}, "properties": {
+                    "tag": boolean("tag", "Tools can use this to tag data"),
+                } },
+            ],
+            "operators": {},
+        }))
+        .unwrap();
+        let catalog: Catalog = serde_json::from_value(json!({
+            "Object": { "properties": { "tag": { "type": "bool" } } },
+        }))
+        .unwrap();
+
+        let lints: Vec<_> = lint(&schema, &catalog)
+            .into_iter()
+            .filter(|xs| matches!(xs.kind, LintKind::NameCollision | LintKind::ShadowedCatalog))
+            .map(|xs| (xs.kind, xs.path, xs.message))
+            .collect();
+        assert_eq!(
+            lints,
+            [
+                (
+                    LintKind::NameCollision,
+                    "$.classes[1].methods.user_map".to_string(),
+                    "bpy.data.user_map is bound as `user_map_fn`; `user_map` is taken by `user_map`".to_string()
+                ),
+                (
+                    LintKind::ShadowedCatalog,
+                    "catalog Object.properties.tag".to_string(),
+                    "Object.tag is described by the schema; the catalog entry is ignored".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,679 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use blextism_schema::{BpyMethod, BpyOperator, BpyProperty, BpyStructure};
+use heck::ToSnekCase;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use serde_json::{json, Value};
+
+use crate::catalog::Catalog;
+
+/// Strict and reserved keywords, across editions. Names that collide with
+/// these are bound as raw identifiers (`r#type`).
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static", "struct", "trait",
+    "true", "try", "type", "typeof", "union", "unsafe", "unsized", "use", "virtual", "where", "while",
+    "yield",
+];
+
+/// Keywords that can't be raw identifiers; these get a trailing `_` instead.
+const NOT_RAW: &[&str] = &["self", "Self", "super", "crate", "_"];
+
+/// Make `name` usable as a Rust identifier: characters other than ASCII
+/// letters, digits and `_` become `_`, a leading digit gets a `_` prefix, and
+/// `self`, `super` and `crate` get a `_` suffix. Other keywords are left
+/// as-is; [`ident`] makes them raw.
+pub fn mangle(name: &str) -> String {
+    let mut mangled: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if mangled.is_empty() || mangled.starts_with(|c: char| c.is_ascii_digit()) {
+        mangled.insert(0, '_');
+    }
+    if NOT_RAW.contains(&mangled.as_str()) {
+        mangled.push('_');
+    }
+    mangled
+}
+
+/// The identifier for `name`, [mangled](mangle), as a raw identifier if it's
+/// a keyword.
+pub fn ident(name: &str) -> Ident {
+    let name = mangle(name);
+    if KEYWORDS.contains(&name.as_str()) {
+        Ident::new_raw(&name, Span::call_site())
+    } else {
+        Ident::new(&name, Span::call_site())
+    }
+}
+
+/// `#[doc(alias = "original")]`, when a member is bound under a name other
+/// than the one the host knows it by.
+pub fn alias(original: &str, ident: &Ident) -> TokenStream {
+    let bound = ident.to_string();
+    if bound.trim_start_matches("r#") == original {
+        quote! {}
+    } else {
+        quote! { #[doc(alias = #original)] }
+    }
+}
+
+/// The names taken in one Rust namespace: a trait and its impl, a module or a
+/// parameter list.
+#[derive(Default)]
+pub struct Scope {
+    taken: HashMap<String, String>,
+}
+
+impl Scope {
+    /// Take `name` for hand-written code.
+    pub fn reserve(&mut self, name: &str) {
+        self.taken.insert(name.to_string(), format!("`{}`", name));
+    }
+
+    /// Take `wanted` (mangled) for `owner`. If it's already taken, take
+    /// `{wanted}_{suffix}` instead (or `{wanted}_{suffix}2`, and so on), and
+    /// also return whatever held `wanted`.
+    pub fn claim(&mut self, wanted: &str, suffix: &str, owner: String) -> (String, Option<String>) {
+        let wanted = mangle(wanted);
+        let Some(holder) = self.taken.get(&wanted).cloned() else {
+            self.taken.insert(wanted.clone(), owner);
+            return (wanted, None);
+        };
+
+        let mut candidate = format!("{}_{}", wanted, suffix);
+        let mut n = 2;
+        while self.taken.contains_key(&candidate) {
+            candidate = format!("{}_{}{}", wanted, suffix, n);
+            n += 1;
+        }
+        self.taken.insert(candidate.clone(), owner);
+        (candidate, Some(holder))
+    }
+}
+
+/// Identifiers for the parameters named `names`, unique within the parameter
+/// list (which also holds `kwargs`, if `kwargs` is set).
+pub fn params<'a>(names: impl IntoIterator<Item = &'a str>, kwargs: bool) -> Vec<Ident> {
+    let mut scope = Scope::default();
+    if kwargs {
+        scope.reserve("kwargs");
+    }
+    names
+        .into_iter()
+        .map(|name| ident(&scope.claim(&name.to_snek_case(), "arg", name.to_string()).0))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Member {
+    Property,
+    Method,
+    Operator,
+}
+
+/// A member bound under a different name because its own was taken.
+#[derive(Debug, Clone)]
+pub struct Renamed {
+    pub member: Member,
+    /// The member's name in the schema or catalog (`"module.name"` for
+    /// operators).
+    pub key: String,
+    pub from: String,
+    pub to: String,
+    /// What holds `from`.
+    pub taken_by: String,
+}
+
+/// Whether `class` has the `ancestor`'s `key`, with the same signature as on
+/// `ancestor`. genschema lists every inherited member again on each class;
+/// those are bound once, on the class that introduces them. A member whose
+/// description or UI name is all that differs is still the same member.
+pub fn inherits_unchanged(class: &BpyStructure, ancestor: &BpyStructure, member: Member, key: &str) -> bool {
+    fn same<T>(lhs: Option<&T>, rhs: Option<&T>, signature: fn(&T) -> Value) -> bool {
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => signature(lhs) == signature(rhs),
+            _ => false,
+        }
+    }
+    match member {
+        Member::Property => same(class.properties.get(key), ancestor.properties.get(key), property_signature),
+        Member::Method => same(class.methods.get(key), ancestor.methods.get(key), method_signature),
+        Member::Operator => false,
+    }
+}
+
+/// What of `property` shows in the generated bindings' signatures: its type,
+/// what it points to, its enum items, whether it's optional, settable or an
+/// output, and (for ints) the range that makes it unsigned.
+fn property_signature(property: &BpyProperty) -> Value {
+    let item = property.as_item();
+    let (fixed_type, items, unsigned) = match property {
+        BpyProperty::Int { number, .. } | BpyProperty::IntArray { number, .. } => {
+            (None, None, number.hard_min == number.soft_min && number.soft_min == 0)
+        }
+        BpyProperty::Enum { items, is_flag, .. } => {
+            (None, Some((items.iter().map(|xs| xs.id.as_str()).collect::<Vec<_>>(), *is_flag)), false)
+        }
+        BpyProperty::Pointer { fixed_type, .. } | BpyProperty::Collection { fixed_type, .. } => {
+            (Some(fixed_type), None, false)
+        }
+        _ => (None, None, false),
+    };
+    json!({
+        "kind": serde_json::to_value(property).ok().and_then(|xs| xs.as_object()?.keys().next().cloned()),
+        "type": item.prop_type,
+        "identifier": item.identifier,
+        "fixed_type": fixed_type,
+        "items": items,
+        "unsigned": unsigned,
+        "is_readonly": item.is_readonly,
+        "is_output": item.is_output,
+        "is_never_none": item.is_never_none,
+        "is_required": item.is_required,
+        "is_argument_optional": item.is_argument_optional,
+    })
+}
+
+/// What of `method` shows in the generated bindings: how it's called and the
+/// signatures of its parameters.
+fn method_signature(method: &BpyMethod) -> Value {
+    match method {
+        BpyMethod::Rna { use_self, use_self_type, parameters, identifier, .. } => json!({
+            "use_self": use_self,
+            "use_self_type": use_self_type,
+            "identifier": identifier,
+            "parameters": parameters.iter().map(property_signature).collect::<Vec<_>>(),
+        }),
+        other => serde_json::to_value(other).unwrap_or_default(),
+    }
+}
+
+/// The [`ClassNames`] of every class in a schema. Each class's names are
+/// chosen after those of every class it extends, since its trait shares a
+/// namespace with its supertraits'.
+#[derive(Default)]
+pub struct SchemaNames {
+    classes: HashMap<String, ClassNames>,
+}
+
+impl SchemaNames {
+    pub fn new(classes: &[BpyStructure], catalog: &Catalog) -> Self {
+        let structures: HashMap<&str, &BpyStructure> = classes.iter().map(|xs| (xs.name.as_str(), xs)).collect();
+        let mut names = SchemaNames::default();
+        for class in classes {
+            names.resolve(class, &structures, catalog, &mut Vec::new());
+        }
+        names
+    }
+
+    fn resolve<'a>(
+        &mut self,
+        class: &'a BpyStructure,
+        structures: &HashMap<&str, &'a BpyStructure>,
+        catalog: &Catalog,
+        visiting: &mut Vec<&'a str>,
+    ) {
+        // A class that (through a broken schema) extends itself gets the
+        // names resolved so far.
+        if self.classes.contains_key(class.name.as_str()) || visiting.contains(&class.name.as_str()) {
+            return;
+        }
+        visiting.push(class.name.as_str());
+        for base in class.supers().filter_map(|xs| structures.get(xs)) {
+            self.resolve(base, structures, catalog, visiting);
+        }
+        visiting.pop();
+
+        let ancestors: Vec<_> = ancestors(class, structures)
+            .into_iter()
+            .filter_map(|xs| Some((xs, self.classes.get(xs.name.as_str())?)))
+            .collect();
+        let names = ClassNames::new(class, &ancestors, catalog);
+        self.classes.insert(class.name.to_string(), names);
+    }
+
+    pub fn class(&self, name: &str) -> &ClassNames {
+        &self.classes[name]
+    }
+}
+
+/// Every class `class` extends, nearest first: each parent's line before any
+/// mixin bases.
+fn ancestors<'a>(class: &BpyStructure, structures: &HashMap<&str, &'a BpyStructure>) -> Vec<&'a BpyStructure> {
+    let mut ancestors: Vec<&'a BpyStructure> = Vec::new();
+    let mut stack: Vec<&str> = class.supers().collect();
+    stack.reverse();
+    while let Some(name) = stack.pop() {
+        let Some(&structure) = structures.get(name) else {
+            continue;
+        };
+        if structure.name == class.name || ancestors.iter().any(|xs| xs.name == structure.name) {
+            continue;
+        }
+        ancestors.push(structure);
+        let supers: Vec<_> = structure.supers().collect();
+        stack.extend(supers.into_iter().rev());
+    }
+    ancestors
+}
+
+/// The Rust names of one class's members: its properties' getters, setters,
+/// `reset_*` and `is_*_set` methods and its methods, from the schema and the
+/// catalog. Names are unique within the class's trait and its supertraits,
+/// and among its class-level functions; a member whose name is taken is bound
+/// with a `_prop` or `_fn` suffix and listed in `renamed`. Members the class
+/// inherits unchanged keep the name they have on the class that introduces
+/// them.
+///
+/// Members claim names in a fixed order (schema properties, RNA methods,
+/// catalog methods, catalog properties), so the same schema always binds the
+/// same names.
+#[derive(Default)]
+pub struct ClassNames {
+    getters: BTreeMap<String, String>,
+    setters: BTreeMap<String, String>,
+    resets: BTreeMap<String, String>,
+    is_sets: BTreeMap<String, String>,
+    methods: BTreeMap<String, String>,
+    class_functions: BTreeSet<String>,
+    /// Members inherited unchanged, and the class that introduces each.
+    inherited: BTreeMap<(Member, String), String>,
+    pub renamed: Vec<Renamed>,
+}
+
+impl ClassNames {
+    /// The names for `structure`, which extends `ancestors` (nearest first,
+    /// with their names).
+    pub fn new(structure: &BpyStructure, ancestors: &[(&BpyStructure, &ClassNames)], catalog: &Catalog) -> Self {
+        let mut names = ClassNames::default();
+        let mut members = Scope::default();
+        let mut class_functions = Scope::default();
+        members.reserve("to_bpy_ptr");
+        for (ancestor, inherited) in ancestors {
+            for (name, member, key) in inherited.iter() {
+                if member == Member::Method && inherited.class_functions.contains(key) {
+                    continue;
+                }
+                let owner = match member {
+                    Member::Property => format!("property `{}` of {}", key, ancestor.name),
+                    _ => format!("method `{}` of {}", key, ancestor.name),
+                };
+                members.taken.entry(name.to_string()).or_insert(owner);
+            }
+        }
+
+        for (key, property) in &structure.properties {
+            if names.inherit(structure, ancestors, Member::Property, key) {
+                continue;
+            }
+            let item = property.as_item();
+            names.claim_property(&mut members, key, item.identifier.as_str(), !item.is_readonly);
+            // Python-level properties have no RNA state to unset, so only
+            // the schema's get these.
+            if !item.is_readonly {
+                names.claim_unset(&mut members, key);
+            }
+        }
+
+        for (key, method) in &structure.methods {
+            let BpyMethod::Rna {
+                use_self,
+                parameters,
+                identifier,
+                ..
+            } = method
+            else {
+                continue;
+            };
+            if parameters.iter().filter(|xs| xs.is_output()).count() > 1 {
+                continue;
+            }
+            let wanted = identifier.as_ref().unwrap_or(key).as_str().to_snek_case();
+            if !*use_self {
+                names.class_functions.insert(key.to_string());
+                names.claim_method(&mut class_functions, key, &wanted);
+            } else if !names.inherit(structure, ancestors, Member::Method, key) {
+                names.claim_method(&mut members, key, &wanted);
+            }
+        }
+
+        let Some(catalog) = catalog.class(&structure.name) else {
+            return names;
+        };
+        let in_schema = |name: &str| {
+            structure.properties.contains_key(name)
+                || matches!(structure.methods.get(name), Some(BpyMethod::Rna { .. }))
+        };
+        for key in catalog.methods.keys().filter(|xs| !in_schema(xs)) {
+            names.claim_method(&mut members, key, &key.to_snek_case());
+        }
+        for (key, property) in catalog.properties.iter().filter(|(xs, _)| !in_schema(xs)) {
+            names.claim_property(&mut members, key, &key.to_snek_case(), !property.readonly);
+        }
+        names
+    }
+
+    /// Take the names of `key` from the nearest ancestor `structure` inherits
+    /// it from unchanged, if there is one.
+    fn inherit(
+        &mut self,
+        structure: &BpyStructure,
+        ancestors: &[(&BpyStructure, &ClassNames)],
+        member: Member,
+        key: &str,
+    ) -> bool {
+        let Some((ancestor, names)) = ancestors
+            .iter()
+            .find(|(ancestor, _)| inherits_unchanged(structure, ancestor, member, key))
+        else {
+            return false;
+        };
+        let introduced_by = names.inherited_from(member, key).unwrap_or(ancestor.name.as_str()).to_string();
+        self.inherited.insert((member, key.to_string()), introduced_by);
+
+        let copy = |from: &BTreeMap<String, String>, to: &mut BTreeMap<String, String>| {
+            if let Some(name) = from.get(key) {
+                to.insert(key.to_string(), name.clone());
+            }
+        };
+        match member {
+            Member::Property => {
+                copy(&names.getters, &mut self.getters);
+                copy(&names.setters, &mut self.setters);
+                copy(&names.resets, &mut self.resets);
+                copy(&names.is_sets, &mut self.is_sets);
+            }
+            _ => copy(&names.methods, &mut self.methods),
+        }
+        true
+    }
+
+    /// The class that introduces `key`, if this class inherits it unchanged
+    /// and so binds it through that class's trait rather than its own.
+    pub fn inherited_from(&self, member: Member, key: &str) -> Option<&str> {
+        self.inherited.get(&(member, key.to_string())).map(|xs| xs.as_str())
+    }
+
+    fn claim_property(&mut self, scope: &mut Scope, key: &str, wanted: &str, settable: bool) {
+        let owner = format!("property `{}`", key);
+        let (getter, taken_by) = scope.claim(wanted, "prop", owner.clone());
+        self.note(Member::Property, key, wanted, &getter, taken_by);
+        if settable {
+            let wanted = format!("set_{}", getter);
+            let (setter, taken_by) = scope.claim(&wanted, "prop", owner);
+            self.note(Member::Property, key, &wanted, &setter, taken_by);
+            self.setters.insert(key.to_string(), setter);
+        }
+        self.getters.insert(key.to_string(), getter);
+    }
+
+    fn claim_unset(&mut self, scope: &mut Scope, key: &str) {
+        let owner = format!("property `{}`", key);
+        let getter = self.getters[key].clone();
+
+        let wanted = format!("reset_{}", getter);
+        let (reset, taken_by) = scope.claim(&wanted, "prop", owner.clone());
+        self.note(Member::Property, key, &wanted, &reset, taken_by);
+        self.resets.insert(key.to_string(), reset);
+
+        let wanted = format!("is_{}_set", getter);
+        let (is_set, taken_by) = scope.claim(&wanted, "prop", owner);
+        self.note(Member::Property, key, &wanted, &is_set, taken_by);
+        self.is_sets.insert(key.to_string(), is_set);
+    }
+
+    fn claim_method(&mut self, scope: &mut Scope, key: &str, wanted: &str) {
+        let (name, taken_by) = scope.claim(wanted, "fn", format!("method `{}`", key));
+        self.note(Member::Method, key, wanted, &name, taken_by);
+        self.methods.insert(key.to_string(), name);
+    }
+
+    fn note(&mut self, member: Member, key: &str, from: &str, to: &str, taken_by: Option<String>) {
+        if let Some(taken_by) = taken_by {
+            self.renamed.push(Renamed {
+                member,
+                key: key.to_string(),
+                from: mangle(from),
+                to: to.to_string(),
+                taken_by,
+            });
+        }
+    }
+
+    pub fn getter(&self, key: &str) -> Ident {
+        ident(&self.getters[key])
+    }
+
+    pub fn setter(&self, key: &str) -> Ident {
+        ident(&self.setters[key])
+    }
+
+    pub fn reset(&self, key: &str) -> Ident {
+        ident(&self.resets[key])
+    }
+
+    pub fn is_set(&self, key: &str) -> Ident {
+        ident(&self.is_sets[key])
+    }
+
+    pub fn method(&self, key: &str) -> Ident {
+        ident(&self.methods[key])
+    }
+
+    /// Every name bound for the class's members, with the member that holds
+    /// it: on its trait (or, for members it inherits unchanged, the trait
+    /// that introduces them) or as a class-level function.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Member, &str)> {
+        let properties = self
+            .getters
+            .iter()
+            .chain(self.setters.iter())
+            .chain(self.resets.iter())
+            .chain(self.is_sets.iter())
+            .map(|(key, name)| (name.as_str(), Member::Property, key.as_str()));
+        let methods = self
+            .methods
+            .iter()
+            .map(|(key, name)| (name.as_str(), Member::Method, key.as_str()));
+        properties.chain(methods)
+    }
+}
+
+/// The Rust names of `bpy.data`'s dynamic functions in `bpy::data`, which
+/// share the module with the `BlendData` members it delegates to.
+#[derive(Default)]
+pub struct DataNames {
+    functions: BTreeMap<String, String>,
+    pub renamed: Vec<Renamed>,
+}
+
+impl DataNames {
+    pub fn new(blend_data: &BpyStructure, names: &ClassNames) -> Self {
+        let mut data = DataNames::default();
+        let mut scope = Scope::default();
+        scope.reserve("blend_data");
+        names.iter().for_each(|(name, _, _)| scope.reserve(name));
+        for (name, method) in &blend_data.methods {
+            if matches!(method, BpyMethod::Rna { .. } | BpyMethod::PropertyDeferred) {
+                continue;
+            }
+            let wanted = name.as_str().to_snek_case();
+            let (bound, taken_by) = scope.claim(&wanted, "fn", format!("function `{}`", name));
+            if let Some(taken_by) = taken_by {
+                data.renamed.push(Renamed {
+                    member: Member::Method,
+                    key: name.to_string(),
+                    from: mangle(&wanted),
+                    to: bound.clone(),
+                    taken_by,
+                });
+            }
+            data.functions.insert(name.to_string(), bound);
+        }
+        data
+    }
+
+    pub fn function(&self, name: &str) -> Ident {
+        ident(&self.functions[name])
+    }
+}
+
+/// The Rust names of `bpy.ops` modules and operators, unique within
+/// `bpy::ops` and within each module respectively.
+#[derive(Default)]
+pub struct OperatorNames {
+    modules: BTreeMap<String, String>,
+    operators: BTreeMap<(String, String), String>,
+    pub renamed: Vec<Renamed>,
+}
+
+impl OperatorNames {
+    pub fn new<K: AsRef<str>>(operators: &BTreeMap<K, BTreeMap<K, BpyOperator>>) -> Self {
+        let mut names = OperatorNames::default();
+        let mut modules = Scope::default();
+        for (opmod, items) in operators {
+            let opmod = opmod.as_ref();
+            let (module, taken_by) = modules.claim(&opmod.to_snek_case(), "ops", format!("module `{}`", opmod));
+            if let Some(taken_by) = taken_by {
+                names.renamed.push(Renamed {
+                    member: Member::Operator,
+                    key: opmod.to_string(),
+                    from: mangle(&opmod.to_snek_case()),
+                    to: module.clone(),
+                    taken_by,
+                });
+            }
+            names.modules.insert(opmod.to_string(), module);
+
+            let mut scope = Scope::default();
+            for (name, operator) in items {
+                let name = name.as_ref();
+                let wanted = operator.identifier.as_deref().unwrap_or(name).to_snek_case();
+                let (bound, taken_by) = scope.claim(&wanted, "op", format!("operator `{}`", name));
+                if let Some(taken_by) = taken_by {
+                    names.renamed.push(Renamed {
+                        member: Member::Operator,
+                        key: format!("{}.{}", opmod, name),
+                        from: mangle(&wanted),
+                        to: bound.clone(),
+                        taken_by,
+                    });
+                }
+                names.operators.insert((opmod.to_string(), name.to_string()), bound);
+            }
+        }
+        names
+    }
+
+    pub fn module(&self, opmod: &str) -> Ident {
+        ident(&self.modules[opmod])
+    }
+
+    pub fn operator(&self, opmod: &str, name: &str) -> Ident {
+        ident(&self.operators[&(opmod.to_string(), name.to_string())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn mangles_names_into_identifiers() {
+        assert_eq!(mangle("self"), "self_");
+        assert_eq!(mangle("crate"), "crate_");
+        assert_eq!(mangle("3d_cursor"), "_3d_cursor");
+        assert_eq!(mangle("use-nodes.x"), "use_nodes_x");
+        assert_eq!(mangle(""), "__");
+        assert_eq!(mangle("type"), "type");
+
+        assert_eq!(ident("type").to_string(), "r#type");
+        assert_eq!(ident("self").to_string(), "self_");
+        assert_eq!(ident("2d").to_string(), "_2d");
+        assert_eq!(ident("location").to_string(), "location");
+    }
+
+    #[test]
+    fn scope_suffixes_taken_names() {
+        let mut scope = Scope::default();
+        scope.reserve("to_bpy_ptr");
+
+        assert_eq!(scope.claim("name", "prop", "property `name`".into()), ("name".into(), None));
+        assert_eq!(
+            scope.claim("name", "fn", "method `name`".into()),
+            ("name_fn".into(), Some("property `name`".into()))
+        );
+        assert_eq!(
+            scope.claim("name", "fn", "method `Name`".into()),
+            ("name_fn2".into(), Some("property `name`".into()))
+        );
+        assert_eq!(scope.claim("to_bpy_ptr", "prop", "property `to_bpy_ptr`".into()).0, "to_bpy_ptr_prop");
+        // Names are mangled before they're compared.
+        assert_eq!(scope.claim("self", "prop", "property `self`".into()), ("self_".into(), None));
+        assert_eq!(scope.claim("self_", "prop", "property `self_`".into()).0, "self__prop");
+    }
+
+    fn boolean(identifier: &str, description: &str) -> serde_json::Value {
+        json!({ "bool": {
+            "identifier": identifier,
+            "description": description,
+            "type": "BOOLEAN",
+            "unit": "NONE",
+            "subtype": "NONE",
+            "is_required": false,
+            "is_runtime": false,
+            "is_output": false,
+            "is_never_none": false,
+            "is_readonly": true,
+            "default": false,
+        } })
+    }
+
+    #[test]
+    fn renames_members_that_clash_with_a_supertrait() {
+        // Settable on Object, so not the property ID binds.
+        let mut settable = boolean("is_evaluated", "Whether this object is evaluated");
+        settable["bool"]["is_readonly"] = json!(false);
+        let classes: Vec<BpyStructure> = serde_json::from_value(json!([
+            { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+            { "name": "ID", "parent": "bpy_struct", "methods": {}, "properties": {
+                "tag": boolean("tag", "Tools can use this to tag data"),
+                "is_evaluated": boolean("is_evaluated", "Whether this is evaluated"),
+            } },
+            { "name": "Object", "parent": "ID", "methods": {}, "properties": {
+                "tag": boolean("tag", "Tools can use this to tag objects"),
+                "is_evaluated": settable,
+                "to_bpy_ptr": boolean("to_bpy_ptr", ""),
+            } },
+            { "name": "Mesh", "parent": "ID", "methods": {}, "properties": {
+                "is_evaluated": boolean("is_evaluated", "Whether this is evaluated"),
+            } },
+        ]))
+        .unwrap();
+        let names = SchemaNames::new(&classes, &Catalog::default());
+
+        let object = names.class("Object");
+        assert_eq!(object.getter("tag").to_string(), "tag");
+        assert_eq!(object.inherited_from(Member::Property, "tag"), Some("ID"));
+        assert_eq!(object.getter("is_evaluated").to_string(), "is_evaluated_prop");
+        assert_eq!(object.inherited_from(Member::Property, "is_evaluated"), None);
+        assert_eq!(object.getter("to_bpy_ptr").to_string(), "to_bpy_ptr_prop");
+
+        let renamed: Vec<_> = object.renamed.iter().map(|xs| (xs.to.as_str(), xs.taken_by.as_str())).collect();
+        assert_eq!(
+            renamed,
+            [("is_evaluated_prop", "property `is_evaluated` of ID"), ("to_bpy_ptr_prop", "`to_bpy_ptr`")]
+        );
+
+        let mesh = names.class("Mesh");
+        assert_eq!(mesh.getter("is_evaluated").to_string(), "is_evaluated");
+        assert_eq!(mesh.inherited_from(Member::Property, "is_evaluated"), Some("ID"));
+        assert!(mesh.renamed.is_empty());
+    }
+}
@@ -0,0 +1,540 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Edits to the schema, applied by bindgen before codegen so that binding
+/// problems can be fixed in the repo instead of in `genschema.py`.
+///
+/// Overlays are JSON or TOML (by file extension). Each file lists `remove`,
+/// `add`, `patch` and `rename` edits, applied in that order, file by file in
+/// the order given on the command line:
+///
+/// ```toml
+/// [[rename]]
+/// class = "RenderEngine"
+/// property = "render"
+/// to = "render_settings"
+///
+/// [[patch]]
+/// class = "Object"
+/// property = "location"
+/// with = { is_never_none = true }
+///
+/// [[remove]]
+/// operator = "object.delete"
+/// ```
+///
+/// An edit targets a `class`, one of its `property` or `method` members, an
+/// `enum_item` of an enum property, or an `operator` (`"module.name"`).
+///
+/// - `add` inserts `value`, replacing the target if it exists. Properties,
+///   methods and operators take the same JSON genschema writes for them.
+/// - `patch` merges `with` into the target (RFC 7386: `null` removes a key).
+///   For properties and RNA methods, that's the inner object holding
+///   `identifier`, `description` and so on.
+/// - `rename` changes the generated name of properties, methods and operators;
+///   the host is still asked for the original member. Classes and enum items
+///   are renamed outright, along with every reference to the class. The host
+///   sees the new name too, as `RnaClass::NAME`, in `@class` and in the id
+///   table, so only rename a class to the name `bpy.types` knows it by.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Overlay {
+    #[serde(default)]
+    pub remove: Vec<Target>,
+    #[serde(default)]
+    pub add: Vec<Add>,
+    #[serde(default)]
+    pub patch: Vec<Patch>,
+    #[serde(default)]
+    pub rename: Vec<Rename>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Target {
+    pub class: Option<String>,
+    pub property: Option<String>,
+    pub method: Option<String>,
+    pub enum_item: Option<String>,
+    pub operator: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Add {
+    #[serde(flatten)]
+    pub target: Target,
+    pub value: Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Patch {
+    #[serde(flatten)]
+    pub target: Target,
+    pub with: Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Rename {
+    #[serde(flatten)]
+    pub target: Target,
+    pub to: String,
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(operator) = &self.operator {
+            return write!(f, "bpy.ops.{}", operator);
+        }
+        write!(f, "{}", self.class.as_deref().unwrap_or("<no class>"))?;
+        if let Some(member) = self.property.as_ref().or(self.method.as_ref()) {
+            write!(f, ".{}", member)?;
+        }
+        if let Some(item) = &self.enum_item {
+            write!(f, "[{:?}]", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a [`Target`] points at.
+enum Kind<'a> {
+    Class(&'a str),
+    Property(&'a str, &'a str),
+    Method(&'a str, &'a str),
+    EnumItem(&'a str, &'a str, &'a str),
+    Operator(&'a str, &'a str),
+}
+
+impl Target {
+    fn kind(&self) -> Result<Kind<'_>, String> {
+        let kind = match (
+            self.class.as_deref(),
+            self.property.as_deref(),
+            self.method.as_deref(),
+            self.enum_item.as_deref(),
+            self.operator.as_deref(),
+        ) {
+            (None, None, None, None, Some(operator)) => {
+                let (opmod, name) = operator
+                    .split_once('.')
+                    .ok_or_else(|| format!("operator {:?} should be \"module.name\"", operator))?;
+                Kind::Operator(opmod, name)
+            }
+            (Some(class), None, None, None, None) => Kind::Class(class),
+            (Some(class), Some(property), None, None, None) => Kind::Property(class, property),
+            (Some(class), None, Some(method), None, None) => Kind::Method(class, method),
+            (Some(class), Some(property), None, Some(item), None) => {
+                Kind::EnumItem(class, property, item)
+            }
+            _ => return Err(format!("{}: unsupported combination of target keys", self)),
+        };
+        Ok(kind)
+    }
+}
+
+impl Overlay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let overlay = if path.extension().is_some_and(|xs| xs == "toml") {
+            toml::from_str(&text).map_err(|err| err.to_string())
+        } else {
+            serde_json::from_str(&text).map_err(|err| err.to_string())
+        };
+        overlay.map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Apply this overlay to a schema in its JSON form.
+    pub fn apply(&self, schema: &mut Value) -> Result<(), String> {
+        for target in &self.remove {
+            remove(schema, target).map_err(|err| format!("remove {}: {}", target, err))?;
+        }
+        for add in &self.add {
+            insert(schema, &add.target, add.value.clone())
+                .map_err(|err| format!("add {}: {}", add.target, err))?;
+        }
+        for patch in &self.patch {
+            let target = resolve(schema, &patch.target)
+                .map_err(|err| format!("patch {}: {}", patch.target, err))?;
+            merge(target, &patch.with);
+        }
+        for rename in &self.rename {
+            apply_rename(schema, &rename.target, &rename.to)
+                .map_err(|err| format!("rename {}: {}", rename.target, err))?;
+        }
+        Ok(())
+    }
+}
+
+fn classes(schema: &mut Value) -> Result<&mut Vec<Value>, String> {
+    schema
+        .get_mut("classes")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| "schema has no classes".to_string())
+}
+
+fn operators(schema: &mut Value) -> Result<&mut Map<String, Value>, String> {
+    schema
+        .get_mut("operators")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| "schema has no operators".to_string())
+}
+
+fn class<'a>(schema: &'a mut Value, name: &str) -> Result<&'a mut Value, String> {
+    classes(schema)?
+        .iter_mut()
+        .find(|xs| xs["name"] == name)
+        .ok_or_else(|| "no such class".to_string())
+}
+
+fn members<'a>(schema: &'a mut Value, class_name: &str, key: &str) -> Result<&'a mut Map<String, Value>, String> {
+    class(schema, class_name)?
+        .get_mut(key)
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| format!("class has no {}", key))
+}
+
+/// The object holding a property's fields: `{"enum": {..}}` -> `{..}`.
+fn property_item(property: &mut Value) -> Result<&mut Value, String> {
+    property
+        .as_object_mut()
+        .and_then(|xs| xs.values_mut().next())
+        .ok_or_else(|| "malformed property".to_string())
+}
+
+fn enum_items<'a>(schema: &'a mut Value, class_name: &str, property: &str) -> Result<&'a mut Vec<Value>, String> {
+    let property = members(schema, class_name, "properties")?
+        .get_mut(property)
+        .ok_or_else(|| "no such property".to_string())?;
+    property
+        .get_mut("enum")
+        .and_then(|xs| xs.get_mut("items"))
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| "not an enum property".to_string())
+}
+
+fn resolve<'a>(schema: &'a mut Value, target: &Target) -> Result<&'a mut Value, String> {
+    match target.kind()? {
+        Kind::Class(name) => class(schema, name),
+        Kind::Property(class_name, name) => property_item(
+            members(schema, class_name, "properties")?
+                .get_mut(name)
+                .ok_or_else(|| "no such property".to_string())?,
+        ),
+        Kind::Method(class_name, name) => {
+            let method = members(schema, class_name, "methods")?
+                .get_mut(name)
+                .ok_or_else(|| "no such method".to_string())?;
+            if method.get("item").is_some() {
+                Ok(&mut method["item"])
+            } else {
+                Ok(method)
+            }
+        }
+        Kind::EnumItem(class_name, property, id) => enum_items(schema, class_name, property)?
+            .iter_mut()
+            .find(|xs| xs["id"] == id)
+            .ok_or_else(|| "no such enum item".to_string()),
+        Kind::Operator(opmod, name) => operators(schema)?
+            .get_mut(opmod)
+            .and_then(|xs| xs.get_mut(name))
+            .ok_or_else(|| "no such operator".to_string()),
+    }
+}
+
+fn remove(schema: &mut Value, target: &Target) -> Result<(), String> {
+    let removed = match target.kind()? {
+        Kind::Class(name) => {
+            let classes = classes(schema)?;
+            let before = classes.len();
+            classes.retain(|xs| xs["name"] != name);
+            before != classes.len()
+        }
+        Kind::Property(class_name, name) => members(schema, class_name, "properties")?.remove(name).is_some(),
+        Kind::Method(class_name, name) => members(schema, class_name, "methods")?.remove(name).is_some(),
+        Kind::EnumItem(class_name, property, id) => {
+            let items = enum_items(schema, class_name, property)?;
+            let before = items.len();
+            items.retain(|xs| xs["id"] != id);
+            before != items.len()
+        }
+        Kind::Operator(opmod, name) => operators(schema)?
+            .get_mut(opmod)
+            .and_then(Value::as_object_mut)
+            .and_then(|xs| xs.remove(name))
+            .is_some(),
+    };
+
+    if removed {
+        Ok(())
+    } else {
+        Err("no such target".to_string())
+    }
+}
+
+fn insert(schema: &mut Value, target: &Target, value: Value) -> Result<(), String> {
+    match target.kind()? {
+        Kind::Class(name) => {
+            if value["name"] != name {
+                return Err("`value.name` must match the class being added".to_string());
+            }
+            let classes = classes(schema)?;
+            match classes.iter_mut().find(|xs| xs["name"] == name) {
+                Some(existing) => *existing = value,
+                None => classes.push(value),
+            }
+        }
+        Kind::Property(class_name, name) => {
+            members(schema, class_name, "properties")?.insert(name.to_string(), value);
+        }
+        Kind::Method(class_name, name) => {
+            members(schema, class_name, "methods")?.insert(name.to_string(), value);
+        }
+        Kind::EnumItem(class_name, property, id) => {
+            let items = enum_items(schema, class_name, property)?;
+            match items.iter_mut().find(|xs| xs["id"] == id) {
+                Some(existing) => *existing = value,
+                None => items.push(value),
+            }
+        }
+        Kind::Operator(opmod, name) => {
+            operators(schema)?
+                .entry(opmod.to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .ok_or_else(|| "malformed operator module".to_string())?
+                .insert(name.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+fn apply_rename(schema: &mut Value, target: &Target, to: &str) -> Result<(), String> {
+    match target.kind()? {
+        Kind::Class(from) => {
+            class(schema, from)?;
+            rename_class(schema, from, to);
+        }
+        Kind::EnumItem(..) => resolve(schema, target)?["id"] = Value::from(to),
+        Kind::Property(..) | Kind::Method(..) | Kind::Operator(..) => {
+            resolve(schema, target)?["identifier"] = Value::from(to)
+        }
+    }
+    Ok(())
+}
+
+/// Rename class `from` to `to`, along with subclasses' parents and bases
+/// (including those of unsupported classes) and every pointer, collection and
+/// parameter type that refers to it.
+fn rename_class(schema: &mut Value, from: &str, to: &str) {
+    for key in ["classes", "unsupported"] {
+        let Some(classes) = schema.get_mut(key).and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for class in classes.iter_mut() {
+            for key in ["name", "parent"] {
+                if class[key] == from {
+                    class[key] = Value::from(to);
+                }
+            }
+            let bases = class.get_mut("bases").and_then(Value::as_array_mut);
+            for base in bases.into_iter().flatten().filter(|xs| *xs == from) {
+                *base = Value::from(to);
+            }
+        }
+    }
+    rename_refs(schema, from, to);
+}
+
+fn rename_refs(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::Object(map) => {
+            for key in ["fixed_type", "collection"] {
+                if let Some(field) = map.get_mut(key) {
+                    if field == from {
+                        *field = Value::from(to);
+                    }
+                }
+            }
+            map.values_mut().for_each(|xs| rename_refs(xs, from, to));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|xs| rename_refs(xs, from, to)),
+        _ => {}
+    }
+}
+
+/// RFC 7386 JSON merge patch.
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let target = target.as_object_mut().expect("just made an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "classes": [
+                { "name": "bpy_struct", "parent": "object", "properties": {}, "methods": {} },
+                { "name": "Mesh", "parent": "bpy_struct", "methods": {
+                    "update": { "type": "rna", "item": { "description": "Update", "parameters": [] } },
+                }, "properties": {
+                    "mode": { "enum": { "identifier": "mode", "description": "Mode", "items": [
+                        { "id": "EDIT", "name": "Edit" },
+                        { "id": "OBJECT", "name": "Object" },
+                    ] } },
+                } },
+                { "name": "Object", "parent": "bpy_struct", "bases": ["bpy_struct", "Mesh"], "methods": {},
+                  "properties": {
+                    "data": { "pointer": { "identifier": "data", "description": "Data", "fixed_type": "Mesh" } },
+                } },
+            ],
+            "unsupported": [{ "name": "MeshMixin", "bases": ["Mesh", "_Mixin"] }],
+            "operators": {
+                "object": { "delete": { "description": "Delete", "parameters": [] } },
+            },
+        })
+    }
+
+    fn overlay(toml: &str) -> Overlay {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn removes_and_adds_members() {
+        let mut schema = schema();
+        overlay(
+            r#"
+            [[remove]]
+            operator = "object.delete"
+
+            [[remove]]
+            class = "Mesh"
+            property = "mode"
+            enum_item = "EDIT"
+
+            [[add]]
+            class = "Mesh"
+            property = "name"
+            value = { string = { identifier = "name", description = "Name" } }
+
+            [[add]]
+            operator = "mesh.primitive_cube_add"
+            value = { description = "Add a cube", parameters = [] }
+            "#,
+        )
+        .apply(&mut schema)
+        .unwrap();
+
+        assert!(schema["operators"]["object"].as_object().unwrap().is_empty());
+        assert_eq!(schema["operators"]["mesh"]["primitive_cube_add"]["description"], "Add a cube");
+        let items = &schema["classes"][1]["properties"]["mode"]["enum"]["items"];
+        assert_eq!(*items, json!([{ "id": "OBJECT", "name": "Object" }]));
+        assert_eq!(schema["classes"][1]["properties"]["name"]["string"]["identifier"], "name");
+    }
+
+    #[test]
+    fn removing_a_missing_target_fails() {
+        let mut schema = schema();
+        let err = overlay("[[remove]]\nclass = \"Mesh\"\nmethod = \"nope\"").apply(&mut schema).unwrap_err();
+        assert_eq!(err, "remove Mesh.nope: no such target");
+        let err = overlay("[[remove]]\nproperty = \"mode\"").apply(&mut schema).unwrap_err();
+        assert_eq!(err, "remove <no class>.mode: <no class>.mode: unsupported combination of target keys");
+    }
+
+    #[test]
+    fn patches_merge_into_the_inner_object() {
+        let mut schema = schema();
+        overlay(
+            r#"
+            [[patch]]
+            class = "Object"
+            property = "data"
+            with = { is_never_none = true, description = "The object's data" }
+
+            [[patch]]
+            class = "Mesh"
+            method = "update"
+            with = { description = "Update the mesh" }
+            "#,
+        )
+        .apply(&mut schema)
+        .unwrap();
+
+        let data = &schema["classes"][2]["properties"]["data"]["pointer"];
+        assert_eq!(data["is_never_none"], true);
+        assert_eq!(data["description"], "The object's data");
+        assert_eq!(data["fixed_type"], "Mesh");
+        assert_eq!(schema["classes"][1]["methods"]["update"]["item"]["description"], "Update the mesh");
+    }
+
+    #[test]
+    fn merge_follows_rfc_7386() {
+        let mut target = json!({ "a": "b", "c": { "d": "e", "f": "g" }, "h": [1, 2] });
+        merge(&mut target, &json!({ "a": "z", "c": { "f": null }, "h": [3], "i": { "j": 1 } }));
+        assert_eq!(target, json!({ "a": "z", "c": { "d": "e" }, "h": [3], "i": { "j": 1 } }));
+
+        let mut target = json!(["a"]);
+        merge(&mut target, &json!({ "a": "b" }));
+        assert_eq!(target, json!({ "a": "b" }));
+    }
+
+    #[test]
+    fn renames_members_by_identifier() {
+        let mut schema = schema();
+        overlay(
+            r#"
+            [[rename]]
+            class = "Object"
+            property = "data"
+            to = "mesh"
+
+            [[rename]]
+            class = "Mesh"
+            property = "mode"
+            enum_item = "OBJECT"
+            to = "OBJ"
+
+            [[rename]]
+            operator = "object.delete"
+            to = "remove"
+            "#,
+        )
+        .apply(&mut schema)
+        .unwrap();
+
+        // The host still knows the property and operator by their keys.
+        assert_eq!(schema["classes"][2]["properties"]["data"]["pointer"]["identifier"], "mesh");
+        assert_eq!(schema["operators"]["object"]["delete"]["identifier"], "remove");
+        assert_eq!(schema["classes"][1]["properties"]["mode"]["enum"]["items"][1]["id"], "OBJ");
+    }
+
+    #[test]
+    fn renames_classes_and_every_reference() {
+        let mut schema = schema();
+        overlay("[[rename]]\nclass = \"Mesh\"\nto = \"MeshData\"").apply(&mut schema).unwrap();
+
+        assert_eq!(schema["classes"][1]["name"], "MeshData");
+        assert_eq!(schema["classes"][2]["bases"], json!(["bpy_struct", "MeshData"]));
+        assert_eq!(schema["classes"][2]["properties"]["data"]["pointer"]["fixed_type"], "MeshData");
+        assert_eq!(schema["unsupported"][0]["bases"], json!(["MeshData", "_Mixin"]));
+        assert_eq!(schema["unsupported"][0].get("parent"), None);
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+
+use blextism_schema::{BpyMethod, BpyProperty, BpyStructure, Schema};
+
+use crate::catalog::Catalog;
+
+/// `Context` and `BlendData` are how plugins reach everything else, so they
+/// are always bound; but only the members that lead to selected classes.
+const ENTRY_POINTS: &[&str] = &["Context", "BlendData"];
+
+/// Which parts of a schema to bind. Patterns match whole names, with `*`
+/// standing for any run of characters (`*Node*`, `Mesh*`).
+///
+/// An empty allow list allows everything. Classes are selected along with
+/// every class they need: their bases, and the `fixed_type` (and collection
+/// class) of their pointer and collection properties and RNA method
+/// parameters. Denied classes are never pulled in; members that need one
+/// aren't bound.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub allow_classes: Vec<String>,
+    pub deny_classes: Vec<String>,
+    /// `bpy.ops` modules: `mesh`, `object`.
+    pub allow_operators: Vec<String>,
+    pub deny_operators: Vec<String>,
+    /// `bpy.data` collections: `meshes`, `node_groups`. Allowing one also
+    /// selects its classes.
+    pub allow_data: Vec<String>,
+    pub deny_data: Vec<String>,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.allow_classes.is_empty()
+            && self.deny_classes.is_empty()
+            && self.allow_operators.is_empty()
+            && self.deny_operators.is_empty()
+            && self.allow_data.is_empty()
+            && self.deny_data.is_empty()
+    }
+
+    /// Drop whatever isn't selected from `schema`, and from `catalog` the
+    /// classes and members that refer to it.
+    pub fn apply(&self, schema: &mut Schema, catalog: &mut Catalog) {
+        if self.is_empty() {
+            return;
+        }
+
+        let kept = self.classes(schema);
+        let keep = |name: &str| kept.contains(name);
+        schema.classes.retain(|xs| keep(&xs.name));
+        for class in &mut schema.classes {
+            class.properties.retain(|_, property| refs(property).all(keep));
+            class.methods.retain(|_, method| match method {
+                BpyMethod::Rna { parameters, .. } => parameters.iter().flat_map(refs).all(keep),
+                _ => true,
+            });
+            if class.name == "BlendData" {
+                class
+                    .properties
+                    .retain(|key, _| selected(&self.allow_data, &self.deny_data, key));
+            }
+        }
+        schema
+            .operators
+            .retain(|opmod, _| selected(&self.allow_operators, &self.deny_operators, opmod));
+        catalog.retain(&keep);
+    }
+
+    /// The names of the classes to bind.
+    fn classes(&self, schema: &Schema) -> HashSet<String> {
+        let classes: HashMap<&str, &BpyStructure> =
+            schema.classes.iter().map(|xs| (xs.name.as_str(), xs)).collect();
+        let denied = |name: &str| {
+            !ENTRY_POINTS.contains(&name) && self.deny_classes.iter().any(|pattern| matches(pattern, name))
+        };
+
+        let mut roots: Vec<&str> = vec!["bpy_struct", "bpy_prop_collection"];
+        roots.extend(
+            classes
+                .keys()
+                .copied()
+                .filter(|name| self.allow_classes.is_empty() || self.allow_classes.iter().any(|xs| matches(xs, name))),
+        );
+        if let Some(blend_data) = classes.get("BlendData") {
+            roots.extend(
+                blend_data
+                    .properties
+                    .iter()
+                    .filter(|(key, _)| !self.allow_data.is_empty() && selected(&self.allow_data, &self.deny_data, key))
+                    .flat_map(|(_, property)| refs(property)),
+            );
+        }
+
+        let mut kept = HashSet::new();
+        let close = |roots: Vec<&str>, kept: &mut HashSet<String>| {
+            let mut stack = roots;
+            while let Some(name) = stack.pop() {
+                let Some(class) = classes.get(name) else {
+                    continue;
+                };
+                if denied(name) || ENTRY_POINTS.contains(&name) || !kept.insert(name.to_string()) {
+                    continue;
+                }
+                stack.extend(class.supers());
+                stack.extend(class.properties.values().flat_map(refs));
+                for method in class.methods.values() {
+                    if let BpyMethod::Rna { parameters, .. } = method {
+                        stack.extend(parameters.iter().flat_map(refs));
+                    }
+                }
+            }
+        };
+        close(roots, &mut kept);
+
+        // The entry points aren't followed, so that they don't select every
+        // class; but their collections of selected classes need the
+        // collection class too.
+        let mut more = Vec::new();
+        for name in ENTRY_POINTS {
+            let Some(class) = classes.get(name) else {
+                continue;
+            };
+            for property in class.properties.values() {
+                if let BpyProperty::Collection {
+                    fixed_type,
+                    collection: Some(collection),
+                    ..
+                } = property
+                {
+                    if kept.contains(fixed_type.as_str()) {
+                        more.push(collection.as_str());
+                    }
+                }
+            }
+            more.extend(class.supers());
+        }
+        close(more, &mut kept);
+        kept.extend(ENTRY_POINTS.iter().filter(|xs| classes.contains_key(*xs)).map(|xs| xs.to_string()));
+
+        // A class whose base was denied can't be bound either, nor then can
+        // its subclasses.
+        loop {
+            let orphans: Vec<String> = kept
+                .iter()
+                .filter(|name| {
+                    classes[name.as_str()]
+                        .supers()
+                        .any(|xs| classes.contains_key(xs) && !kept.contains(xs))
+                })
+                .cloned()
+                .collect();
+            if orphans.is_empty() {
+                break;
+            }
+            for name in orphans {
+                kept.remove(&name);
+            }
+        }
+        kept
+    }
+}
+
+/// Whether `name` passes `allow` (if it isn't empty) and isn't in `deny`.
+fn selected(allow: &[String], deny: &[String], name: &str) -> bool {
+    (allow.is_empty() || allow.iter().any(|xs| matches(xs, name))) && !deny.iter().any(|xs| matches(xs, name))
+}
+
+/// The classes a property's values are: a pointer's `fixed_type`, or a
+/// collection's `fixed_type` and collection class.
+fn refs(property: &BpyProperty) -> impl Iterator<Item = &str> {
+    let (fixed_type, collection) = match property {
+        BpyProperty::Pointer { fixed_type, .. } => (Some(fixed_type), None),
+        BpyProperty::Collection {
+            fixed_type, collection, ..
+        } => (Some(fixed_type), collection.as_ref()),
+        _ => (None, None),
+    };
+    fixed_type.into_iter().chain(collection).map(|xs| xs.as_str())
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters.
+fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
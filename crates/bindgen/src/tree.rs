@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use heck::ToSnekCase;
+use syn::{parse_quote, File, Item, ItemMod, TypeParamBound};
+
+use crate::names;
+
+/// Traits a class family can't be rooted under, since nearly everything
+/// extends them: each is a family of its own.
+const ROOTS: &[&str] = &["BpyStruct", "BpyPropCollection", "Id"];
+
+/// Split generated bindings into the module files
+/// [`Bindings::write_to_dir`](crate::Bindings::write_to_dir) writes, with
+/// paths relative to its directory.
+pub fn split(file: File) -> Vec<(PathBuf, File)> {
+    let mut tree = Tree::default();
+    let File { shebang, attrs, items } = file;
+    let mut root = Vec::with_capacity(items.len());
+    let mut copies = Copies::default();
+    for item in items {
+        match item {
+            Item::Mod(module) if module.ident == "bpy" && module.content.is_some() => {
+                let (module, items, name) = copies.outline(module, true);
+                root.push(Item::Mod(module));
+                tree.bpy(&name, items);
+            }
+            other => root.push(other),
+        }
+    }
+    tree.files.insert(
+        0,
+        (
+            PathBuf::from("mod.rs"),
+            File {
+                shebang,
+                attrs,
+                items: root,
+            },
+        ),
+    );
+    tree.files
+}
+
+#[derive(Default)]
+struct Tree {
+    files: Vec<(PathBuf, File)>,
+}
+
+impl Tree {
+    fn push(&mut self, path: impl Into<PathBuf>, items: Vec<Item>) {
+        self.files.push((
+            path.into(),
+            File {
+                shebang: None,
+                attrs: Vec::new(),
+                items,
+            },
+        ));
+    }
+
+    /// `bpy`'s items, for `dir/mod.rs`.
+    fn bpy(&mut self, dir: &str, items: Vec<Item>) {
+        let mut kept = Vec::with_capacity(items.len());
+        let mut context: Vec<Item> = vec![parse_quote! { use super::*; }];
+        let mut copies = Copies::default();
+        for item in items {
+            match item {
+                Item::Mod(module)
+                    if ["types", "data", "ops"].iter().any(|xs| module.ident == xs) && module.content.is_some() =>
+                {
+                    let kind = module.ident.to_string();
+                    let (module, items, name) = copies.outline(module, kind != "data");
+                    kept.push(Item::Mod(module));
+                    match kind.as_str() {
+                        "types" => self.types(&format!("{}/{}", dir, name), items),
+                        "ops" => self.ops(&format!("{}/{}", dir, name), items),
+                        _ => self.push(format!("{}/{}.rs", dir, name), items),
+                    }
+                }
+                Item::Fn(function) if function.sig.ident == "context" => {
+                    if context.len() == 1 {
+                        kept.push(parse_quote! { mod context; });
+                        kept.push(parse_quote! { pub use self::context::*; });
+                    }
+                    context.push(Item::Fn(function));
+                }
+                other => kept.push(other),
+            }
+        }
+        if context.len() > 1 {
+            self.push(format!("{}/context.rs", dir), context);
+        }
+        self.push(format!("{}/mod.rs", dir), kept);
+    }
+
+    /// `bpy::ops`'s items, for `dir/mod.rs`.
+    fn ops(&mut self, dir: &str, items: Vec<Item>) {
+        let mut kept = Vec::with_capacity(items.len());
+        let mut copies = Copies::default();
+        for item in items {
+            match item {
+                // `r#mod` would need `ops/mod.rs`, so it stays inline.
+                Item::Mod(module) if module.content.is_some() && module.ident != "r#mod" => {
+                    let (module, items, name) = copies.outline(module, false);
+                    kept.push(Item::Mod(module));
+                    self.push(format!("{}/{}.rs", dir, name), items);
+                }
+                other => kept.push(other),
+            }
+        }
+        self.push(format!("{}/mod.rs", dir), kept);
+    }
+
+    /// `bpy::types`'s items, for `dir/mod.rs`.
+    fn types(&mut self, dir: &str, items: Vec<Item>) {
+        // A class's parent is its trait's first supertrait named by a bare
+        // identifier; `std::fmt::Debug` and `private::Sealed` aren't.
+        let mut parents: HashMap<String, String> = HashMap::new();
+        for item in &items {
+            if let Item::Trait(xs) = item {
+                let parent = xs.supertraits.iter().find_map(|bound| match bound {
+                    TypeParamBound::Trait(bound) => bound.path.get_ident().map(|xs| xs.to_string()),
+                    _ => None,
+                });
+                if let Some(parent) = parent {
+                    parents.entry(xs.ident.to_string()).or_insert(parent);
+                }
+            }
+        }
+        let family = |class: &str| {
+            let mut class = class.to_string();
+            while let Some(parent) = parents.get(&class).filter(|xs| !ROOTS.contains(&xs.as_str())) {
+                class = parent.clone();
+            }
+            class
+        };
+
+        let mut kept = Vec::with_capacity(items.len());
+        let mut families: Vec<(String, Vec<Item>)> = Vec::new();
+        let mut copies = Copies::default();
+        for item in items {
+            let class = match &item {
+                Item::Trait(xs) => Some(xs.ident.to_string()),
+                Item::Impl(xs) => match (&xs.trait_, &*xs.self_ty) {
+                    (Some((_, path, _)), _) => path.get_ident().map(|xs| xs.to_string()),
+                    (None, syn::Type::Path(ty)) => ty.path.segments.last().map(|xs| xs.ident.to_string()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match (class, item) {
+                (Some(class), item) => {
+                    let family = family(&class);
+                    match families.iter_mut().find(|(xs, _)| *xs == family) {
+                        Some((_, items)) => items.push(item),
+                        None => {
+                            let module = names::ident(&file_name(&family));
+                            kept.push(parse_quote! { mod #module; });
+                            kept.push(parse_quote! { pub use self::#module::*; });
+                            families.push((family, vec![parse_quote! { use super::*; }, item]));
+                        }
+                    }
+                }
+                (None, Item::Mod(module)) if module.ident == "rna" && module.content.is_some() => {
+                    let (module, items, name) = copies.outline(module, false);
+                    kept.push(Item::Mod(module));
+                    self.push(format!("{}/{}.rs", dir, name), items);
+                }
+                (None, item) => kept.push(item),
+            }
+        }
+        for (family, items) in families {
+            self.push(format!("{}/{}.rs", dir, file_name(&family)), items);
+        }
+        self.push(format!("{}/mod.rs", dir), kept);
+    }
+}
+
+/// The modules one scope has outlined. A module that differs between
+/// Blender versions is emitted once per distinct form, each behind its own
+/// `cfg`: the first gets the module's usual file, and the others
+/// `<name>-2`, `<name>-3`, ..., which a `#[path]` points them at.
+#[derive(Default)]
+struct Copies {
+    seen: HashMap<String, usize>,
+}
+
+impl Copies {
+    /// [`outline`] `module`, returning the name of its file (or, if `dir`,
+    /// of the directory holding its `mod.rs`) without an extension.
+    fn outline(&mut self, module: ItemMod, dir: bool) -> (ItemMod, Vec<Item>, String) {
+        let name = module.ident.to_string().trim_start_matches("r#").to_string();
+        let copy = self.seen.entry(name.clone()).or_default();
+        *copy += 1;
+        let (mut module, items) = outline(module);
+        if *copy == 1 {
+            return (module, items, name);
+        }
+        let name = format!("{}-{}", name, copy);
+        let path = match dir {
+            true => format!("{}/mod.rs", name),
+            false => format!("{}.rs", name),
+        };
+        module.attrs.push(parse_quote! { #[path = #path] });
+        (module, items, name)
+    }
+}
+
+/// The module (and file) name for the family of trait `family`; never
+/// `mod` or `rna`, which are taken.
+fn file_name(family: &str) -> String {
+    let mut name = names::mangle(&family.to_snek_case());
+    if name == "mod" || name == "rna" {
+        name.push('_');
+    }
+    name
+}
+
+/// Turn `module { .. }` into `module;`, returning its items.
+fn outline(mut module: ItemMod) -> (ItemMod, Vec<Item>) {
+    let items = module.content.take().map(|(_, items)| items).unwrap_or_default();
+    module.semi = Some(Default::default());
+    (module, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+
+    use super::*;
+
+    #[test]
+    fn versioned_copies_of_a_module_get_files_too() {
+        let file: File = parse_quote! {
+            #[cfg(blextism_blender = "3.6")]
+            pub mod bpy {
+                pub mod ops {
+                    pub mod object {}
+                }
+            }
+            #[cfg(blextism_blender = "4.0")]
+            pub mod bpy {
+                #[cfg(blextism_blender = "4.0")]
+                pub mod data {}
+                #[cfg(blextism_blender = "4.0")]
+                pub mod ops {
+                    /// Since 4.0.
+                    pub mod object {}
+                    pub mod object {}
+                }
+            }
+        };
+        let files: Vec<_> = split(file)
+            .into_iter()
+            .map(|(path, file)| (path.display().to_string(), file.into_token_stream().to_string()))
+            .collect();
+        let paths: Vec<_> = files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "mod.rs",
+                "bpy/ops/object.rs",
+                "bpy/ops/mod.rs",
+                "bpy/mod.rs",
+                "bpy-2/data.rs",
+                "bpy-2/ops/object.rs",
+                "bpy-2/ops/object-2.rs",
+                "bpy-2/ops/mod.rs",
+                "bpy-2/mod.rs",
+            ]
+        );
+        let expected: File = parse_quote! {
+            #[cfg(blextism_blender = "3.6")]
+            pub mod bpy;
+            #[cfg(blextism_blender = "4.0")]
+            #[path = "bpy-2/mod.rs"]
+            pub mod bpy;
+        };
+        assert_eq!(files[0].1, expected.into_token_stream().to_string());
+        assert!(files[7].1.ends_with("# [path = \"object-2.rs\"] pub mod object ;"), "{}", files[7].1);
+    }
+}
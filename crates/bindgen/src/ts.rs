@@ -0,0 +1,664 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+
+use crate::ir::{Api, Class, Enum, MemberKind, Names, Param, Receiver, Ty};
+
+/// JavaScript's reserved words; parameters named after one get a `_`.
+const RESERVED: &[&str] = &[
+    "arguments", "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete",
+    "do", "else", "enum", "eval", "export", "extends", "false", "finally", "for", "function", "if", "implements",
+    "import", "in", "instanceof", "interface", "let", "new", "null", "package", "private", "protected", "public",
+    "return", "static", "super", "switch", "this", "throw", "true", "try", "typeof", "var", "void", "while",
+    "with", "yield",
+];
+
+/// Names `bpy.d.ts` declares next to `types`, which no class or enum may
+/// shadow.
+const PRELUDE: &[&str] = &["BpyPtr", "Json", "PropCollection"];
+
+/// The runtime's hand-written half: the host calls, and the conversions
+/// between `BpyPtr`s and the host's pointers.
+const RUNTIME: &str = r#"const host = Host.getFunctions();
+
+function send(fn, ...strings) {
+    const memory = strings.map((xs) => Memory.fromString(xs));
+    const offset = host[fn](...memory.map((xs) => xs.offset));
+    memory.forEach((xs) => xs.free());
+    if (!offset) {
+        return null;
+    }
+    const response = Memory.find(offset);
+    const result = JSON.parse(response.readString());
+    response.free();
+    return result;
+}
+
+// `PyArgs`, as the Rust bindings serialize it.
+function pyArgs({ self = null, cls, args = null, kwargs = null, id, out }) {
+    const pyargs = { self };
+    if (cls !== undefined) {
+        pyargs["@class"] = cls;
+    }
+    pyargs.args = args;
+    pyargs.kwargs = kwargs;
+    if (id !== undefined) {
+        pyargs["@id"] = id;
+    }
+    if (out !== undefined) {
+        pyargs["@out"] = out;
+    }
+    return JSON.stringify(pyargs);
+}
+
+function getattr(attr, args) {
+    return send("bpy_getattr", attr, pyArgs(args));
+}
+
+function setattr(attr, args) {
+    send("bpy_setattr", attr, pyArgs(args));
+}
+
+function callmethod(method, args) {
+    return send("bpy_callmethod", method, pyArgs(args));
+}
+
+function operator(opmod, name, params) {
+    return send("bpy_operator", opmod, name, pyArgs({ args: [], kwargs: lower(params ?? {}) }));
+}
+
+/** A pointer to something on the host; every class in `types` is one. */
+export class BpyPtr {
+    constructor(ptr) {
+        this.$ptr = ptr;
+    }
+}
+
+// Host pointers for `BpyPtr`s, in anything sent to the host.
+function lower(value) {
+    if (value instanceof BpyPtr) {
+        return value.$ptr;
+    }
+    if (Array.isArray(value)) {
+        return value.map(lower);
+    }
+    if (value !== null && typeof value === "object") {
+        return Object.fromEntries(Object.entries(value).map(([key, xs]) => [key, lower(xs)]));
+    }
+    return value ?? null;
+}
+
+const classes = new Map();
+
+// A host pointer as `cls`, or as the subclass of it the host says it is.
+function wrap(cls, ptr) {
+    if (ptr === null || ptr === undefined) {
+        return null;
+    }
+    const actual = classes.get(ptr["@type"]);
+    return new (actual && actual.prototype instanceof cls ? actual : cls)(ptr);
+}
+
+function collection(cls, item, ptr) {
+    if (ptr === null || ptr === undefined) {
+        return null;
+    }
+    const xs = new cls(ptr);
+    xs.get = (key) => wrap(item, callmethod("get", { self: ptr, args: [key] }));
+    xs.keys = () => callmethod("keys", { self: ptr });
+    xs.values = () => callmethod("values", { self: ptr }).map((value) => wrap(item, value));
+    xs.items = () => callmethod("items", { self: ptr }).map(([key, value]) => [key, wrap(item, value)]);
+    return xs;
+}
+
+function define(name, cls) {
+    cls.$name = name;
+    classes.set(name, cls);
+}
+
+// Copy the methods of `base` and its ancestors that `cls` doesn't have, for
+// classes with more than one base.
+function mixin(cls, base) {
+    for (let proto = base.prototype; proto && proto !== BpyPtr.prototype; proto = Object.getPrototypeOf(proto)) {
+        for (const key of Object.getOwnPropertyNames(proto)) {
+            if (!(key in cls.prototype)) {
+                Object.defineProperty(cls.prototype, key, Object.getOwnPropertyDescriptor(proto, key));
+            }
+        }
+    }
+}
+
+let pointers = null;
+
+// A pointer from the `bpy.data` config table.
+function bpyData(key) {
+    pointers ??= JSON.parse(Config.get("bpy.data"));
+    if (!(key in pointers)) {
+        throw new Error(`'bpy.data' is missing an entry for ${JSON.stringify(key)}`);
+    }
+    return { "@ptr": pointers[key] };
+}
+"#;
+
+/// The hand-written half of `bpy.d.ts`.
+const DECLARATIONS: &str = r#"/** Anything JSON can hold. */
+export type Json = null | boolean | number | string | Json[] | { [key: string]: Json };
+
+/** A pointer to something on the host; every class in `types` is one. */
+export declare class BpyPtr {
+    protected constructor();
+    /** The pointer as the host sent it. */
+    readonly $ptr: { "@ptr": number; "@type"?: string };
+}
+
+/** A `bpy_prop_collection`. */
+export interface PropCollection<T> {
+    get(key: string): T | null;
+    keys(): string[];
+    values(): T[];
+    items(): [string, T][];
+}
+"#;
+
+/// `bpy.d.ts` and `bpy.js`: TypeScript declarations mirroring the Rust
+/// bindings, and the module that implements them for extism's JS PDK. The
+/// module calls the same `chrisdickinson:blender/bpy` host functions, with
+/// the same `PyArgs`.
+///
+/// - `types`: a class per `bpy.types` class, extending its parent, with a
+///   method per property getter, setter, `reset*` and `is*Set` and per RNA
+///   or catalog method (static for class-level functions), named as in Rust
+///   in camelCase. Optional parameters are passed together, as `kwargs`.
+///   Enum properties get a union of their items, pointers return `T | null`
+///   unless never `None`, and collections a `PropCollection` of their items.
+/// - `ops`: a function per operator, taking its parameters as an object and
+///   returning the operator's result.
+/// - `context()` and `data()`.
+pub fn ts(api: &Api) -> (String, String) {
+    let mut ts = TypeScript::new(api);
+    let mut declarations = header(api);
+    let mut runtime = header(api);
+    declarations.push_str(DECLARATIONS);
+    runtime.push_str(RUNTIME);
+
+    let mut classes = String::new();
+    let mut definitions = String::new();
+    runtime.push_str("\nexport const types = {};\n");
+    for class in ts.order(api) {
+        classes.push_str(&ts.class(class, &mut definitions));
+    }
+    declarations.push_str("\nexport declare namespace types {\n");
+    for def in ts.enums.values() {
+        declarations.push_str(def);
+    }
+    declarations.push_str(&classes);
+    declarations.push_str("}\n");
+    runtime.push_str(&definitions);
+
+    for (function, class, description) in [("context", "Context", "`bpy.context`"), ("data", "BlendData", "`bpy.data`")] {
+        if let Some(name) = ts.classes.get(class) {
+            let _ = write!(declarations, "\n/** {} */\nexport declare function {}(): types.{};\n", description, function, name);
+            let _ = write!(
+                runtime,
+                "\nexport function {}() {{\n    return wrap(types.{}, bpyData({}));\n}}\n",
+                function,
+                name,
+                string(function)
+            );
+        }
+    }
+
+    declarations.push_str("\nexport declare const ops: {\n");
+    runtime.push_str("\nexport const ops = {\n");
+    for module in &api.operators {
+        let mut functions = Names::default();
+        let name = property(&module.name);
+        let _ = writeln!(declarations, "    /** `bpy.ops.{}` */", module.name);
+        let _ = writeln!(declarations, "    {}: {{", name);
+        let _ = writeln!(runtime, "    {}: {{", name);
+        for operator in &module.operators {
+            let function = property(&functions.claim(&operator.name));
+            let fields: Vec<String> = operator
+                .params
+                .iter()
+                .map(|param| format!("{}?: {}", property(&param.name), ts.operator_param(&param.ty)))
+                .collect();
+            let params = if fields.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", fields.join("; "))
+            };
+            doc(&mut declarations, "        ", &operator.description);
+            let _ = writeln!(declarations, "        {}(params?: {}): Json;", function, params);
+            let _ = writeln!(
+                runtime,
+                "        {}(params) {{\n            return operator({}, {}, params);\n        }},",
+                function,
+                string(&module.key),
+                string(&operator.key)
+            );
+        }
+        declarations.push_str("    };\n");
+        runtime.push_str("    },\n");
+    }
+    declarations.push_str("};\n");
+    runtime.push_str("};\n");
+
+    (declarations, runtime)
+}
+
+fn header(api: &Api) -> String {
+    match &api.version {
+        Some(version) => format!("// Generated by blextism-bindgen from Blender {}'s schema.\n\n", version),
+        None => "// Generated by blextism-bindgen.\n\n".to_string(),
+    }
+}
+
+/// `name` as an object key: as-is if it's an identifier, quoted if not.
+fn property(name: &str) -> String {
+    let is_ident = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_ident {
+        name.to_string()
+    } else {
+        string(name)
+    }
+}
+
+/// `name` as a member or parameter name: camelCase, with a `_` in front if
+/// it would start with a digit.
+fn ident(name: &str) -> String {
+    let name = name.to_lower_camel_case();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// `xs` as a string literal.
+fn string(xs: &str) -> String {
+    serde_json::to_string(xs).expect("strings serialize")
+}
+
+/// A doc comment for `text`, indented by `indent`.
+fn doc(out: &mut String, indent: &str, text: &str) {
+    let lines: Vec<&str> = text.lines().map(|xs| xs.trim_end()).filter(|xs| !xs.trim().is_empty()).collect();
+    match lines.as_slice() {
+        [] => {}
+        [line] => {
+            let _ = writeln!(out, "{}/** {} */", indent, line.replace("*/", "*\\/"));
+        }
+        lines => {
+            let _ = writeln!(out, "{}/**", indent);
+            for line in lines {
+                let _ = writeln!(out, "{} * {}", indent, line.replace("*/", "*\\/"));
+            }
+            let _ = writeln!(out, "{} */", indent);
+        }
+    }
+}
+
+struct TypeScript {
+    /// Each class's name in `types`, by its name in `bpy.types`.
+    classes: HashMap<String, String>,
+    names: Names,
+    /// Enum type aliases, by the name they were claimed under.
+    enums: std::collections::BTreeMap<String, String>,
+    /// The type each enum was given, by its path; a property's getter and
+    /// setter share it.
+    paths: HashMap<Vec<String>, String>,
+    /// The signature of every member each class declares or inherits from
+    /// its parent, by class and member.
+    signatures: HashMap<String, HashMap<String, String>>,
+}
+
+impl TypeScript {
+    fn new(api: &Api) -> Self {
+        let mut names = Names::default();
+        for name in PRELUDE {
+            names.claim(name);
+        }
+        let classes = api
+            .classes
+            .iter()
+            .map(|xs| (xs.name.to_string(), names.claim(&xs.name.to_upper_camel_case())))
+            .collect();
+        TypeScript {
+            classes,
+            names,
+            enums: Default::default(),
+            paths: HashMap::new(),
+            signatures: HashMap::new(),
+        }
+    }
+
+    /// The classes, each after its bases.
+    fn order<'a>(&self, api: &'a Api) -> Vec<&'a Class> {
+        fn visit<'a>(api: &'a Api, class: &'a Class, seen: &mut HashSet<&'a str>, out: &mut Vec<&'a Class>) {
+            if !seen.insert(&class.name) {
+                return;
+            }
+            for base in class.bases.iter().filter_map(|xs| api.class(xs)) {
+                visit(api, base, seen, out);
+            }
+            out.push(class);
+        }
+        let mut seen = HashSet::new();
+        let mut out = Vec::with_capacity(api.classes.len());
+        for class in &api.classes {
+            visit(api, class, &mut seen, &mut out);
+        }
+        out
+    }
+
+    /// `class`'s declaration; its definition goes in `definitions`.
+    fn class(&mut self, class: &Class, definitions: &mut String) -> String {
+        let name = self.classes[class.name.as_str()].clone();
+        let parent = match class.bases.first() {
+            Some(base) => format!("types.{}", self.classes[base.as_str()]),
+            None => "BpyPtr".to_string(),
+        };
+
+        let mut members = Names::default();
+        members.claim("constructor");
+        members.claim("prototype");
+        // Each member's name (`static` ones with `static `), doc comment and
+        // signature.
+        let mut declared: Vec<(String, String, String)> = Vec::new();
+        let mut defined = String::new();
+        for member in &class.members {
+            let function = members.claim(&ident(&member.name));
+            let (key, id) = (&member.key, member.id);
+            match &member.kind {
+                MemberKind::Get { ty } => {
+                    let mut comment = String::new();
+                    doc(&mut comment, "        ", &member.description);
+                    declared.push((function.clone(), comment, format!("{}(): {};", function, self.ty(ty))));
+                    let _ = writeln!(
+                        defined,
+                        "    {}() {{\n        return {};\n    }}",
+                        function,
+                        self.lift(ty, &format!("getattr({}, {{ self: this.$ptr, id: {} }})", string(key), id)),
+                    );
+                }
+                MemberKind::Set { ty } => {
+                    let signature = format!("{}(value: {}): void;", function, self.ty(ty));
+                    declared.push((function.clone(), String::new(), signature));
+                    let _ = writeln!(
+                        defined,
+                        "    {}(value) {{\n        setattr({}, {{ self: this.$ptr, args: [lower(value)], id: {} }});\n    }}",
+                        function,
+                        string(key),
+                        id
+                    );
+                }
+                MemberKind::Reset => {
+                    declared.push((function.clone(), String::new(), format!("{}(): void;", function)));
+                    let _ = writeln!(
+                        defined,
+                        "    {}() {{\n        callmethod(\"property_unset\", {{ self: this.$ptr, args: [{}], id: {} }});\n    }}",
+                        function,
+                        string(key),
+                        id
+                    );
+                }
+                MemberKind::IsSet => {
+                    declared.push((function.clone(), String::new(), format!("{}(): boolean;", function)));
+                    let _ = writeln!(
+                        defined,
+                        "    {}() {{\n        return callmethod(\"is_property_set\", {{ self: this.$ptr, args: [{}], id: {} }});\n    }}",
+                        function,
+                        string(key),
+                        id
+                    );
+                }
+                MemberKind::Method {
+                    receiver,
+                    params,
+                    returns,
+                    out,
+                } => {
+                    let (required, optional): (Vec<&Param>, Vec<&Param>) = params.iter().partition(|xs| xs.required);
+                    let mut param_names = Names::default();
+                    let kwargs = (!optional.is_empty()).then(|| param_names.claim("kwargs"));
+                    let mut decls = Vec::new();
+                    let mut args = Vec::new();
+                    for param in &required {
+                        let mut wanted = ident(&param.name);
+                        if RESERVED.contains(&wanted.as_str()) {
+                            wanted.push('_');
+                        }
+                        let param_name = param_names.claim(&wanted);
+                        decls.push(format!("{}: {}", param_name, self.ty(&param.ty)));
+                        args.push(param_name);
+                    }
+                    if let Some(kwargs) = &kwargs {
+                        let fields: Vec<String> = optional
+                            .iter()
+                            .map(|param| format!("{}?: {}", property(&param.name), self.ty(&param.ty)))
+                            .collect();
+                        decls.push(format!("{}?: {{ {} }}", kwargs, fields.join("; ")));
+                    }
+
+                    let description = params
+                        .iter()
+                        .filter(|xs| !xs.description.is_empty())
+                        .map(|xs| format!("- {}: {}", xs.name, xs.description))
+                        .fold(member.description.to_string(), |lhs, rhs| format!("{}\n{}", lhs, rhs));
+                    let is_static = *receiver != Receiver::Instance;
+                    let returns_ts = returns.as_ref().map_or("void".to_string(), |xs| self.ty(xs));
+                    let static_ = if is_static { "static " } else { "" };
+                    let mut comment = String::new();
+                    doc(&mut comment, "        ", &description);
+                    declared.push((
+                        format!("{}{}", static_, function),
+                        comment,
+                        format!("{}{}({}): {};", static_, function, decls.join(", "), returns_ts),
+                    ));
+
+                    let mut pyargs = match receiver {
+                        Receiver::Instance => "self: this.$ptr".to_string(),
+                        Receiver::Class => format!("cls: {}", string(&class.name)),
+                        Receiver::ClassOf => "cls: this.$name".to_string(),
+                    };
+                    let lowered: Vec<String> = args.iter().map(|xs| format!("lower({})", xs)).collect();
+                    let _ = write!(pyargs, ", args: [{}]", lowered.join(", "));
+                    if let Some(kwargs) = &kwargs {
+                        let _ = write!(pyargs, ", kwargs: lower({} ?? {{}})", kwargs);
+                    }
+                    let _ = write!(pyargs, ", id: {}", id);
+                    if let Some(out) = out {
+                        let _ = write!(pyargs, ", out: {}", out);
+                    }
+                    let call = format!("callmethod({}, {{ {} }})", string(key), pyargs);
+                    let body = match returns {
+                        Some(ty) => format!("return {};", self.lift(ty, &call)),
+                        None => format!("{};", call),
+                    };
+                    let _ = writeln!(
+                        defined,
+                        "    {}{}({}) {{\n        {}\n    }}",
+                        if is_static { "static " } else { "" },
+                        function,
+                        args.iter().chain(&kwargs).cloned().collect::<Vec<_>>().join(", "),
+                        body
+                    );
+                }
+            }
+        }
+
+        let _ = write!(
+            definitions,
+            "\ntypes.{} = class extends {} {{\n{}}};\ndefine({}, types.{});\n",
+            name,
+            parent,
+            defined,
+            string(&class.name),
+            name
+        );
+        for base in class.bases.iter().skip(1) {
+            let _ = writeln!(definitions, "mixin(types.{}, types.{});", name, self.classes[base.as_str()]);
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(out, "\n    /** `bpy.types.{}` */", class.name);
+        let extends = match class.bases.first() {
+            Some(base) => self.classes[base.as_str()].clone(),
+            None => "BpyPtr".to_string(),
+        };
+        let _ = writeln!(out, "    export class {} extends {} {{", name, extends);
+        // A class can redefine a member of one of its bases, with another
+        // type; TypeScript only allows that if the types are compatible, so
+        // such members keep their base's declaration. Their definition is
+        // still the class's own.
+        let mut signatures = class
+            .bases
+            .first()
+            .and_then(|xs| self.signatures.get(xs.as_str()))
+            .cloned()
+            .unwrap_or_default();
+        for (key, comment, signature) in declared {
+            match signatures.get(&key) {
+                Some(inherited) if *inherited != signature => continue,
+                _ => {}
+            }
+            out.push_str(&comment);
+            let _ = writeln!(out, "        {}", signature);
+            signatures.insert(key, signature);
+        }
+        self.signatures.insert(class.name.to_string(), signatures);
+        out.push_str("    }\n");
+        if class.bases.len() > 1 {
+            let others: Vec<&str> = class.bases[1..].iter().map(|xs| self.classes[xs.as_str()].as_str()).collect();
+            let _ = writeln!(out, "    export interface {} extends {} {{}}", name, others.join(", "));
+        }
+        out
+    }
+
+    /// The TypeScript type of `ty`, inside `types`. Enums are declared the
+    /// first time they're seen.
+    fn ty(&mut self, ty: &Ty) -> String {
+        match ty {
+            Ty::Bool => "boolean".to_string(),
+            Ty::Int | Ty::UInt | Ty::Float => "number".to_string(),
+            Ty::String => "string".to_string(),
+            Ty::Json => "Json".to_string(),
+            Ty::List(inner) => list(self.ty(inner)),
+            Ty::Optional(inner) => format!("{} | null", self.ty(inner)),
+            Ty::Enum(xs) => self.enum_(xs),
+            Ty::Struct(class) => self.struct_(class),
+            Ty::Collection { item, class } => {
+                let collection = format!("PropCollection<{}>", self.struct_(item));
+                match class {
+                    Some(class) => format!("{} & {}", collection, self.struct_(class)),
+                    None => collection,
+                }
+            }
+        }
+    }
+
+    /// The type of an operator parameter, outside `types`: enums are
+    /// written out rather than named, as operators take them by name.
+    fn operator_param(&self, ty: &Ty) -> String {
+        match ty {
+            Ty::Bool => "boolean".to_string(),
+            Ty::Int | Ty::UInt | Ty::Float => "number".to_string(),
+            Ty::String => "string".to_string(),
+            Ty::Json => "Json".to_string(),
+            Ty::List(inner) => list(self.operator_param(inner)),
+            Ty::Optional(inner) => format!("{} | null", self.operator_param(inner)),
+            Ty::Enum(xs) => {
+                let items = union(xs);
+                if xs.is_flag {
+                    list(items)
+                } else {
+                    items
+                }
+            }
+            Ty::Struct(class) => format!("types.{}", self.struct_(class)),
+            Ty::Collection { item, .. } => format!("types.{}[]", self.struct_(item)),
+        }
+    }
+
+    fn struct_(&self, class: &str) -> String {
+        self.classes
+            .get(class)
+            .or_else(|| self.classes.get("bpy_struct"))
+            .cloned()
+            .unwrap_or_else(|| "BpyPtr".to_string())
+    }
+
+    fn enum_(&mut self, xs: &Enum) -> String {
+        if xs.items.is_empty() {
+            return if xs.is_flag { "string[]" } else { "string" }.to_string();
+        }
+        let path: Vec<String> = xs.path.iter().map(|xs| xs.to_string()).collect();
+        let name = match self.paths.get(&path) {
+            Some(name) => name.clone(),
+            None => {
+                let wanted: String = path.iter().map(|xs| xs.to_upper_camel_case()).collect();
+                let name = self.names.claim(&wanted);
+                let mut def = String::new();
+                let _ = writeln!(def);
+                doc(&mut def, "    ", &xs.description);
+                let _ = writeln!(def, "    export type {} = {};", name, union(xs));
+                self.enums.insert(name.clone(), def);
+                self.paths.insert(path, name.clone());
+                name
+            }
+        };
+        if xs.is_flag {
+            format!("{}[]", name)
+        } else {
+            name
+        }
+    }
+
+    /// JavaScript that turns `expr`, the host's response, into a `ty`.
+    fn lift(&self, ty: &Ty, expr: &str) -> String {
+        match ty {
+            Ty::Struct(class) => format!("wrap(types.{}, {})", self.struct_(class), expr),
+            Ty::Optional(inner) => self.lift(inner, expr),
+            Ty::List(inner) if has_structs(inner) => format!("{}.map((xs) => {})", expr, self.lift(inner, "xs")),
+            Ty::Collection { item, class } => {
+                let class = match class {
+                    Some(class) => format!("types.{}", self.struct_(class)),
+                    None => match self.classes.get("bpy_prop_collection") {
+                        Some(name) => format!("types.{}", name),
+                        None => "BpyPtr".to_string(),
+                    },
+                };
+                format!("collection({}, types.{}, {})", class, self.struct_(item), expr)
+            }
+            _ => expr.to_string(),
+        }
+    }
+}
+
+fn has_structs(ty: &Ty) -> bool {
+    match ty {
+        Ty::Struct(_) | Ty::Collection { .. } => true,
+        Ty::List(inner) | Ty::Optional(inner) => has_structs(inner),
+        _ => false,
+    }
+}
+
+/// `T[]`, with parentheses if `T` is a union or intersection.
+fn list(inner: String) -> String {
+    if inner.contains(['|', '&']) {
+        format!("({})[]", inner)
+    } else {
+        format!("{}[]", inner)
+    }
+}
+
+/// An enum's items, as a union of string literals.
+fn union(xs: &Enum) -> String {
+    if xs.items.is_empty() {
+        return "string".to_string();
+    }
+    let items: Vec<String> = xs.items.iter().map(|item| string(&item.id)).collect();
+    items.join(" | ")
+}
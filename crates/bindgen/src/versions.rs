@@ -0,0 +1,344 @@
+use std::collections::{BTreeSet, HashMap};
+
+use quote::ToTokens;
+use syn::{parse_quote, Attribute, File, ImplItem, Item, TraitItem};
+
+/// The `cfg` that selects the Blender version bindings merged from several
+/// schemas are compiled for: `--cfg blextism_blender="4.0"`. The pdk's build
+/// script sets it; see the README.
+pub const CFG: &str = "blextism_blender";
+
+/// `version` as numbers, for ordering: `"3.6"` -> `[3, 6]`.
+pub fn parse(version: &str) -> Result<Vec<u32>, String> {
+    version
+        .split('.')
+        .map(|xs| xs.parse().map_err(|_| format!("bad Blender version {:?}; expected e.g. \"4.0\"", version)))
+        .collect()
+}
+
+/// Merge bindings generated from several schemas, one per Blender version in
+/// `versions` (oldest first), into one file.
+///
+/// Items are matched by kind and name, down through modules, traits and
+/// impls. Those identical in every version are emitted as-is; the rest are
+/// emitted once per distinct form, behind a [`CFG`] `cfg` for the versions
+/// that have it. A module, trait or impl whose header differs (a trait's
+/// supertraits, say) is split the same way, with its members merged among the
+/// versions that share the header.
+///
+/// The version is a `cfg` rather than a cargo feature, as features are
+/// unified across a build and several versions could end up enabled. Setting
+/// none is an error: there's no version to fall back on silently.
+pub fn merge(versions: &[String], files: Vec<File>) -> File {
+    let merger = Merger { versions };
+    let all: BTreeSet<usize> = (0..files.len()).collect();
+    let attrs = files.first().map(|xs| xs.attrs.clone()).unwrap_or_default();
+    let cfg = syn::Ident::new(CFG, proc_macro2::Span::call_site());
+    let message = format!(
+        "these bindings are for Blender {}; choose one with `--cfg {}=\"<version>\"`",
+        versions.join(", "),
+        CFG
+    );
+    let mut items: Vec<Item> = vec![parse_quote! {
+        #[cfg(not(any(#(#cfg = #versions),*)))]
+        compile_error!(#message);
+    }];
+    items.extend(merger.merge(
+        files.into_iter().enumerate().map(|(version, file)| (version, file.items)).collect(),
+        &all,
+    ));
+    File {
+        shebang: None,
+        attrs,
+        items,
+    }
+}
+
+struct Merger<'a> {
+    versions: &'a [String],
+}
+
+impl Merger<'_> {
+    /// Merge one scope's items, given per version. `within` is the versions
+    /// the scope itself exists in; items in all of them need no `cfg`.
+    fn merge<N: Node>(&self, scopes: Vec<(usize, Vec<N>)>, within: &BTreeSet<usize>) -> Vec<N> {
+        // Items only some versions have are placed after the item that
+        // precedes them in the first version that has them.
+        let mut order: Vec<String> = Vec::new();
+        let mut groups: HashMap<String, Vec<(usize, N)>> = HashMap::new();
+        for (version, items) in scopes {
+            let mut seen: HashMap<String, usize> = HashMap::new();
+            let mut prev: Option<String> = None;
+            for item in items {
+                let base = item.key();
+                let n = seen.entry(base.clone()).or_default();
+                *n += 1;
+                let key = format!("{}#{}", base, n);
+
+                if !groups.contains_key(&key) {
+                    let at = match &prev {
+                        Some(prev) if order.last() == Some(prev) => order.len(),
+                        Some(prev) => order.iter().position(|xs| xs == prev).map_or(order.len(), |xs| xs + 1),
+                        None => 0,
+                    };
+                    order.insert(at, key.clone());
+                }
+                groups.entry(key.clone()).or_default().push((version, item));
+                prev = Some(key);
+            }
+        }
+
+        let mut merged = Vec::with_capacity(order.len());
+        for key in order {
+            let mut forms: Vec<(String, Vec<(usize, N)>)> = Vec::new();
+            for (version, item) in groups.remove(&key).unwrap_or_default() {
+                let header = item.header();
+                match forms.iter_mut().find(|(xs, _)| *xs == header) {
+                    Some((_, items)) => items.push((version, item)),
+                    None => forms.push((header, vec![(version, item)])),
+                }
+            }
+
+            for (_, items) in forms {
+                let versions: BTreeSet<usize> = items.iter().map(|(version, _)| *version).collect();
+                let mut item = items[0].1.clone();
+                item.merge_body(self, items, &versions);
+                if versions != *within {
+                    item.attrs_mut().insert(0, self.cfg(&versions));
+                }
+                merged.push(item);
+            }
+        }
+        merged
+    }
+
+    /// The `cfg` for items only `versions` have.
+    fn cfg(&self, versions: &BTreeSet<usize>) -> Attribute {
+        let cfg = syn::Ident::new(CFG, proc_macro2::Span::call_site());
+        let versions: Vec<&str> = versions.iter().map(|xs| self.versions[*xs].as_str()).collect();
+        match versions.as_slice() {
+            [version] => parse_quote! { #[cfg(#cfg = #version)] },
+            _ => parse_quote! { #[cfg(any(#(#cfg = #versions),*))] },
+        }
+    }
+}
+
+/// An item the merger matches up across versions.
+trait Node: Clone + ToTokens {
+    /// What identifies the item within its scope: its kind and name.
+    fn key(&self) -> String;
+
+    /// The item without its members; versions whose headers differ get
+    /// separate copies. Items without members are all header.
+    fn header(&self) -> String {
+        self.to_token_stream().to_string()
+    }
+
+    /// Replace this item's members with those of `items` merged.
+    fn merge_body(&mut self, _merger: &Merger, _items: Vec<(usize, Self)>, _versions: &BTreeSet<usize>) {}
+
+    fn attrs_mut(&mut self) -> &mut Vec<Attribute>;
+}
+
+impl Node for Item {
+    fn key(&self) -> String {
+        match self {
+            Item::Const(xs) => format!("const {}", xs.ident),
+            Item::Fn(xs) => format!("fn {}", xs.sig.ident),
+            Item::Mod(xs) => format!("mod {}", xs.ident),
+            Item::Struct(xs) => format!("struct {}", xs.ident),
+            Item::Trait(xs) => format!("trait {}", xs.ident),
+            Item::Impl(xs) => {
+                let trait_ = xs.trait_.as_ref().map(|(_, path, _)| path.to_token_stream().to_string());
+                format!("impl {} for {}", trait_.unwrap_or_default(), xs.self_ty.to_token_stream())
+            }
+            other => other.to_token_stream().to_string(),
+        }
+    }
+
+    fn header(&self) -> String {
+        let mut header = self.clone();
+        match &mut header {
+            Item::Mod(xs) => {
+                if let Some((_, items)) = &mut xs.content {
+                    items.clear();
+                }
+            }
+            Item::Trait(xs) => xs.items.clear(),
+            Item::Impl(xs) => xs.items.clear(),
+            _ => {}
+        }
+        header.to_token_stream().to_string()
+    }
+
+    fn merge_body(&mut self, merger: &Merger, items: Vec<(usize, Self)>, versions: &BTreeSet<usize>) {
+        match self {
+            Item::Mod(xs) => {
+                let scopes = items
+                    .into_iter()
+                    .filter_map(|(version, item)| match item {
+                        Item::Mod(xs) => xs.content.map(|(_, items)| (version, items)),
+                        _ => None,
+                    })
+                    .collect();
+                if let Some((_, items)) = &mut xs.content {
+                    *items = merger.merge(scopes, versions);
+                }
+            }
+            Item::Trait(xs) => {
+                let scopes = items
+                    .into_iter()
+                    .filter_map(|(version, item)| match item {
+                        Item::Trait(xs) => Some((version, xs.items)),
+                        _ => None,
+                    })
+                    .collect();
+                xs.items = merger.merge(scopes, versions);
+            }
+            Item::Impl(xs) => {
+                let scopes = items
+                    .into_iter()
+                    .filter_map(|(version, item)| match item {
+                        Item::Impl(xs) => Some((version, xs.items)),
+                        _ => None,
+                    })
+                    .collect();
+                xs.items = merger.merge(scopes, versions);
+            }
+            _ => {}
+        }
+    }
+
+    fn attrs_mut(&mut self) -> &mut Vec<Attribute> {
+        match self {
+            Item::Const(xs) => &mut xs.attrs,
+            Item::Enum(xs) => &mut xs.attrs,
+            Item::ExternCrate(xs) => &mut xs.attrs,
+            Item::Fn(xs) => &mut xs.attrs,
+            Item::ForeignMod(xs) => &mut xs.attrs,
+            Item::Impl(xs) => &mut xs.attrs,
+            Item::Macro(xs) => &mut xs.attrs,
+            Item::Mod(xs) => &mut xs.attrs,
+            Item::Static(xs) => &mut xs.attrs,
+            Item::Struct(xs) => &mut xs.attrs,
+            Item::Trait(xs) => &mut xs.attrs,
+            Item::TraitAlias(xs) => &mut xs.attrs,
+            Item::Type(xs) => &mut xs.attrs,
+            Item::Union(xs) => &mut xs.attrs,
+            Item::Use(xs) => &mut xs.attrs,
+            other => unreachable!("bindgen doesn't generate {}", other.to_token_stream()),
+        }
+    }
+}
+
+impl Node for TraitItem {
+    fn key(&self) -> String {
+        match self {
+            TraitItem::Const(xs) => format!("const {}", xs.ident),
+            TraitItem::Fn(xs) => format!("fn {}", xs.sig.ident),
+            TraitItem::Type(xs) => format!("type {}", xs.ident),
+            other => other.to_token_stream().to_string(),
+        }
+    }
+
+    fn attrs_mut(&mut self) -> &mut Vec<Attribute> {
+        match self {
+            TraitItem::Const(xs) => &mut xs.attrs,
+            TraitItem::Fn(xs) => &mut xs.attrs,
+            TraitItem::Type(xs) => &mut xs.attrs,
+            TraitItem::Macro(xs) => &mut xs.attrs,
+            other => unreachable!("bindgen doesn't generate {}", other.to_token_stream()),
+        }
+    }
+}
+
+impl Node for ImplItem {
+    fn key(&self) -> String {
+        match self {
+            ImplItem::Const(xs) => format!("const {}", xs.ident),
+            ImplItem::Fn(xs) => format!("fn {}", xs.sig.ident),
+            ImplItem::Type(xs) => format!("type {}", xs.ident),
+            other => other.to_token_stream().to_string(),
+        }
+    }
+
+    fn attrs_mut(&mut self) -> &mut Vec<Attribute> {
+        match self {
+            ImplItem::Const(xs) => &mut xs.attrs,
+            ImplItem::Fn(xs) => &mut xs.attrs,
+            ImplItem::Type(xs) => &mut xs.attrs,
+            ImplItem::Macro(xs) => &mut xs.attrs,
+            other => unreachable!("bindgen doesn't generate {}", other.to_token_stream()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse("4.10"), Ok(vec![4, 10]));
+        assert!(parse("4.x").is_err());
+    }
+
+    #[test]
+    fn merge_puts_what_differs_behind_cfgs() {
+        let v36: File = parse_quote! {
+            pub mod types {
+                pub trait Object {
+                    fn name(&self) -> String;
+                    fn grease_pencil(&self) -> bool;
+                }
+                pub fn shared() {}
+            }
+        };
+        let v40: File = parse_quote! {
+            pub mod types {
+                pub trait Object {
+                    fn name(&self) -> String;
+                    fn name_full(&self) -> String;
+                }
+                pub fn shared() {}
+                pub fn newer(x: i32) {}
+            }
+        };
+        let v41: File = parse_quote! {
+            pub mod types {
+                pub trait Object {
+                    fn name(&self) -> String;
+                    fn name_full(&self) -> String;
+                }
+                pub fn shared() {}
+                pub fn newer(x: i64) {}
+            }
+        };
+        let versions = ["3.6", "4.0", "4.1"].map(String::from);
+
+        let merged = merge(&versions, vec![v36, v40, v41]);
+        let expected: File = parse_quote! {
+            #[cfg(not(any(blextism_blender = "3.6", blextism_blender = "4.0", blextism_blender = "4.1")))]
+            compile_error!(
+                "these bindings are for Blender 3.6, 4.0, 4.1; choose one with `--cfg blextism_blender=\"<version>\"`"
+            );
+            pub mod types {
+                pub trait Object {
+                    fn name(&self) -> String;
+                    // Placed after the item before it in the first version
+                    // that has it.
+                    #[cfg(any(blextism_blender = "4.0", blextism_blender = "4.1"))]
+                    fn name_full(&self) -> String;
+                    #[cfg(blextism_blender = "3.6")]
+                    fn grease_pencil(&self) -> bool;
+                }
+                pub fn shared() {}
+                #[cfg(blextism_blender = "4.0")]
+                pub fn newer(x: i32) {}
+                #[cfg(blextism_blender = "4.1")]
+                pub fn newer(x: i64) {}
+            }
+        };
+        assert_eq!(merged.to_token_stream().to_string(), expected.to_token_stream().to_string());
+    }
+}
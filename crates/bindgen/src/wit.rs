@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+use heck::ToKebabCase;
+
+use crate::ir::{Api, Class, Enum, MemberKind, Names, OperatorModule, Receiver, Ty};
+
+/// WIT's keywords; identifiers that collide with these are escaped with `%`.
+const KEYWORDS: &[&str] = &[
+    "as", "bool", "borrow", "char", "constructor", "enum", "export", "f32", "f64", "flags", "float32",
+    "float64", "from", "func", "future", "import", "include", "interface", "list", "option", "own",
+    "package", "record", "resource", "result", "s16", "s32", "s64", "s8", "static", "stream", "string",
+    "tuple", "type", "u16", "u32", "u64", "u8", "use", "variant", "with", "world",
+];
+
+/// WIT allows at most this many flags in a `flags` type; flag enums with
+/// more items are lists of the enum instead.
+const MAX_FLAGS: usize = 32;
+
+/// A WIT package, `chrisdickinson:blender`, mirroring the `bpy` Rust API:
+///
+/// - `interface types`: a resource per class, with a method per property
+///   getter, setter, `reset-*` and `is-*-set` and per RNA or catalog method
+///   (static for class-level functions), named as in Rust. Resources can't
+///   extend each other, so each has an `as-*` method per base instead.
+///   Enum properties get an `enum` type (or `flags`, for flag enums),
+///   pointers return `option<T>` unless never `None`, and collections return
+///   `list<T>`.
+/// - `interface bpy`: `context` and `data`.
+/// - `interface ops-<module>`: a function per operator, taking every
+///   parameter as an `option`, and returning the operator's result as JSON.
+/// - `world plugin`, importing them all.
+///
+/// The interface `bpy` is `chrisdickinson:blender/bpy`, the module the
+/// extism host functions are imported from.
+pub fn wit(api: &Api) -> String {
+    let mut out = String::new();
+    match api.version.as_deref().map(|xs| xs.split('.').collect::<Vec<_>>()) {
+        Some(version) if version.len() == 2 => {
+            let _ = writeln!(out, "package chrisdickinson:blender@{}.{}.0;", version[0], version[1]);
+        }
+        _ => out.push_str("package chrisdickinson:blender;\n"),
+    }
+
+    let mut types = Types::new(api);
+    out.push_str(&types.interface(api));
+    out.push_str(&bpy_interface(&types));
+
+    let mut modules = Names::default();
+    let mut imports = vec!["types".to_string(), "bpy".to_string()];
+    for module in &api.operators {
+        let name = modules.claim(&format!("ops-{}", wit_name(&module.name)));
+        out.push_str(&ops_interface(&name, module, &types));
+        imports.push(name);
+    }
+
+    out.push_str("\nworld plugin {\n");
+    for name in imports {
+        let _ = writeln!(out, "    import {};", escape(&name));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// `name` as a WIT identifier, not yet [escaped](escape): kebab-case, with
+/// words that start with a digit joined to the word before them
+/// (`use_3d_cursor` -> `use3d-cursor`).
+fn wit_name(name: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    for word in name.to_kebab_case().split('-').filter(|xs| !xs.is_empty()) {
+        let word: String = word.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+        if word.is_empty() {
+            continue;
+        }
+        match words.last_mut() {
+            Some(last) if word.starts_with(|c: char| c.is_ascii_digit()) => last.push_str(&word),
+            None if word.starts_with(|c: char| c.is_ascii_digit()) => words.push(format!("n{}", word)),
+            _ => words.push(word),
+        }
+    }
+    if words.is_empty() {
+        return "unnamed".to_string();
+    }
+    words.join("-")
+}
+
+/// `name`, with a `%` if it's a keyword.
+fn escape(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("%{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// `///` lines for `text`, indented by `indent`.
+fn doc(out: &mut String, indent: &str, text: &str) {
+    for line in text.lines().filter(|xs| !xs.trim().is_empty()) {
+        let _ = writeln!(out, "{}/// {}", indent, line.trim_end());
+    }
+}
+
+/// Whether a type is returned, or taken as a parameter (where resources are
+/// borrowed).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Return,
+    Param,
+}
+
+/// `interface types`: the resource for every class, and the enum types of
+/// their properties and parameters.
+struct Types {
+    resources: HashMap<String, String>,
+    names: Names,
+    /// `enum` and `flags` definitions, by the name they were claimed under.
+    defs: BTreeMap<String, String>,
+    /// The type each enum was given, by the name it was wanted under; a
+    /// property's getter and setter share it.
+    enums: HashMap<String, String>,
+}
+
+impl Types {
+    fn new(api: &Api) -> Self {
+        let mut names = Names::default();
+        let resources = api
+            .classes
+            .iter()
+            .map(|xs| (xs.name.to_string(), names.claim(&wit_name(&xs.name))))
+            .collect();
+        Types {
+            resources,
+            names,
+            defs: BTreeMap::new(),
+            enums: HashMap::new(),
+        }
+    }
+
+    /// The resource for `class`; `bpy-struct` for classes the schema
+    /// doesn't have, as in Rust.
+    fn resource(&self, class: &str) -> String {
+        let name = self
+            .resources
+            .get(class)
+            .or_else(|| self.resources.get("bpy_struct"))
+            .map(|xs| xs.as_str())
+            .unwrap_or("bpy-struct");
+        escape(name)
+    }
+
+    fn interface(&mut self, api: &Api) -> String {
+        let mut resources = String::new();
+        for class in &api.classes {
+            resources.push_str(&self.class(class));
+        }
+
+        let mut out = String::from("\ninterface types {\n");
+        for def in self.defs.values() {
+            out.push_str(def);
+        }
+        out.push_str(&resources);
+        out.push_str("}\n");
+        out
+    }
+
+    fn class(&mut self, class: &Class) -> String {
+        // Members are named as in Rust, so they're already unique, unless
+        // two Rust names make the same WIT name.
+        let mut members = Names::default();
+        let mut method = |name: &str| escape(&members.claim(&wit_name(name)));
+        let mut out = String::new();
+        for member in &class.members {
+            let name = method(&member.name);
+            match &member.kind {
+                MemberKind::Get { ty } => {
+                    let ty = self.ty(ty, Position::Return);
+                    doc(&mut out, "        ", &member.description);
+                    let _ = writeln!(out, "        {}: func() -> {};", name, ty);
+                }
+                MemberKind::Set { ty } => {
+                    let _ = writeln!(out, "        {}: func(value: {});", name, self.ty(ty, Position::Param));
+                }
+                MemberKind::Reset => {
+                    let _ = writeln!(out, "        {}: func();", name);
+                }
+                MemberKind::IsSet => {
+                    let _ = writeln!(out, "        {}: func() -> bool;", name);
+                }
+                MemberKind::Method {
+                    receiver,
+                    params,
+                    returns,
+                    ..
+                } => {
+                    let params: Vec<String> = params
+                        .iter()
+                        .map(|param| {
+                            let ty = self.ty(&param.ty, Position::Param);
+                            let ty = if param.required { ty } else { format!("option<{}>", ty) };
+                            format!("{}: {}", escape(&wit_name(&param.name)), ty)
+                        })
+                        .collect();
+                    let returns = returns.as_ref().map(|xs| format!(" -> {}", self.ty(xs, Position::Return)));
+                    doc(&mut out, "        ", &member.description);
+                    let _ = writeln!(
+                        out,
+                        "        {}: {}func({}){};",
+                        name,
+                        if *receiver == Receiver::Instance { "" } else { "static " },
+                        params.join(", "),
+                        returns.unwrap_or_default(),
+                    );
+                }
+            }
+        }
+
+        let mut upcasts = String::new();
+        for base in &class.bases {
+            let name = members.claim(&format!("as-{}", wit_name(base)));
+            let _ = writeln!(upcasts, "        {}: func() -> {};", escape(&name), self.resource(base));
+        }
+
+        let mut resource = String::new();
+        let _ = writeln!(resource, "\n    /// `bpy.types.{}`", class.name);
+        let _ = writeln!(resource, "    resource {} {{", self.resource(&class.name));
+        resource.push_str(&upcasts);
+        resource.push_str(&out);
+        resource.push_str("    }\n");
+        resource
+    }
+
+    /// The WIT type of `ty`. Enums are defined the first time they're seen.
+    fn ty(&mut self, ty: &Ty, position: Position) -> String {
+        if let Some(ty) = scalar(ty) {
+            return ty;
+        }
+        match ty {
+            Ty::List(inner) => format!("list<{}>", self.ty(inner, position)),
+            Ty::Optional(inner) => format!("option<{}>", self.ty(inner, position)),
+            Ty::Enum(xs) => self.enum_(xs),
+            Ty::Struct(class) => match position {
+                Position::Param => format!("borrow<{}>", self.resource(class)),
+                Position::Return => self.resource(class),
+            },
+            Ty::Collection { item, .. } => match position {
+                Position::Param => format!("list<borrow<{}>>", self.resource(item)),
+                Position::Return => format!("list<{}>", self.resource(item)),
+            },
+            _ => unreachable!("scalars are handled above"),
+        }
+    }
+
+    fn enum_(&mut self, xs: &Enum) -> String {
+        // Enums whose items are only known at runtime have none here.
+        if xs.items.is_empty() {
+            return if xs.is_flag { "list<string>" } else { "string" }.to_string();
+        }
+
+        let wanted: Vec<String> = xs.path.iter().map(|xs| wit_name(xs)).collect();
+        let wanted = wanted.join("-");
+        if let Some(ty) = self.enums.get(&wanted) {
+            return ty.clone();
+        }
+        let name = self.names.claim(&wanted);
+        let mut cases = Names::default();
+        let mut def = String::new();
+        let _ = writeln!(def);
+        doc(&mut def, "    ", &xs.description);
+        let kind = if xs.is_flag && xs.items.len() <= MAX_FLAGS { "flags" } else { "enum" };
+        let _ = writeln!(def, "    {} {} {{", kind, escape(&name));
+        for item in &xs.items {
+            let case = cases.claim(&wit_name(&item.id));
+            doc(&mut def, "        ", &item.description);
+            let _ = writeln!(def, "        {},", escape(&case));
+        }
+        def.push_str("    }\n");
+        self.defs.insert(name.clone(), def);
+
+        let ty = match kind {
+            "enum" if xs.is_flag => format!("list<{}>", escape(&name)),
+            _ => escape(&name),
+        };
+        self.enums.insert(wanted, ty.clone());
+        ty
+    }
+}
+
+/// The WIT type of `ty`, if it's neither an enum nor made of structs.
+fn scalar(ty: &Ty) -> Option<String> {
+    let ty = match ty {
+        Ty::Bool => "bool".to_string(),
+        Ty::Int => "s64".to_string(),
+        Ty::UInt => "u64".to_string(),
+        Ty::Float => "f64".to_string(),
+        Ty::String | Ty::Json => "string".to_string(),
+        Ty::List(inner) => format!("list<{}>", scalar(inner)?),
+        Ty::Optional(inner) => format!("option<{}>", scalar(inner)?),
+        Ty::Enum(_) | Ty::Struct(_) | Ty::Collection { .. } => return None,
+    };
+    Some(ty)
+}
+
+/// `interface bpy`: the entry points, as `bpy::context()` and
+/// `bpy::data::blend_data()` in Rust.
+fn bpy_interface(types: &Types) -> String {
+    let mut out = String::from("\ninterface bpy {\n");
+    let mut used = Vec::new();
+    let mut functions = String::new();
+    for (function, class, doc) in [("context", "Context", "`bpy.context`"), ("data", "BlendData", "`bpy.data`")] {
+        if types.resources.contains_key(class) {
+            // Functions and types share a namespace, and `Context`'s
+            // resource is `context` too.
+            let resource = types.resource(class);
+            let (used_as, local) = if resource == function {
+                let local = format!("types-{}", resource.trim_start_matches('%'));
+                (format!("{} as {}", resource, local), local)
+            } else {
+                (resource.clone(), resource)
+            };
+            let _ = writeln!(functions, "    /// {}", doc);
+            let _ = writeln!(functions, "    {}: func() -> {};", function, local);
+            used.push(used_as);
+        }
+    }
+    if !used.is_empty() {
+        let _ = writeln!(out, "    use types.{{{}}};\n", used.join(", "));
+    }
+    out.push_str(&functions);
+    out.push_str("}\n");
+    out
+}
+
+/// `interface ops-<module>`: one function per operator in `bpy.ops.<module>`.
+fn ops_interface(name: &str, module: &OperatorModule, types: &Types) -> String {
+    let mut body = String::new();
+    let mut used = HashSet::new();
+    let mut functions = Names::default();
+    for operator in &module.operators {
+        let function = functions.claim(&wit_name(&operator.name));
+        let mut params = Vec::new();
+        let mut param_names = Names::default();
+        for param in &operator.params {
+            // Operator enums are passed by name, like in Rust.
+            let ty = match &param.ty {
+                Ty::Enum(xs) if xs.is_flag => "list<string>".to_string(),
+                Ty::Enum(_) => "string".to_string(),
+                Ty::Struct(class) => {
+                    let resource = types.resource(class);
+                    used.insert(resource.clone());
+                    format!("borrow<{}>", resource)
+                }
+                Ty::Collection { item, .. } => {
+                    let resource = types.resource(item);
+                    used.insert(resource.clone());
+                    format!("list<borrow<{}>>", resource)
+                }
+                other => scalar(other).unwrap_or_else(|| "string".to_string()),
+            };
+            let param = param_names.claim(&wit_name(&param.name));
+            params.push(format!("{}: option<{}>", escape(&param), ty));
+        }
+        doc(&mut body, "    ", &operator.description);
+        let _ = writeln!(
+            body,
+            "    {}: func({}) -> string;",
+            escape(&function),
+            params.join(", ")
+        );
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "\n/// `bpy.ops.{}`. Every function returns the operator's result, as JSON.", module.name);
+    let _ = writeln!(out, "interface {} {{", escape(name));
+    if !used.is_empty() {
+        let mut used: Vec<_> = used.into_iter().collect();
+        used.sort();
+        let _ = writeln!(out, "    use types.{{{}}};\n", used.join(", "));
+    }
+    out.push_str(&body);
+    out.push_str("}\n");
+    out
+}
@@ -0,0 +1,91 @@
+use serde_json::{Map, Value};
+
+/// Why the host refused a call. These surface to the plugin as a failed host
+/// function call, and to the embedder as the error from [`extism::Plugin::call`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostError {
+    /// The plugin sent a pointer the host never handed out.
+    UnknownPtr(i64),
+    /// The plugin sent a pointer with a different `@type` than the one the
+    /// host handed out.
+    TypeMismatch {
+        ptr: i64,
+        expected: String,
+        actual: String,
+    },
+    /// The request did not match the protocol, e.g. a getattr without `self`.
+    BadRequest(String),
+    /// The backend failed to carry out a well-formed request.
+    Backend(String),
+}
+
+impl std::fmt::Display for HostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostError::UnknownPtr(ptr) => write!(f, "unknown pointer {}", ptr),
+            HostError::TypeMismatch {
+                ptr,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "pointer {} is a {:?}, but the plugin sent it as a {:?}",
+                ptr, expected, actual
+            ),
+            HostError::BadRequest(msg) => write!(f, "bad request: {}", msg),
+            HostError::Backend(msg) => write!(f, "backend error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
+
+/// Whatever stands in for `bpy` on the host side: a simulator, a recording,
+/// or a bridge into a real Blender process.
+///
+/// The host takes care of the wire protocol and pointer bookkeeping; a backend
+/// only sees pointers the plugin is allowed to use, and returns JSON values
+/// with `bpy_struct`s lowered to `{"@ptr": ptr, "@type": class}`.
+pub trait Backend {
+    /// The `bpy.data` config payload: `"context"` plus one lowered pointer per
+    /// `bpy.data` member.
+    fn bpy_data(&mut self) -> Result<Map<String, Value>, HostError>;
+
+    fn getattr(&mut self, target: i64, attr: &str) -> Result<Value, HostError>;
+
+    fn setattr(&mut self, target: i64, attr: &str, value: Value) -> Result<(), HostError>;
+
+    /// Call `target.method(..)`. Methods that fill an argument in place, such
+    /// as `foreach_get`, write it back into `args`; the host sends it to the
+    /// plugin when the request names it with `@out`.
+    fn callmethod(
+        &mut self,
+        target: i64,
+        method: &str,
+        args: &mut [Value],
+        kwargs: Map<String, Value>,
+    ) -> Result<Value, HostError>;
+
+    /// Call a class-level function, `bpy.types.<class>.<method>(..)`. The
+    /// default refuses, for backends that only model instances.
+    fn classmethod(
+        &mut self,
+        class: &str,
+        method: &str,
+        _args: &mut [Value],
+        _kwargs: Map<String, Value>,
+    ) -> Result<Value, HostError> {
+        Err(HostError::Backend(format!(
+            "class-level function {}.{} is not supported",
+            class, method
+        )))
+    }
+
+    fn operator(
+        &mut self,
+        opmod: &str,
+        operator: &str,
+        args: Vec<Value>,
+        kwargs: Map<String, Value>,
+    ) -> Result<Value, HostError>;
+}
@@ -0,0 +1,415 @@
+use extism::{CurrentPlugin, Error, Function, Manifest, Plugin, PluginBuilder, UserData, Val, PTR};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{Backend, HostError, Registry};
+
+/// The extism namespace the `blextism` pdk imports its host functions from.
+pub const NAMESPACE: &str = "chrisdickinson:blender/bpy";
+
+/// The JSON the pdk sends as the last argument of every host function.
+#[derive(Deserialize, Default)]
+struct Request {
+    #[serde(rename = "self", default)]
+    target: Option<Value>,
+    #[serde(rename = "@class", default)]
+    class: Option<String>,
+    #[serde(default)]
+    args: Option<Vec<Value>>,
+    #[serde(default)]
+    kwargs: Option<Map<String, Value>>,
+    /// The argument a method fills in place, which is sent back instead of
+    /// the method's result.
+    #[serde(rename = "@out", default)]
+    out: Option<usize>,
+}
+
+struct State<B> {
+    backend: B,
+    registry: Registry,
+}
+
+impl<B> State<B> {
+    /// Validate every pointer in `request` and return its `self` pointer.
+    fn check(&self, request: &Request, what: &str) -> Result<Option<i64>, HostError> {
+        let target = match &request.target {
+            None | Some(Value::Null) => None,
+            Some(value) => {
+                self.registry.check(value)?;
+                let ptr = value
+                    .get("@ptr")
+                    .and_then(Value::as_i64)
+                    .ok_or_else(|| HostError::BadRequest(format!("{}: `self` is not a pointer", what)))?;
+                Some(ptr)
+            }
+        };
+
+        for value in request.args.iter().flatten() {
+            self.registry.check(value)?;
+        }
+        for value in request.kwargs.iter().flat_map(|xs| xs.values()) {
+            self.registry.check(value)?;
+        }
+        Ok(target)
+    }
+}
+
+impl<B: Backend> State<B> {
+    fn getattr(&mut self, attr: &str, request: &Request) -> Result<Value, HostError> {
+        let target = required(self.check(request, attr)?, attr)?;
+        let value = self.backend.getattr(target, attr)?;
+        self.registry.register(&value);
+        Ok(value)
+    }
+
+    fn setattr(&mut self, attr: &str, request: &Request) -> Result<(), HostError> {
+        let target = required(self.check(request, attr)?, attr)?;
+        let value = match request.args.as_deref() {
+            Some([value]) => value.clone(),
+            _ => {
+                return Err(HostError::BadRequest(format!(
+                    "setattr {} expects exactly one argument",
+                    attr
+                )))
+            }
+        };
+        self.backend.setattr(target, attr, value)
+    }
+
+    fn callmethod(&mut self, method: &str, request: &Request) -> Result<Value, HostError> {
+        let target = self.check(request, method)?;
+        let mut args = request.args.clone().unwrap_or_default();
+        let kwargs = request.kwargs.clone().unwrap_or_default();
+        if request.out.is_some_and(|out| out >= args.len()) {
+            return Err(HostError::BadRequest(format!("{}: `@out` is not an argument", method)));
+        }
+        let value = match (target, request.class.as_deref()) {
+            (Some(target), _) => self.backend.callmethod(target, method, &mut args, kwargs)?,
+            (None, Some(class)) => self.backend.classmethod(class, method, &mut args, kwargs)?,
+            (None, None) => {
+                return Err(HostError::BadRequest(format!(
+                    "{} called without `self` or `@class`",
+                    method
+                )))
+            }
+        };
+        // The method filled this argument in place (e.g. `foreach_get`).
+        let value = match request.out {
+            Some(out) => args.swap_remove(out),
+            None => value,
+        };
+        self.registry.register(&value);
+        Ok(value)
+    }
+
+    fn operator(&mut self, opmod: &str, operator: &str, request: &Request) -> Result<Value, HostError> {
+        self.check(request, operator)?;
+        // Optional positional arguments are sent as trailing nulls; operators
+        // only take keywords, so drop them the same way run.py does.
+        let mut args = request.args.clone().unwrap_or_default();
+        while args.last().is_some_and(Value::is_null) {
+            args.pop();
+        }
+        let kwargs = request.kwargs.clone().unwrap_or_default();
+        let value = self.backend.operator(opmod, operator, args, kwargs)?;
+        self.registry.register(&value);
+        Ok(value)
+    }
+}
+
+/// A Rust implementation of the `chrisdickinson:blender/bpy` host functions,
+/// answering from a [`Backend`].
+///
+/// This is the reference implementation of the protocol `run.py` speaks:
+/// every pointer the plugin sends must have been handed out by the host, with
+/// the same `@type`, and every failure is reported instead of being swallowed.
+///
+/// ```no_run
+/// use blextism_host::{Backend, Host};
+/// use extism::{Manifest, Wasm};
+///
+/// fn run(backend: impl Backend + Send + 'static) {
+///     let host = Host::new(backend);
+///     let manifest = Manifest::new([Wasm::file("target/wasm32-wasi/debug/plugin.wasm")]);
+///     let mut plugin = host.plugin(manifest).unwrap();
+///     plugin.call::<&str, &str>("example", "").unwrap();
+/// }
+/// ```
+pub struct Host<B> {
+    state: UserData<State<B>>,
+}
+
+impl<B> Clone for Host<B> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<B: Backend + Send + 'static> Host<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            state: UserData::new(State {
+                backend,
+                registry: Registry::new(),
+            }),
+        }
+    }
+
+    /// Run `f` against the backend, e.g. to inspect it after a plugin call.
+    pub fn with_backend<R>(&self, f: impl FnOnce(&mut B) -> R) -> R {
+        let state = self.state.get().expect("host state must be rust user data");
+        let mut state = state.lock().expect("host state poisoned");
+        f(&mut state.backend)
+    }
+
+    /// A copy of the pointers handed out so far.
+    pub fn registry(&self) -> Registry {
+        let state = self.state.get().expect("host state must be rust user data");
+        let state = state.lock().expect("host state poisoned");
+        state.registry.clone()
+    }
+
+    /// The `bpy.data` config value: a JSON object of member name to pointer.
+    /// Every pointer in it is registered.
+    pub fn bpy_data(&self) -> Result<String, HostError> {
+        let state = self.state.get().map_err(|err| HostError::Backend(err.to_string()))?;
+        let mut state = state.lock().expect("host state poisoned");
+        let lowered = state.backend.bpy_data()?;
+
+        let mut payload = Map::new();
+        for (name, value) in lowered {
+            let ptr = value
+                .get("@ptr")
+                .unwrap_or(&value)
+                .as_i64()
+                .ok_or_else(|| HostError::Backend(format!("bpy.data.{} is not a pointer", name)))?;
+            state
+                .registry
+                .register_ptr(ptr, value.get("@type").and_then(Value::as_str));
+            payload.insert(name, Value::from(ptr));
+        }
+        Ok(Value::Object(payload).to_string())
+    }
+
+    /// The four host functions, namespaced under [`NAMESPACE`].
+    pub fn functions(&self) -> Vec<Function> {
+        vec![
+            Function::new("bpy_getattr", [PTR, PTR], [PTR], self.state.clone(), bpy_getattr::<B>),
+            Function::new("bpy_setattr", [PTR, PTR], [], self.state.clone(), bpy_setattr::<B>),
+            Function::new(
+                "bpy_callmethod",
+                [PTR, PTR],
+                [PTR],
+                self.state.clone(),
+                bpy_callmethod::<B>,
+            ),
+            Function::new(
+                "bpy_operator",
+                [PTR, PTR, PTR],
+                [PTR],
+                self.state.clone(),
+                bpy_operator::<B>,
+            ),
+        ]
+        .into_iter()
+        .map(|xs| xs.with_namespace(NAMESPACE))
+        .collect()
+    }
+
+    /// Build a plugin from `manifest` with WASI, the host functions and the
+    /// `bpy.data` config installed.
+    pub fn plugin(&self, manifest: Manifest) -> Result<Plugin, Error> {
+        let manifest = manifest.with_config_key("bpy.data", self.bpy_data()?);
+        PluginBuilder::new(manifest)
+            .with_wasi(true)
+            .with_functions(self.functions())
+            .build()
+    }
+}
+
+fn read_str(plugin: &mut CurrentPlugin, input: &Val) -> Result<String, Error> {
+    Ok(plugin.memory_get_val::<&str>(input)?.to_string())
+}
+
+fn read_request(plugin: &mut CurrentPlugin, input: &Val) -> Result<Request, Error> {
+    let request = plugin.memory_get_val::<&str>(input)?;
+    serde_json::from_str(request).map_err(|err| HostError::BadRequest(err.to_string()).into())
+}
+
+fn write_value(plugin: &mut CurrentPlugin, output: &mut Val, value: &Value) -> Result<(), Error> {
+    let handle = plugin.memory_new(value.to_string())?;
+    *output = plugin.memory_to_val(handle);
+    Ok(())
+}
+
+fn with_state<B, R>(
+    data: &UserData<State<B>>,
+    f: impl FnOnce(&mut State<B>) -> Result<R, HostError>,
+) -> Result<R, Error> {
+    let state = data.get()?;
+    let mut state = state.lock().map_err(|_| Error::msg("host state poisoned"))?;
+    Ok(f(&mut state)?)
+}
+
+fn required(target: Option<i64>, what: &str) -> Result<i64, HostError> {
+    target.ok_or_else(|| HostError::BadRequest(format!("{} called without `self`", what)))
+}
+
+fn bpy_getattr<B: Backend>(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    data: UserData<State<B>>,
+) -> Result<(), Error> {
+    let attr = read_str(plugin, &inputs[0])?;
+    let request = read_request(plugin, &inputs[1])?;
+    let value = with_state(&data, |state| state.getattr(&attr, &request))?;
+    write_value(plugin, &mut outputs[0], &value)
+}
+
+fn bpy_setattr<B: Backend>(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    _outputs: &mut [Val],
+    data: UserData<State<B>>,
+) -> Result<(), Error> {
+    let attr = read_str(plugin, &inputs[0])?;
+    let request = read_request(plugin, &inputs[1])?;
+    with_state(&data, |state| state.setattr(&attr, &request))
+}
+
+fn bpy_callmethod<B: Backend>(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    data: UserData<State<B>>,
+) -> Result<(), Error> {
+    let method = read_str(plugin, &inputs[0])?;
+    let request = read_request(plugin, &inputs[1])?;
+    let value = with_state(&data, |state| state.callmethod(&method, &request))?;
+    write_value(plugin, &mut outputs[0], &value)
+}
+
+fn bpy_operator<B: Backend>(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    data: UserData<State<B>>,
+) -> Result<(), Error> {
+    let opmod = read_str(plugin, &inputs[0])?;
+    let operator = read_str(plugin, &inputs[1])?;
+    let request = read_request(plugin, &inputs[2])?;
+    let value = with_state(&data, |state| state.operator(&opmod, &operator, &request))?;
+    write_value(plugin, &mut outputs[0], &value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// `bpy.data.meshes` holds one mesh, whose `foreach_get` fills its buffer
+    /// with the vertex count.
+    struct Meshes;
+
+    impl Backend for Meshes {
+        fn bpy_data(&mut self) -> Result<Map<String, Value>, HostError> {
+            Ok(Map::new())
+        }
+
+        fn getattr(&mut self, _target: i64, attr: &str) -> Result<Value, HostError> {
+            match attr {
+                "data" => Ok(json!({ "@ptr": 2, "@type": "Mesh" })),
+                _ => Ok(Value::Null),
+            }
+        }
+
+        fn setattr(&mut self, _target: i64, _attr: &str, _value: Value) -> Result<(), HostError> {
+            Ok(())
+        }
+
+        fn callmethod(
+            &mut self,
+            _target: i64,
+            method: &str,
+            args: &mut [Value],
+            _kwargs: Map<String, Value>,
+        ) -> Result<Value, HostError> {
+            if method == "foreach_get" {
+                args[1] = json!([8, 8, 8]);
+            }
+            Ok(Value::Null)
+        }
+
+        fn operator(
+            &mut self,
+            _opmod: &str,
+            _operator: &str,
+            _args: Vec<Value>,
+            _kwargs: Map<String, Value>,
+        ) -> Result<Value, HostError> {
+            Ok(Value::Null)
+        }
+    }
+
+    /// Host state that has handed out `{"@ptr": 1, "@type": "Object"}`.
+    fn state() -> State<Meshes> {
+        let mut registry = Registry::new();
+        registry.register_ptr(1, Some("Object"));
+        State {
+            backend: Meshes,
+            registry,
+        }
+    }
+
+    fn request(value: Value) -> Request {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn out_arguments_are_sent_back() {
+        let mut state = state();
+        let mesh = state.getattr("data", &request(json!({ "self": { "@ptr": 1, "@type": "Object" } })));
+        assert_eq!(mesh, Ok(json!({ "@ptr": 2, "@type": "Mesh" })));
+
+        let filled = state.callmethod(
+            "foreach_get",
+            &request(json!({ "self": { "@ptr": 2 }, "args": ["count", [0, 0, 0]], "@out": 1 })),
+        );
+        assert_eq!(filled, Ok(json!([8, 8, 8])));
+
+        let unfilled = state.callmethod(
+            "foreach_get",
+            &request(json!({ "self": { "@ptr": 2 }, "args": [], "@out": 1 })),
+        );
+        assert!(matches!(unfilled, Err(HostError::BadRequest(_))));
+    }
+
+    #[test]
+    fn pointers_sent_as_another_type_are_refused() {
+        let mut state = state();
+        let mismatch = HostError::TypeMismatch {
+            ptr: 1,
+            expected: "Object".to_string(),
+            actual: "Mesh".to_string(),
+        };
+        let as_mesh = json!({ "@ptr": 1, "@type": "Mesh" });
+
+        let getattr = state.getattr("data", &request(json!({ "self": as_mesh })));
+        assert_eq!(getattr, Err(mismatch.clone()));
+        let setattr = state.setattr("name", &request(json!({ "self": as_mesh, "args": ["Cube"] })));
+        assert_eq!(setattr, Err(mismatch.clone()));
+        let callmethod = state.callmethod("select_set", &request(json!({ "self": as_mesh, "args": [true] })));
+        assert_eq!(callmethod, Err(mismatch.clone()));
+        let operator = state.operator("object", "delete", &request(json!({ "kwargs": { "target": as_mesh } })));
+        assert_eq!(operator, Err(mismatch));
+
+        assert_eq!(
+            state.getattr("data", &request(json!({ "self": { "@ptr": 3, "@type": "Object" } }))),
+            Err(HostError::UnknownPtr(3))
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! A Rust host for `blextism` plugins.
+//!
+//! [`Host`] implements the `chrisdickinson:blender/bpy` host functions on the
+//! extism runtime, over any [`Backend`]. Unlike `run.py`, it validates every
+//! pointer a plugin sends against a [`Registry`] of the pointers it handed
+//! out, and reports protocol errors to the caller instead of swallowing them.
+//! With `blextism-test`'s `host` feature, a [`Simulator`] can serve as the
+//! backend, so a real `.wasm` plugin can run in CI without Blender.
+//!
+//! [`Simulator`]: https://docs.rs/blextism-test/latest/blextism_test/struct.Simulator.html
+mod backend;
+mod host;
+mod registry;
+
+pub use crate::backend::{Backend, HostError};
+pub use crate::host::{Host, NAMESPACE};
+pub use crate::registry::Registry;
+pub use extism;
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::HostError;
+
+/// Every pointer the host has handed to the plugin, with the `@type` it was
+/// handed out as.
+///
+/// Outgoing values are [`register`](Self::register)ed and incoming values are
+/// [`check`](Self::check)ed, so a plugin can only refer to objects it was
+/// given, and only as the type it was given them as. Collections are lowered
+/// without an `@type` and are only checked for existence. A pointer keeps the
+/// first `@type` it was handed out with, even if it's later handed out
+/// without one.
+#[derive(Debug, Default, Clone)]
+pub struct Registry {
+    types: HashMap<i64, Option<String>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record every `{"@ptr": ..}` in `value`.
+    pub fn register(&mut self, value: &Value) {
+        match value {
+            Value::Array(xs) => xs.iter().for_each(|xs| self.register(xs)),
+            Value::Object(map) => {
+                if let Some(ptr) = map.get("@ptr").and_then(Value::as_i64) {
+                    self.register_ptr(ptr, map.get("@type").and_then(Value::as_str));
+                    return;
+                }
+                map.values().for_each(|xs| self.register(xs));
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a bare pointer, as used by the `bpy.data` payload.
+    pub fn register_ptr(&mut self, ptr: i64, ty: Option<&str>) {
+        let known = self.types.entry(ptr).or_default();
+        if known.is_none() {
+            *known = ty.map(|xs| xs.to_string());
+        }
+    }
+
+    pub fn contains(&self, ptr: i64) -> bool {
+        self.types.contains_key(&ptr)
+    }
+
+    /// The `@type` `ptr` was handed out as, if it had one.
+    pub fn type_of(&self, ptr: i64) -> Option<&str> {
+        self.types.get(&ptr).and_then(|xs| xs.as_deref())
+    }
+
+    /// Validate every `{"@ptr": ..}` in `value`.
+    pub fn check(&self, value: &Value) -> Result<(), HostError> {
+        match value {
+            Value::Array(xs) => xs.iter().try_for_each(|xs| self.check(xs)),
+            Value::Object(map) => match map.get("@ptr") {
+                Some(ptr) => {
+                    let ptr = ptr
+                        .as_i64()
+                        .ok_or_else(|| HostError::BadRequest(format!("{} is not a pointer", ptr)))?;
+                    self.check_ptr(ptr, map.get("@type").and_then(Value::as_str))
+                }
+                None => map.values().try_for_each(|xs| self.check(xs)),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn check_ptr(&self, ptr: i64, ty: Option<&str>) -> Result<(), HostError> {
+        let expected = self.types.get(&ptr).ok_or(HostError::UnknownPtr(ptr))?;
+        match (expected, ty) {
+            (Some(expected), Some(actual)) if expected != actual => Err(HostError::TypeMismatch {
+                ptr,
+                expected: expected.clone(),
+                actual: actual.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn pointers_keep_their_first_type() {
+        let mut registry = Registry::new();
+        registry.register(&json!({ "@ptr": 1 }));
+        assert_eq!(registry.type_of(1), None);
+
+        registry.register(&json!([{ "@ptr": 1, "@type": "Object" }, { "@ptr": 2, "@type": "Mesh" }]));
+        registry.register(&json!({ "items": [{ "@ptr": 1 }, { "@ptr": 2, "@type": "ID" }] }));
+        assert_eq!(registry.type_of(1), Some("Object"));
+        assert_eq!(registry.type_of(2), Some("Mesh"));
+        assert!(registry.check(&json!({ "@ptr": 2, "@type": "Mesh" })).is_ok());
+        assert!(registry.check(&json!({ "@ptr": 2, "@type": "ID" })).is_err());
+    }
+}
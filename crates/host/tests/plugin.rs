@@ -0,0 +1,152 @@
+use blextism_host::extism::{Manifest, Wasm};
+use blextism_host::{Backend, Host, HostError, NAMESPACE};
+use serde_json::{json, Map, Value};
+
+/// `bpy.data.objects` holds one object, the active one, looked up by name.
+#[derive(Default)]
+struct Objects {
+    name: String,
+}
+
+impl Backend for Objects {
+    fn bpy_data(&mut self) -> Result<Map<String, Value>, HostError> {
+        Ok(Map::from_iter([("objects".to_string(), json!({ "@ptr": 1 }))]))
+    }
+
+    fn getattr(&mut self, target: i64, attr: &str) -> Result<Value, HostError> {
+        match (target, attr) {
+            (1, "active") => Ok(json!({ "@ptr": 2, "@type": "Object" })),
+            (2, "name") => Ok(Value::from(self.name.as_str())),
+            _ => Err(HostError::Backend(format!("no attribute {}", attr))),
+        }
+    }
+
+    fn setattr(&mut self, target: i64, attr: &str, value: Value) -> Result<(), HostError> {
+        match (target, attr, value) {
+            (2, "name", Value::String(name)) => {
+                self.name = name;
+                Ok(())
+            }
+            _ => Err(HostError::Backend(format!("can't set {}", attr))),
+        }
+    }
+
+    fn callmethod(
+        &mut self,
+        target: i64,
+        method: &str,
+        args: &mut [Value],
+        _kwargs: Map<String, Value>,
+    ) -> Result<Value, HostError> {
+        match (target, method, &*args) {
+            (1, "get", [name]) if *name == self.name => Ok(json!({ "@ptr": 2, "@type": "Object" })),
+            (1, "get", [_]) => Ok(Value::Null),
+            _ => Err(HostError::Backend(format!("no method {}", method))),
+        }
+    }
+
+    fn operator(
+        &mut self,
+        opmod: &str,
+        operator: &str,
+        _args: Vec<Value>,
+        _kwargs: Map<String, Value>,
+    ) -> Result<Value, HostError> {
+        Err(HostError::Backend(format!("no operator {}.{}", opmod, operator)))
+    }
+}
+
+/// A host function call: the function, the attribute or method, and the
+/// request.
+type Call<'a> = (&'a str, &'a str, Value);
+
+/// A plugin that makes the calls the pdk would, written against the extism
+/// kernel directly. Each export makes the calls in its body in order and
+/// outputs the last one's result.
+fn plugin(exports: &[(&str, &[Call])]) -> String {
+    let mut data = Vec::new();
+    let mut offset = 0;
+    let mut string = |text: &str| {
+        data.push(format!("(data (i32.const {}) \"{}\")", offset, text.replace('"', "\\\"")));
+        let call = format!("(call $str (i32.const {}) (i32.const {}))", offset, text.len());
+        offset += text.len();
+        call
+    };
+
+    let mut funcs = Vec::new();
+    for (export, calls) in exports {
+        let mut body = vec!["(local $out i64)".to_string()];
+        for (kind, name, request) in *calls {
+            let (name, request) = (string(name), string(&request.to_string()));
+            body.push(match *kind {
+                "setattr" => format!("(call $bpy_setattr {} {})", name, request),
+                _ => format!("(local.set $out (call $bpy_{} {} {}))", kind, name, request),
+            });
+        }
+        body.push("(call $output_set (local.get $out) (call $length (local.get $out)))".to_string());
+        funcs.push(format!("(func (export \"{}\") (result i32) {} (i32.const 0))", export, body.join(" ")));
+    }
+
+    format!(
+        r#"(module
+            (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+            (import "extism:host/env" "length" (func $length (param i64) (result i64)))
+            (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+            (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+            (import "{ns}" "bpy_getattr" (func $bpy_getattr (param i64 i64) (result i64)))
+            (import "{ns}" "bpy_setattr" (func $bpy_setattr (param i64 i64)))
+            (import "{ns}" "bpy_callmethod" (func $bpy_callmethod (param i64 i64) (result i64)))
+            (memory 1)
+            {data}
+            ;; Copy `len` bytes at `addr` into a new block of extism memory.
+            (func $str (param $addr i32) (param $len i32) (result i64)
+                (local $block i64) (local $i i32)
+                (local.set $block (call $alloc (i64.extend_i32_u (local.get $len))))
+                (block $done (loop $next
+                    (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                    (call $store_u8
+                        (i64.add (local.get $block) (i64.extend_i32_u (local.get $i)))
+                        (i32.load8_u (i32.add (local.get $addr) (local.get $i))))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br $next)))
+                (local.get $block))
+            {funcs}
+        )"#,
+        ns = NAMESPACE,
+        data = data.join("\n"),
+        funcs = funcs.join("\n"),
+    )
+}
+
+#[test]
+fn plugins_round_trip_through_the_host_functions() {
+    let objects = json!({ "@ptr": 1 });
+    let active = json!({ "@ptr": 2, "@type": "Object" });
+    let wasm = plugin(&[
+        (
+            "rename",
+            &[
+                ("getattr", "active", json!({ "self": objects })),
+                ("setattr", "name", json!({ "self": active, "args": ["Suzanne"] })),
+                ("callmethod", "get", json!({ "self": objects, "args": ["Suzanne"], "kwargs": {} })),
+            ],
+        ),
+        ("name", &[("getattr", "name", json!({ "self": active }))]),
+        ("stray", &[("getattr", "name", json!({ "self": { "@ptr": 3 } }))]),
+    ]);
+
+    let host = Host::new(Objects::default());
+    let mut plugin = host.plugin(Manifest::new([Wasm::data(wasm)])).unwrap();
+
+    // The object is handed out by the getattr before it's renamed through it.
+    let found: String = plugin.call("rename", "").unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&found).unwrap(), active);
+    assert_eq!(host.with_backend(|objects| objects.name.clone()), "Suzanne");
+    assert_eq!(host.registry().type_of(2), Some("Object"));
+
+    let name: String = plugin.call("name", "").unwrap();
+    assert_eq!(name, "\"Suzanne\"");
+
+    let stray = plugin.call::<&str, &str>("stray", "").unwrap_err();
+    assert!(format!("{:?}", stray).contains("unknown pointer 3"), "{:?}", stray);
+}
@@ -0,0 +1,113 @@
+//! With the `generate` feature, the `bpy` bindings are generated into
+//! `OUT_DIR` rather than taken from `src/bindings.rs`. The schema is
+//! `$BLEXTISM_SCHEMA`, or `schema.json` at the root of the workspace, with the
+//! workspace's catalog and overlays applied.
+//!
+//! To bind several Blender versions at once, list their schemas in
+//! `$BLEXTISM_SCHEMAS` instead (`3.6=schema-3.6.json,4.0=schema-4.0.json`)
+//! and name the version to build for in `$BLEXTISM_BLENDER` (`4.0`), which
+//! sets the `blextism_blender` cfg the merged bindings are gated on.
+//!
+//! The `ops-*` and `types-*` features choose which parts of `bpy` to bind:
+//! each `ops-<module>` adds the `bpy.ops` module of that name, and each
+//! `types-*` adds the classes in [`TYPE_GROUPS`]. They turn on `generate`, as
+//! `src/bindings.rs` can't be cut down; turning off `ops-all` or `types-all`
+//! without any of them is warned about, since it would change nothing.
+
+/// The classes each `types-*` feature binds, as bindgen patterns.
+#[cfg(feature = "generate")]
+const TYPE_GROUPS: &[(&str, &[&str])] = &[
+    ("MESH", &["Mesh*"]),
+    ("NODES", &["*Node*"]),
+    ("OBJECT", &["Object*", "*Modifier", "*Constraint"]),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "generate")]
+    generate();
+    #[cfg(not(feature = "generate"))]
+    if !enabled("OPS_ALL") || !enabled("TYPES_ALL") {
+        println!(
+            "cargo:warning=`ops-all` or `types-all` is off, but without `generate` (or an `ops-*` or `types-*` \
+             feature) all of `bpy` is bound from src/bindings.rs"
+        );
+    }
+}
+
+/// Whether the cargo feature `feature` (uppercased, `-` as `_`) is enabled.
+fn enabled(feature: &str) -> bool {
+    std::env::var_os(format!("CARGO_FEATURE_{}", feature)).is_some()
+}
+
+#[cfg(feature = "generate")]
+fn generate() {
+    use std::path::{Path, PathBuf};
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    println!("cargo:rerun-if-env-changed=BLEXTISM_SCHEMA");
+    println!("cargo:rerun-if-env-changed=BLEXTISM_SCHEMAS");
+    println!("cargo:rerun-if-env-changed=BLEXTISM_BLENDER");
+
+    let mut builder = blextism_bindgen::Builder::new()
+        .catalog(root.join("catalog.toml"))
+        .overlay(root.join("overlays/fixes.toml"));
+    match std::env::var("BLEXTISM_SCHEMAS") {
+        Ok(schemas) => {
+            let mut versions = Vec::new();
+            for tagged in schemas.split(',') {
+                let (version, path) = tagged
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("BLEXTISM_SCHEMAS: expected `<version>=<path>`, not {:?}", tagged));
+                builder = builder.versioned_schema(version, path);
+                versions.push(format!("{:?}", version));
+            }
+            let version = std::env::var("BLEXTISM_BLENDER").unwrap_or_default();
+            if !versions.contains(&format!("{:?}", version)) {
+                panic!("set BLEXTISM_BLENDER to the Blender version to build for: one of {}", versions.join(", "));
+            }
+            println!("cargo:rustc-check-cfg=cfg(blextism_blender, values({}))", versions.join(", "));
+            println!("cargo:rustc-cfg=blextism_blender={:?}", version);
+        }
+        Err(_) => {
+            let schema = std::env::var_os("BLEXTISM_SCHEMA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| root.join("schema.json"));
+            builder = builder.schema(schema);
+        }
+    }
+
+    if !enabled("OPS_ALL") {
+        let modules: Vec<String> = std::env::vars()
+            .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_OPS_").map(|xs| xs.to_lowercase()))
+            .collect();
+        if modules.is_empty() {
+            builder = builder.deny_operators("*");
+        }
+        for module in modules {
+            builder = builder.allow_operators(module);
+        }
+    }
+    if !enabled("TYPES_ALL") {
+        // `bpy_struct` is always bound, so with no group enabled this binds
+        // just the base classes, `Context` and `BlendData`.
+        builder = builder.allow_class("bpy_struct");
+        for (group, patterns) in TYPE_GROUPS {
+            if enabled(&format!("TYPES_{}", group)) {
+                for pattern in *patterns {
+                    builder = builder.allow_class(*pattern);
+                }
+            }
+        }
+    }
+
+    let bindings = builder
+        .generate()
+        .unwrap_or_else(|err| panic!("failed to generate bindings: {}", err));
+    bindings.emit_rerun_if_changed();
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    bindings
+        .write_to_file(out_dir.join("bindings.rs"))
+        .expect("failed to write bindings");
+}
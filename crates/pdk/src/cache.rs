@@ -0,0 +1,109 @@
+//! Opt-in read-through cache for pointer, collection and read-only
+//! properties.
+//!
+//! Deep access chains such as `world.node_tree().and_then(|xs| xs.nodes())`
+//! fetch the same intermediate pointers again on every call, and loops read
+//! the same read-only values (`users`, `name_full`) over and over. With the
+//! cache enabled, the generated getters for pointer, collection and read-only
+//! properties are answered from a per-thread table keyed by
+//! `(pointer, attribute)`.
+//!
+//! The cache can't see changes made behind the plugin's back, so it is off by
+//! default. Every setattr and operator clears it, as does every method call
+//! other than the read-only collection lookups (`get`, `keys`, `values`,
+//! `items`, `find`). Use [`scope`] around code that knows nothing else is
+//! mutating the scene while it runs:
+//!
+//! ```no_run
+//! use blextism::{bpy, cache};
+//!
+//! cache::scope(|| {
+//!     for obj in bpy::data::objects().values() {
+//!         let _ = obj.modifiers();
+//!     }
+//! });
+//! ```
+use std::{cell::RefCell, collections::HashMap};
+
+use serde_json::Value;
+
+/// Method calls that only read, and so leave the cache intact.
+const READ_ONLY_METHODS: &[&str] = &["get", "keys", "values", "items", "find", "__repr__"];
+
+thread_local! {
+    static CACHE: RefCell<Option<HashMap<(i64, String), Value>>> = const { RefCell::new(None) };
+}
+
+/// Start caching on this thread.
+pub fn enable() {
+    CACHE.with(|cache| {
+        cache.borrow_mut().get_or_insert_with(HashMap::new);
+    });
+}
+
+/// Stop caching on this thread and forget everything cached so far.
+pub fn disable() {
+    CACHE.with(|cache| cache.borrow_mut().take());
+}
+
+pub fn is_enabled() -> bool {
+    CACHE.with(|cache| cache.borrow().is_some())
+}
+
+/// Forget everything cached so far, leaving the cache enabled if it was.
+pub fn clear() {
+    CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.clear();
+        }
+    });
+}
+
+/// Run `f` with the cache enabled. The cache is cleared when `f` returns, and
+/// disabled again unless it was enabled before.
+pub fn scope<R>(f: impl FnOnce() -> R) -> R {
+    struct Guard {
+        was_enabled: bool,
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            if self.was_enabled {
+                clear();
+            } else {
+                disable();
+            }
+        }
+    }
+
+    let _guard = Guard {
+        was_enabled: is_enabled(),
+    };
+    enable();
+    f()
+}
+
+/// Answer `ptr.attr` from the cache, or `fetch` it and remember the result.
+pub(crate) fn get_or_fetch(ptr: i64, attr: &str, fetch: impl FnOnce() -> Value) -> Value {
+    let key = (ptr, attr.to_string());
+    let cached = CACHE.with(|cache| cache.borrow().as_ref().map(|xs| xs.get(&key).cloned()));
+    let value = match cached {
+        None => return fetch(),
+        Some(Some(value)) => return value,
+        Some(None) => fetch(),
+    };
+
+    CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.insert(key, value.clone());
+        }
+    });
+    value
+}
+
+/// Clear the cache unless `method` is known not to mutate anything.
+pub(crate) fn invalidate_for_method(method: &str) {
+    if !READ_ONLY_METHODS.contains(&method) {
+        clear();
+    }
+}
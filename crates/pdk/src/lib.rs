@@ -1,16 +1,89 @@
+#[cfg(not(feature = "generate"))]
 mod bindings;
+#[cfg(feature = "generate")]
+mod bindings {
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+}
+pub mod cache;
+pub mod profile;
+mod trace;
+mod transport;
 
 use std::{collections::HashMap, str::FromStr};
 
-use extism_pdk::*;
-use serde::{ Deserialize, Serialize };
+use serde::{ de::DeserializeOwned, Deserialize, Serialize };
 use smartstring::alias::String;
 pub use crate::bindings::bpy;
+use crate::profile::CallKind;
+#[cfg(target_arch = "wasm32")]
+pub use crate::transport::ExtismTransport;
+pub use crate::trace::{ read_trace, write_trace, RecordingTransport, ReplayTransport, TraceEvent };
+pub use crate::transport::{ clear_transport, set_transport, Transport };
+pub(crate) use crate::transport::bpy_data_ptr;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct BpyPtr {
     #[serde(rename = "@ptr")]
     ptr: i64,
+    /// The RNA type the host handed this pointer out as. It is sent back with
+    /// every call so the host can check it.
+    #[serde(rename = "@type", default, skip_serializing_if = "Option::is_none")]
+    ty: Option<String>,
+}
+
+impl BpyPtr {
+    pub(crate) fn from_raw(ptr: i64) -> Self {
+        Self { ptr, ty: None }
+    }
+
+    /// The host-side pointer value.
+    pub fn as_raw(&self) -> i64 {
+        self.ptr
+    }
+
+    /// The RNA type identifier the host reported for this pointer, if any.
+    /// Collections are reported without one.
+    pub fn rna_type(&self) -> Option<&str> {
+        self.ty.as_deref()
+    }
+
+    /// Read `self.<name>` by name, for attributes the generated bindings
+    /// don't cover (Python-defined properties, newer Blender versions).
+    ///
+    /// Pointers come back as `BpyPtr`; cast them to a generated trait with
+    /// `Box::new(ptr) as Box<dyn bpy::types::Object>`.
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<T, serde_json::Error> {
+        serde_json::from_value(invoke_bpy_getattr(name, PyArgs::new(self)))
+    }
+
+    /// Write `self.<name> = value` by name.
+    pub fn set(&self, name: &str, value: impl Serialize) {
+        invoke_bpy_setattr(name, PyArgs::arg1(self, value))
+    }
+
+    /// Call `self.<name>(*args, **kwargs)` by name. `args` is a tuple, array
+    /// or `Vec` of positional arguments, or `()` for none.
+    pub fn call<R: DeserializeOwned>(
+        &self,
+        name: &str,
+        args: impl Serialize,
+        kwargs: impl Into<Kwargs>,
+    ) -> Result<R, serde_json::Error> {
+        let args = match serde_json::to_value(args)? {
+            serde_json::Value::Null => Vec::new(),
+            serde_json::Value::Array(xs) => xs,
+            xs => vec![xs],
+        };
+        let kwargs = Some(kwargs.into()).filter(|xs| !xs.0.is_empty());
+        serde_json::from_value(invoke_bpy_callmethod(name, PyArgs::argv(Some(self), args, kwargs)))
+    }
+
+    /// Resolve an RNA data path relative to `self`, such as
+    /// `modifiers["GeometryNodes"].show_viewport` or `location[2]`, the way
+    /// `bpy_struct.path_resolve` does.
+    pub fn path_resolve<T: DeserializeOwned>(&self, path: &str) -> Result<T, serde_json::Error> {
+        self.call("path_resolve", (path,), ())
+    }
 }
 
 impl std::fmt::Debug for BpyPtr {
@@ -28,15 +101,101 @@ impl std::fmt::Debug for BpyPtr {
 #[serde(transparent)]
 pub struct Kwargs(HashMap<String, serde_json::Value>);
 
+impl Kwargs {
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.0.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+/// A `bpy.types` class, named at the type level. The generated
+/// `bpy::types::rna` module has one of these per class; functions that
+/// receive the class they are called on (`use_self_type`) are generic over
+/// it.
+pub trait RnaClass {
+    /// The class identifier, e.g. `"Object"`.
+    const NAME: &'static str;
+
+    /// The class's RNA properties, as the schema describes them.
+    const PROPERTIES: &'static [PropertyMeta];
+
+    /// Look up one of [`Self::PROPERTIES`] by name.
+    fn property(name: &str) -> Option<&'static PropertyMeta> {
+        Self::PROPERTIES.iter().find(|xs| xs.name == name)
+    }
+}
+
+/// Flags the schema records for an RNA property, so plugins can check
+/// whether a property can be written or keyframed before trying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyMeta {
+    /// The property's name on the host, e.g. `"location"`.
+    pub name: &'static str,
+    /// No setter is generated for read-only properties.
+    pub is_readonly: bool,
+    pub is_animatable: bool,
+    pub is_overridable: bool,
+}
+
 #[derive(Serialize, Deserialize, Default)]
 pub struct PyArgs {
     #[serde(rename = "self")]
     target: Option<BpyPtr>,
+    /// For class-level functions, the `bpy.types` class to call them on in
+    /// place of `self`.
+    #[serde(rename = "@class", default, skip_serializing_if = "Option::is_none")]
+    class: Option<String>,
     args: Option<Vec<serde_json::Value>>,
     kwargs: Option<Kwargs>,
+    /// The stable id bindgen assigned to the `(class, member)` being accessed;
+    /// see bindgen's `--ids` table. Hosts may use it to pre-resolve the member
+    /// instead of looking it up by name.
+    #[serde(rename = "@id", default, skip_serializing_if = "Option::is_none")]
+    id: Option<u32>,
+    /// The index of an argument the method fills in place, such as
+    /// `foreach_get`'s buffer. Hosts respond with that argument as it stands
+    /// after the call instead of the method's return value.
+    #[serde(rename = "@out", default, skip_serializing_if = "Option::is_none")]
+    out: Option<u32>,
 }
 
 impl PyArgs {
+    /// The `self` this call targets; `None` for operators and class-level
+    /// functions.
+    pub fn target(&self) -> Option<&BpyPtr> {
+        self.target.as_ref()
+    }
+
+    /// The `bpy.types` class a class-level function is called on.
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    pub fn args(&self) -> &[serde_json::Value] {
+        self.args.as_deref().unwrap_or_default()
+    }
+
+    pub fn kwargs(&self) -> Option<&Kwargs> {
+        self.kwargs.as_ref()
+    }
+
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+
+    fn with_id(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    fn with_out(mut self, idx: u32) -> Self {
+        self.out = Some(idx);
+        self
+    }
+
     fn new(target: &BpyPtr) -> Self {
         Self {
             target: Some(target.clone()),
@@ -53,11 +212,21 @@ impl PyArgs {
         }
     }
 
+    fn class_argv(class: &str, args: Vec<serde_json::Value>, kwargs: Option<Kwargs>) -> Self {
+        Self {
+            class: Some(class.into()),
+            args: Some(args),
+            kwargs,
+            ..Default::default()
+        }
+    }
+
     fn argv(target: Option<&BpyPtr>, args: Vec<serde_json::Value>, kwargs: Option<Kwargs>) -> Self {
         Self {
             target: target.cloned(),
             args: Some(args),
-            kwargs
+            kwargs,
+            ..Default::default()
         }
     }
 }
@@ -119,35 +288,41 @@ impl<S: Into<String>, T: Serialize> From<HashMap<S, T>> for Kwargs {
     }
 }
 
-#[host_fn("chrisdickinson:blender/bpy")]
-extern "ExtismHost" {
-    fn bpy_setattr(method: &str, args: Json<PyArgs>);
-    fn bpy_getattr(method: &str, args: Json<PyArgs>) -> Json<serde_json::Value>;
-    fn bpy_callmethod(method: &str, args: Json<PyArgs>) -> Json<serde_json::Value>;
-    fn bpy_operator(opmod: &str, method: &str, args: Json<PyArgs>) -> Json<serde_json::Value>;
+pub(crate) fn invoke_bpy_getattr(method: &str, args: PyArgs) -> serde_json::Value {
+    profile::record(CallKind::GetAttr, None, method, args, |args| {
+        transport::current().getattr(method, args)
+    })
 }
 
-pub(crate) fn invoke_bpy_getattr(method: &str, args: PyArgs) -> serde_json::Value {
-    unsafe {
-        let result = bpy_getattr(method, Json(args)).unwrap().into_inner();
-        result
-    }
+/// A getattr that may be answered from the [`cache`]; used for pointer,
+/// collection and read-only properties.
+pub(crate) fn invoke_bpy_getattr_cached(method: &str, args: PyArgs) -> serde_json::Value {
+    let Some(ptr) = args.target().map(BpyPtr::as_raw) else {
+        return invoke_bpy_getattr(method, args);
+    };
+    cache::get_or_fetch(ptr, method, || invoke_bpy_getattr(method, args))
 }
 
 pub(crate) fn invoke_bpy_setattr(method: &str, args: PyArgs) {
-    unsafe { bpy_setattr(method, Json(args)).unwrap() };
+    cache::clear();
+    profile::record(CallKind::SetAttr, None, method, args, |args| {
+        transport::current().setattr(method, args);
+        serde_json::Value::Null
+    });
 }
 
 pub(crate) fn invoke_bpy_callmethod(method: &str, args: PyArgs) -> serde_json::Value {
-    unsafe {
-        let result = bpy_callmethod(method, Json(args)).unwrap().into_inner();
-        result
-    }
+    cache::invalidate_for_method(method);
+    profile::record(CallKind::CallMethod, None, method, args, |args| {
+        transport::current().callmethod(method, args)
+    })
 }
 
+// Unused when the bindings were generated without `bpy.ops`.
+#[allow(dead_code)]
 pub(crate) fn invoke_bpy_operator(opmod: &str, operator: &str, args: PyArgs) -> serde_json::Value {
-    unsafe {
-        let result = bpy_operator(opmod, operator, Json(args)).unwrap().into_inner();
-        result
-    }
+    cache::clear();
+    profile::record(CallKind::Operator, Some(opmod), operator, args, |args| {
+        transport::current().operator(opmod, operator, args)
+    })
 }
@@ -0,0 +1,292 @@
+//! Opt-in instrumentation of host calls.
+//!
+//! While profiling is [`enable`]d on a thread, every getattr, setattr, method
+//! call and operator the bindings make is timed and measured. The result is a
+//! [`Profile`], which can be rendered as a per-`(RNA type, attribute)` summary
+//! or as a Chrome trace (load it in `chrome://tracing` or Perfetto).
+//!
+//! ```no_run
+//! use blextism::{bpy, profile};
+//!
+//! profile::enable();
+//! let scene = bpy::context().scene();
+//! let report = profile::finish().unwrap().summary();
+//! ```
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use crate::PyArgs;
+
+/// The extism config key `run.py` sets when `BLEXTISM_PROFILE` is set.
+pub const CONFIG_KEY: &str = "blextism.profile";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    GetAttr,
+    SetAttr,
+    CallMethod,
+    Operator,
+}
+
+impl CallKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallKind::GetAttr => "getattr",
+            CallKind::SetAttr => "setattr",
+            CallKind::CallMethod => "callmethod",
+            CallKind::Operator => "operator",
+        }
+    }
+}
+
+/// One host call.
+#[derive(Debug, Clone)]
+pub struct CallRecord {
+    pub kind: CallKind,
+    /// The `@type` of `self`, the class of a class-level function,
+    /// `bpy.ops.<module>` for operators, or `"<untyped>"` for pointers the
+    /// host sent without a type.
+    pub rna_type: String,
+    pub name: String,
+    pub bytes_sent: usize,
+    /// Zero for setattrs, which get no response.
+    pub bytes_received: usize,
+    /// When the call started, relative to [`enable`].
+    pub start: Duration,
+    /// Time spent waiting on the host.
+    pub duration: Duration,
+}
+
+/// Calls aggregated per `(kind, RNA type, name)`.
+#[derive(Debug, Clone)]
+pub struct CallStats {
+    pub kind: CallKind,
+    pub rna_type: String,
+    pub name: String,
+    pub calls: usize,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+    pub duration: Duration,
+}
+
+/// How to render a [`Profile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Summary,
+    ChromeTrace,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "summary" => Ok(Format::Summary),
+            "chrome" => Ok(Format::ChromeTrace),
+            _ => Err(format!("unknown profile format {:?}; expected \"summary\" or \"chrome\"", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub calls: Vec<CallRecord>,
+}
+
+impl Profile {
+    /// Aggregate the calls, most frequent first.
+    pub fn stats(&self) -> Vec<CallStats> {
+        let mut stats: HashMap<(CallKind, &str, &str), CallStats> = HashMap::new();
+        for call in &self.calls {
+            let entry = stats
+                .entry((call.kind, call.rna_type.as_str(), call.name.as_str()))
+                .or_insert_with(|| CallStats {
+                    kind: call.kind,
+                    rna_type: call.rna_type.clone(),
+                    name: call.name.clone(),
+                    calls: 0,
+                    bytes_sent: 0,
+                    bytes_received: 0,
+                    duration: Duration::ZERO,
+                });
+            entry.calls += 1;
+            entry.bytes_sent += call.bytes_sent;
+            entry.bytes_received += call.bytes_received;
+            entry.duration += call.duration;
+        }
+
+        let mut stats: Vec<_> = stats.into_values().collect();
+        stats.sort_by(|lhs, rhs| {
+            rhs.calls
+                .cmp(&lhs.calls)
+                .then(rhs.duration.cmp(&lhs.duration))
+                .then_with(|| (&lhs.rna_type, &lhs.name).cmp(&(&rhs.rna_type, &rhs.name)))
+        });
+        stats
+    }
+
+    /// A plain-text table of [`stats`](Self::stats).
+    pub fn summary(&self) -> String {
+        let total: Duration = self.calls.iter().map(|xs| xs.duration).sum();
+        let sent: usize = self.calls.iter().map(|xs| xs.bytes_sent).sum();
+        let received: usize = self.calls.iter().map(|xs| xs.bytes_received).sum();
+
+        let mut out = format!(
+            "blextism profile: {} host calls, {:.1?} waiting on the host, {} sent, {} received\n\n",
+            self.calls.len(),
+            total,
+            bytes(sent),
+            bytes(received)
+        );
+        out.push_str(&format!(
+            "{:>8} {:>12} {:>10} {:>10}  call\n",
+            "calls", "time", "sent", "received"
+        ));
+        for stat in self.stats() {
+            out.push_str(&format!(
+                "{:>8} {:>12} {:>10} {:>10}  {} {}.{}\n",
+                stat.calls,
+                format!("{:.1?}", stat.duration),
+                bytes(stat.bytes_sent),
+                bytes(stat.bytes_received),
+                stat.kind.as_str(),
+                stat.rna_type,
+                stat.name
+            ));
+        }
+        out
+    }
+
+    /// The calls in Chrome's trace event format.
+    pub fn chrome_trace(&self) -> Value {
+        let events: Vec<_> = self
+            .calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "name": format!("{}.{}", call.rna_type, call.name),
+                    "cat": call.kind.as_str(),
+                    "ph": "X",
+                    "ts": call.start.as_secs_f64() * 1e6,
+                    "dur": call.duration.as_secs_f64() * 1e6,
+                    "pid": 0,
+                    "tid": 0,
+                    "args": {
+                        "bytes_sent": call.bytes_sent,
+                        "bytes_received": call.bytes_received,
+                    }
+                })
+            })
+            .collect();
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Summary => self.summary(),
+            Format::ChromeTrace => self.chrome_trace().to_string(),
+        }
+    }
+}
+
+fn bytes(n: usize) -> String {
+    if n < 1024 {
+        format!("{}B", n)
+    } else {
+        format!("{:.1}KiB", n as f64 / 1024.)
+    }
+}
+
+struct Recorder {
+    epoch: Instant,
+    calls: Vec<CallRecord>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Start profiling host calls on this thread, discarding any calls recorded
+/// so far.
+pub fn enable() {
+    RECORDER.with(|slot| {
+        *slot.borrow_mut() = Some(Recorder {
+            epoch: Instant::now(),
+            calls: Vec::new(),
+        })
+    });
+}
+
+/// Enable profiling if the host set the [`CONFIG_KEY`] config, returning the
+/// format it asked for.
+pub fn enable_from_config() -> Option<Format> {
+    let format = crate::transport::current().config(CONFIG_KEY)?;
+    let format = format
+        .parse()
+        .unwrap_or_else(|err| panic!("{}: {}", CONFIG_KEY, err));
+    enable();
+    Some(format)
+}
+
+pub fn is_enabled() -> bool {
+    RECORDER.with(|slot| slot.borrow().is_some())
+}
+
+/// Stop profiling and return what was recorded, or `None` if profiling was
+/// not enabled.
+pub fn finish() -> Option<Profile> {
+    RECORDER.with(|slot| slot.borrow_mut().take()).map(|recorder| Profile {
+        calls: recorder.calls,
+    })
+}
+
+/// Run `call` against the host, recording it if profiling is enabled.
+pub(crate) fn record(
+    kind: CallKind,
+    opmod: Option<&str>,
+    name: &str,
+    args: PyArgs,
+    call: impl FnOnce(PyArgs) -> Value,
+) -> Value {
+    let Some(epoch) = RECORDER.with(|slot| slot.borrow().as_ref().map(|xs| xs.epoch)) else {
+        return call(args);
+    };
+
+    let rna_type = match (opmod, args.target()) {
+        (Some(opmod), _) => format!("bpy.ops.{}", opmod),
+        (None, Some(target)) => target.rna_type().unwrap_or("<untyped>").to_string(),
+        (None, None) => args.class().unwrap_or("<untyped>").to_string(),
+    };
+    let bytes_sent = serde_json::to_vec(&args).map(|xs| xs.len()).unwrap_or_default();
+
+    let start = Instant::now();
+    let result = call(args);
+    let duration = start.elapsed();
+    // `bpy_setattr` has no response; the `null` here is the pdk's own.
+    let bytes_received = match kind {
+        CallKind::SetAttr => 0,
+        _ => result.to_string().len(),
+    };
+
+    let record = CallRecord {
+        kind,
+        rna_type,
+        name: name.to_string(),
+        bytes_sent,
+        bytes_received,
+        start: start.duration_since(epoch),
+        duration,
+    };
+    RECORDER.with(|slot| {
+        if let Some(recorder) = slot.borrow_mut().as_mut() {
+            recorder.calls.push(record);
+        }
+    });
+    result
+}
@@ -0,0 +1,311 @@
+use std::{
+    cell::{Cell, RefCell},
+    io::{BufRead, Write},
+    path::Path,
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{PyArgs, Transport};
+
+/// One host call, as written to a trace file.
+///
+/// Traces are JSON lines, one event per line. `args` is the request exactly as
+/// the pdk sends it (`{"self": .., "args": .., "kwargs": ..}`) and `result` is
+/// the host's response. `run.py` writes the same format when `BLEXTISM_TRACE`
+/// is set, so a session recorded inside Blender can be replayed with
+/// [`ReplayTransport`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "call", rename_all = "lowercase")]
+pub enum TraceEvent {
+    Config {
+        key: String,
+        value: Option<String>,
+    },
+    GetAttr {
+        attr: String,
+        args: Value,
+        result: Value,
+    },
+    SetAttr {
+        attr: String,
+        args: Value,
+    },
+    CallMethod {
+        method: String,
+        args: Value,
+        result: Value,
+    },
+    Operator {
+        opmod: String,
+        operator: String,
+        args: Value,
+        result: Value,
+    },
+}
+
+impl TraceEvent {
+    /// The event with its response removed, for comparing requests.
+    fn request(&self) -> Self {
+        let mut request = self.clone();
+        match &mut request {
+            TraceEvent::GetAttr { result, .. }
+            | TraceEvent::CallMethod { result, .. }
+            | TraceEvent::Operator { result, .. } => *result = Value::Null,
+            TraceEvent::Config { value, .. } => *value = None,
+            TraceEvent::SetAttr { .. } => {}
+        }
+        request
+    }
+
+    fn result(&self) -> Value {
+        match self {
+            TraceEvent::GetAttr { result, .. }
+            | TraceEvent::CallMethod { result, .. }
+            | TraceEvent::Operator { result, .. } => result.clone(),
+            TraceEvent::Config { .. } | TraceEvent::SetAttr { .. } => Value::Null,
+        }
+    }
+}
+
+/// Read a JSON-lines trace. Blank lines are skipped.
+pub fn read_trace(reader: impl BufRead) -> std::io::Result<Vec<TraceEvent>> {
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line)?);
+    }
+    Ok(events)
+}
+
+/// Write `events` as a JSON-lines trace.
+pub fn write_trace<'a>(
+    mut writer: impl Write,
+    events: impl IntoIterator<Item = &'a TraceEvent>,
+) -> std::io::Result<()> {
+    for event in events {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+fn to_value(args: &PyArgs) -> Value {
+    serde_json::to_value(args).expect("pyargs must be serializable")
+}
+
+/// A [`Transport`] that forwards to another transport and records every
+/// request and response.
+///
+/// Like the test harnesses, this is a cheap handle: install a clone and keep
+/// one to [`save`](Self::save) the trace afterwards.
+pub struct RecordingTransport<T> {
+    inner: Rc<T>,
+    events: Rc<RefCell<Vec<TraceEvent>>>,
+}
+
+impl<T> Clone for RecordingTransport<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner: Rc::new(inner),
+            events: Default::default(),
+        }
+    }
+
+    /// Every event recorded so far.
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.events.borrow().clone()
+    }
+
+    pub fn write_to(&self, writer: impl Write) -> std::io::Result<()> {
+        write_trace(writer, self.events.borrow().iter())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.write_to(std::io::BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    fn record(&self, event: TraceEvent) {
+        self.events.borrow_mut().push(event);
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn getattr(&self, attr: &str, args: PyArgs) -> Value {
+        let request = to_value(&args);
+        let result = self.inner.getattr(attr, args);
+        self.record(TraceEvent::GetAttr {
+            attr: attr.to_string(),
+            args: request,
+            result: result.clone(),
+        });
+        result
+    }
+
+    fn setattr(&self, attr: &str, args: PyArgs) {
+        let request = to_value(&args);
+        self.inner.setattr(attr, args);
+        self.record(TraceEvent::SetAttr {
+            attr: attr.to_string(),
+            args: request,
+        });
+    }
+
+    fn callmethod(&self, method: &str, args: PyArgs) -> Value {
+        let request = to_value(&args);
+        let result = self.inner.callmethod(method, args);
+        self.record(TraceEvent::CallMethod {
+            method: method.to_string(),
+            args: request,
+            result: result.clone(),
+        });
+        result
+    }
+
+    fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> Value {
+        let request = to_value(&args);
+        let result = self.inner.operator(opmod, operator, args);
+        self.record(TraceEvent::Operator {
+            opmod: opmod.to_string(),
+            operator: operator.to_string(),
+            args: request,
+            result: result.clone(),
+        });
+        result
+    }
+
+    fn config(&self, key: &str) -> Option<String> {
+        let value = self.inner.config(key);
+        self.record(TraceEvent::Config {
+            key: key.to_string(),
+            value: value.clone(),
+        });
+        value
+    }
+}
+
+/// A [`Transport`] that answers from a recorded trace.
+///
+/// Calls must arrive in the order they were recorded, with the same requests;
+/// the first call that differs panics with both the recorded and the actual
+/// request, so a replayed session fails as a regression test. Config reads
+/// are answered from the trace's `config` events regardless of order.
+#[derive(Clone)]
+pub struct ReplayTransport {
+    events: Rc<Vec<TraceEvent>>,
+    cursor: Rc<Cell<usize>>,
+}
+
+impl ReplayTransport {
+    pub fn new(events: Vec<TraceEvent>) -> Self {
+        Self {
+            events: Rc::new(events),
+            cursor: Default::default(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(Self::new(read_trace(std::io::BufReader::new(file))?))
+    }
+
+    /// The recorded calls that have not been replayed yet.
+    pub fn remaining(&self) -> Vec<TraceEvent> {
+        self.calls().skip(self.cursor.get()).cloned().collect()
+    }
+
+    /// Panic unless every recorded call was replayed.
+    #[track_caller]
+    pub fn assert_finished(&self) {
+        let remaining = self.remaining();
+        assert!(
+            remaining.is_empty(),
+            "trace ended early: {} recorded calls were never made, starting with {:#?}",
+            remaining.len(),
+            remaining.first()
+        );
+    }
+
+    fn calls(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events
+            .iter()
+            .filter(|xs| !matches!(xs, TraceEvent::Config { .. }))
+    }
+
+    fn replay(&self, actual: TraceEvent) -> Value {
+        let idx = self.cursor.get();
+        let Some(expected) = self.calls().nth(idx) else {
+            panic!(
+                "trace diverged at call #{}: the recording ends here, but the plugin made\n{:#?}",
+                idx, actual
+            )
+        };
+
+        if expected.request() != actual {
+            panic!(
+                "trace diverged at call #{}\nrecorded: {:#?}\nactual: {:#?}",
+                idx,
+                expected.request(),
+                actual
+            );
+        }
+
+        self.cursor.set(idx + 1);
+        expected.result()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn getattr(&self, attr: &str, args: PyArgs) -> Value {
+        self.replay(TraceEvent::GetAttr {
+            attr: attr.to_string(),
+            args: to_value(&args),
+            result: Value::Null,
+        })
+    }
+
+    fn setattr(&self, attr: &str, args: PyArgs) {
+        self.replay(TraceEvent::SetAttr {
+            attr: attr.to_string(),
+            args: to_value(&args),
+        });
+    }
+
+    fn callmethod(&self, method: &str, args: PyArgs) -> Value {
+        self.replay(TraceEvent::CallMethod {
+            method: method.to_string(),
+            args: to_value(&args),
+            result: Value::Null,
+        })
+    }
+
+    fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> Value {
+        self.replay(TraceEvent::Operator {
+            opmod: opmod.to_string(),
+            operator: operator.to_string(),
+            args: to_value(&args),
+            result: Value::Null,
+        })
+    }
+
+    fn config(&self, key: &str) -> Option<String> {
+        self.events.iter().find_map(|xs| match xs {
+            TraceEvent::Config { key: k, value } if k == key => value.clone(),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use smartstring::alias::String;
+
+use crate::PyArgs;
+
+/// The channel between the generated bindings and whatever is answering on the
+/// other side of the `chrisdickinson:blender/bpy` namespace.
+///
+/// On `wasm32` the default transport is `ExtismTransport`, which calls the
+/// extism host functions. Other transports (mocks, simulators, recorders) can
+/// be installed per-thread with [`set_transport`]; this is what lets plugin
+/// logic run under a plain `cargo test` on the host.
+pub trait Transport {
+    fn getattr(&self, attr: &str, args: PyArgs) -> serde_json::Value;
+    fn setattr(&self, attr: &str, args: PyArgs);
+    fn callmethod(&self, method: &str, args: PyArgs) -> serde_json::Value;
+    fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> serde_json::Value;
+
+    /// Read an extism config value. The bindings use this to load the
+    /// `bpy.data` pointer table.
+    fn config(&self, key: &str) -> Option<std::string::String>;
+}
+
+impl<T: Transport + ?Sized> Transport for Rc<T> {
+    fn getattr(&self, attr: &str, args: PyArgs) -> serde_json::Value {
+        (**self).getattr(attr, args)
+    }
+
+    fn setattr(&self, attr: &str, args: PyArgs) {
+        (**self).setattr(attr, args)
+    }
+
+    fn callmethod(&self, method: &str, args: PyArgs) -> serde_json::Value {
+        (**self).callmethod(method, args)
+    }
+
+    fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> serde_json::Value {
+        (**self).operator(opmod, operator, args)
+    }
+
+    fn config(&self, key: &str) -> Option<std::string::String> {
+        (**self).config(key)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod host {
+    use extism_pdk::*;
+
+    use crate::PyArgs;
+
+    #[host_fn("chrisdickinson:blender/bpy")]
+    extern "ExtismHost" {
+        fn bpy_setattr(method: &str, args: Json<PyArgs>);
+        fn bpy_getattr(method: &str, args: Json<PyArgs>) -> Json<serde_json::Value>;
+        fn bpy_callmethod(method: &str, args: Json<PyArgs>) -> Json<serde_json::Value>;
+        fn bpy_operator(opmod: &str, method: &str, args: Json<PyArgs>) -> Json<serde_json::Value>;
+    }
+
+    /// The default transport: the extism host functions registered by `run.py`.
+    #[derive(Default, Clone, Copy, Debug)]
+    pub struct ExtismTransport;
+
+    impl super::Transport for ExtismTransport {
+        fn getattr(&self, attr: &str, args: PyArgs) -> serde_json::Value {
+            unsafe { bpy_getattr(attr, Json(args)).unwrap().into_inner() }
+        }
+
+        fn setattr(&self, attr: &str, args: PyArgs) {
+            unsafe { bpy_setattr(attr, Json(args)).unwrap() };
+        }
+
+        fn callmethod(&self, method: &str, args: PyArgs) -> serde_json::Value {
+            unsafe { bpy_callmethod(method, Json(args)).unwrap().into_inner() }
+        }
+
+        fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> serde_json::Value {
+            unsafe { bpy_operator(opmod, operator, Json(args)).unwrap().into_inner() }
+        }
+
+        fn config(&self, key: &str) -> Option<std::string::String> {
+            extism_pdk::config::get(key).expect("extism config must be readable")
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use host::ExtismTransport;
+
+thread_local! {
+    static TRANSPORT: RefCell<Option<Rc<dyn Transport>>> = const { RefCell::new(None) };
+    static BPY_DATA: RefCell<Option<Rc<HashMap<String, i64>>>> = const { RefCell::new(None) };
+}
+
+/// Install `transport` for the current thread, returning the previously
+/// installed transport (if any).
+///
+/// Installing a transport also forgets the cached `bpy.data` table and any
+/// [`cache`](crate::cache)d properties, so the next access reads them from the
+/// new transport.
+pub fn set_transport(transport: impl Transport + 'static) -> Option<Rc<dyn Transport>> {
+    BPY_DATA.with(|data| data.borrow_mut().take());
+    crate::cache::clear();
+    TRANSPORT.with(|slot| slot.borrow_mut().replace(Rc::new(transport)))
+}
+
+/// Remove the transport installed for the current thread. On `wasm32` this
+/// restores the `ExtismTransport` default.
+pub fn clear_transport() -> Option<Rc<dyn Transport>> {
+    BPY_DATA.with(|data| data.borrow_mut().take());
+    crate::cache::clear();
+    TRANSPORT.with(|slot| slot.borrow_mut().take())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_transport() -> Rc<dyn Transport> {
+    Rc::new(ExtismTransport)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_transport() -> Rc<dyn Transport> {
+    panic!("no blextism transport installed on this thread; call `blextism::set_transport` first")
+}
+
+/// The transport for the current thread. The `Rc` is cloned out of the slot so
+/// that transports may themselves make bpy calls without tripping the borrow.
+pub(crate) fn current() -> Rc<dyn Transport> {
+    TRANSPORT.with(|slot| {
+        slot.borrow_mut()
+            .get_or_insert_with(default_transport)
+            .clone()
+    })
+}
+
+/// Look up a pointer from the `bpy.data` config table.
+pub(crate) fn bpy_data_ptr(key: &str) -> i64 {
+    let data = BPY_DATA.with(|data| {
+        data.borrow_mut()
+            .get_or_insert_with(|| {
+                let cfg = current()
+                    .config("bpy.data")
+                    .expect("'bpy.data' extism config must be set");
+
+                Rc::new(serde_json::from_str(cfg.as_str()).expect("'bpy.data' must contain valid JSON"))
+            })
+            .clone()
+    });
+
+    *data
+        .get(key)
+        .unwrap_or_else(|| panic!("'bpy.data' is missing an entry for {:?}", key))
+}
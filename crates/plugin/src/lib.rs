@@ -3,15 +3,17 @@ use blextism::bpy::{self, types::NodeSocket};
 
 // A port of https://github.com/CGArtPython/blender_plus_python/blob/main/geo_nodes/subdivided_triangulated_cube/subdivided_triangulated_cube_part_2_done.py
 
-fn scene_setup() -> Option<()> {
+/// Clear the default scene and set up the world, render settings, light and
+/// camera the example renders with.
+pub fn scene_setup() -> Option<()> {
     if bpy::context().active_object().and_then(|obj| obj.mode())? == "EDIT" {
         bpy::ops::object::editmode_toggle(());
     }
 
     for obj in bpy::data::objects().values() {
         obj.hide_set(false, ().into());
-        obj.set_hide_select(Some(false));
-        obj.set_hide_viewport(Some(false));
+        obj.set_hide_select(false);
+        obj.set_hide_viewport(false);
     }
 
     bpy::ops::object::select_all((
@@ -28,13 +30,13 @@ fn scene_setup() -> Option<()> {
     let scene = bpy::context().scene()?;
     scene.set_world(Some(worlds.get("World")?.to_bpy_ptr()));
     let render = scene.render();
-    render.set_engine(Some("CYCLES"));
-    render.image_settings().set_file_format(Some("FFMPEG"));
-    render.ffmpeg()?.set_codec(Some("H264"));
-    render.set_filepath(Some("output"));
-    render.set_fps(Some(30));
-    render.set_resolution_x(Some(1280));
-    render.set_resolution_y(Some(720));
+    render.set_engine("CYCLES");
+    render.image_settings().set_file_format("FFMPEG");
+    render.ffmpeg()?.set_codec("H264");
+    render.set_filepath("output");
+    render.set_fps(30);
+    render.set_resolution_x(1280);
+    render.set_resolution_y(720);
 
     bpy::ops::outliner::orphans_purge((
         ("do_local_ids", true),
@@ -42,7 +44,7 @@ fn scene_setup() -> Option<()> {
         ("do_recursive", true),
     ));
 
-    scene.set_frame_end(Some(30 * 12));
+    scene.set_frame_end(30 * 12);
 
     let world = worlds.get("World")?;
     if let Some(bg) = world.node_tree().and_then(|xs| xs.nodes()).and_then(|xs| xs.get("Background")) {
@@ -50,30 +52,30 @@ fn scene_setup() -> Option<()> {
         let socket = Box::new(
             socket.to_bpy_ptr()
         ) as Box<dyn bpy::types::NodeSocketColor>;
-        socket.set_default_value(Some(&[0., 0., 0., 1.]));
+        socket.set_default_value(&[0., 0., 0., 1.]);
     }
 
-    scene.set_frame_current(Some(1));
-    scene.set_frame_start(Some(1));
+    scene.set_frame_current(1);
+    scene.set_frame_start(1);
 
     let light_data = bpy::data::lights().new("light", "POINT")?;
     let as_point_light_data = Box::new(
         light_data.to_bpy_ptr()
     ) as Box<dyn bpy::types::PointLight>;
-    as_point_light_data.set_energy(Some(100.0));
+    as_point_light_data.set_energy(100.0);
 
     let light_object = bpy::data::objects().new("light", as_point_light_data.to_bpy_ptr())?;
 
     bpy::context().collection()?.objects()?.link(light_object.to_bpy_ptr());
-    light_object.set_location(Some(&[2.0, 2.0, 1.333]));
+    light_object.set_location(&[2.0, 2.0, 1.333]);
     bpy::context().view_layer()?.objects()?.set_active(Some(light_object.to_bpy_ptr()));
 
     let camera_data = bpy::data::cameras().new("Camera")?;
     let camera_object = bpy::data::objects().new("Camera", camera_data.to_bpy_ptr())?;
 
 
-    camera_object.set_location(Some(&[4.93136, -2.46555, 4.62837]));
-    camera_object.set_rotation_euler(Some(&[0.9223401872, 0., 1.10716881]));
+    camera_object.set_location(&[4.93136, -2.46555, 4.62837]);
+    camera_object.set_rotation_euler(&[0.9223401872, 0., 1.10716881]);
 
     scene.set_camera(Some(camera_object.to_bpy_ptr()));
 
@@ -112,7 +114,7 @@ fn create_random_bool_value_node(
     let as_func_node = Box::new(
         random_node.to_bpy_ptr()
     ) as Box<dyn bpy::types::FunctionNodeRandomValue>;
-    as_func_node.set_data_type(Some("BOOLEAN"));
+    as_func_node.set_data_type("BOOLEAN");
 
     random_node.outputs()?.values().into_iter().find(|xs| {
         let Some(ty) = xs.r#type() else { return false };
@@ -137,7 +139,7 @@ fn create_separate_geo_node(
     let as_geom_sep_node = Box::new(
         separate_geometry_node.to_bpy_ptr()
     ) as Box<dyn bpy::types::GeometryNodeSeparateGeometry>;
-    as_geom_sep_node.set_domain(Some("FACE"));
+    as_geom_sep_node.set_domain("FACE");
 
     node_tree.links()?.new(
         random_value_node_output_socket.to_bpy_ptr(),
@@ -170,15 +172,15 @@ fn create_scale_element_geo_node(
     let as_scale = Box::new(
         scale.to_bpy_ptr()
     ) as Box<dyn bpy::types::NodeSocketFloat>;
-    as_scale.set_default_value(Some(0.0));
+    as_scale.set_default_value(0.0);
     as_scale.keyframe_insert("default_value", (
         ("frame", 0.0),
     ).into());
-    as_scale.set_default_value(Some(0.8));
+    as_scale.set_default_value(0.8);
     as_scale.keyframe_insert("default_value", (
         ("frame", 45.0),
     ).into());
-    as_scale.set_default_value(Some(0.0));
+    as_scale.set_default_value(0.0);
     as_scale.keyframe_insert("default_value", (
         ("frame", 90.0),
     ).into());
@@ -200,6 +202,8 @@ fn create_scale_element_geo_node(
     Some((scale_elements_node, node_x_location))
 }
 
+// The plugin is linted with the workspace now that it builds off-Wasm.
+#[allow(clippy::type_complexity)]
 fn separate_faces_and_animate_scale(
     node_tree: &dyn bpy::types::NodeTree,
     node_x_location: i32,
@@ -268,7 +272,7 @@ fn create_node(
     node_y_location: i32
 ) -> Option<(Box<dyn bpy::types::Node + Send + Sync>, i32)> {
     let node_obj = node_tree.nodes()?.new(type_name)?;
-    node_obj.set_location(Some(&[node_x_location as f64, node_y_location as f64]));
+    node_obj.set_location(&[node_x_location as f64, node_y_location as f64]);
     Some((node_obj, node_x_location + node_location_step))
 }
 
@@ -297,7 +301,7 @@ fn update_geo_node_tree(node_tree: &dyn bpy::types::NodeTree) -> Option<()> {
         subdivide_mesh_node.inputs()?.get("Level")?.to_bpy_ptr()
     ) as Box<dyn bpy::types::NodeSocketInterfaceIntUnsigned>;
 
-    lvl.set_default_value(Some(3));
+    lvl.set_default_value(3);
 
     let (triangulate_node, node_x_location) = create_node(
         node_tree,
@@ -323,7 +327,7 @@ fn update_geo_node_tree(node_tree: &dyn bpy::types::NodeTree) -> Option<()> {
 
     let mut pos = out_node.location()?;
     pos[0] = node_x_location as f64;
-    out_node.set_location(Some(pos.as_slice()));
+    out_node.set_location(pos.as_slice());
 
     link_nodes(node_tree, &*mesh_cube_node, &*subdivide_mesh_node, "Mesh"); 
     link_nodes(node_tree, &*subdivide_mesh_node, &*triangulate_node, "Mesh"); 
@@ -346,10 +350,10 @@ fn update_geo_node_tree(node_tree: &dyn bpy::types::NodeTree) -> Option<()> {
 
 fn create_centerpiece() -> Option<()> {
     bpy::ops::mesh::primitive_plane_add(());
-    bpy::context().active_object()?.set_scale(Some(&[10., 10., 1.]));
+    bpy::context().active_object()?.set_scale(&[10., 10., 1.]);
 
     bpy::ops::mesh::primitive_plane_add(());
-    bpy::context().active_object()?.set_location(Some(&[0., 0., 1.5]));
+    bpy::context().active_object()?.set_location(&[0., 0., 1.5]);
 
     bpy::ops::node::new_geometry_nodes_modifier(());
     let node_tree = bpy::data::node_groups().get("Geometry Nodes")?;
@@ -360,7 +364,7 @@ fn create_centerpiece() -> Option<()> {
         ("type", "SOLIDIFY"),
     ));
 
-    bpy::context().active_object()?.modifiers()?.get("GeometryNodes")?.set_is_active(Some(true));
+    bpy::context().active_object()?.modifiers()?.get("GeometryNodes")?.set_is_active(true);
     Some(())
 }
 
@@ -373,7 +377,12 @@ fn example_main() {
 }
 
 #[plugin_fn]
-pub fn example() -> FnResult<()> {
+pub fn example() -> FnResult<String> {
+    // `run.py` asks for a profile of the host calls with BLEXTISM_PROFILE.
+    let format = blextism::profile::enable_from_config();
     example_main();
-    Ok(())
+    let report = format
+        .zip(blextism::profile::finish())
+        .map(|(format, profile)| profile.render(format));
+    Ok(report.unwrap_or_default())
 }
@@ -0,0 +1,388 @@
+use serde::{Deserialize, Serialize};
+use smartstring::alias::String;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub enum BpyType {
+    #[serde(rename = "BOOLEAN")]
+    Boolean,
+    #[serde(rename = "INT")]
+    Int,
+    #[serde(rename = "FLOAT")]
+    Float,
+    #[serde(rename = "STRING")]
+    String,
+    #[serde(rename = "ENUM")]
+    Enum,
+    #[serde(rename = "POINTER")]
+    Pointer,
+    #[serde(rename = "COLLECTION")]
+    Collection,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub enum BpyUnit {
+    #[serde(rename = "NONE")]
+    None,
+    #[serde(rename = "LENGTH")]
+    Length,
+    #[serde(rename = "AREA")]
+    Area,
+    #[serde(rename = "VOLUME")]
+    Volume,
+    #[serde(rename = "ROTATION")]
+    Rotation,
+    #[serde(rename = "TIME")]
+    Time,
+    #[serde(rename = "TIME_ABSOLUTE")]
+    TimeAbsolute,
+    #[serde(rename = "VELOCITY")]
+    Velocity,
+    #[serde(rename = "ACCELERATION")]
+    Acceleration,
+    #[serde(rename = "MASS")]
+    Mass,
+    #[serde(rename = "CAMERA")]
+    Camera,
+    #[serde(rename = "POWER")]
+    Power,
+    #[serde(rename = "TEMPERATURE")]
+    Temperature,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub enum BpySubtype {
+    #[serde(rename = "NONE")]
+    None,
+    #[serde(rename = "FILE_PATH")]
+    FilePath,
+    #[serde(rename = "DIR_PATH")]
+    DirPath,
+    #[serde(rename = "FILE_NAME")]
+    FileName,
+    #[serde(rename = "BYTE_STRING")]
+    ByteString,
+    #[serde(rename = "PASSWORD")]
+    Password,
+    #[serde(rename = "PIXEL")]
+    Pixel,
+    #[serde(rename = "UNSIGNED")]
+    Unsigned,
+    #[serde(rename = "PERCENTAGE")]
+    Percentage,
+    #[serde(rename = "FACTOR")]
+    Factor,
+    #[serde(rename = "ANGLE")]
+    Angle,
+    #[serde(rename = "TIME")]
+    Time,
+    #[serde(rename = "TIME_ABSOLUTE")]
+    TimeAbsolute,
+    #[serde(rename = "DISTANCE")]
+    Distance,
+    #[serde(rename = "DISTANCE_CAMERA")]
+    DistanceCamera,
+    #[serde(rename = "POWER")]
+    Power,
+    #[serde(rename = "TEMPERATURE")]
+    Temperature,
+    #[serde(rename = "COLOR")]
+    Color,
+    #[serde(rename = "TRANSLATION")]
+    Translation,
+    #[serde(rename = "DIRECTION")]
+    Direction,
+    #[serde(rename = "VELOCITY")]
+    Velocity,
+    #[serde(rename = "ACCELERATION")]
+    Acceleration,
+    #[serde(rename = "MATRIX")]
+    Matrix,
+    #[serde(rename = "EULER")]
+    Euler,
+    #[serde(rename = "QUATERNION")]
+    Quaternion,
+    #[serde(rename = "AXISANGLE")]
+    Axisangle,
+    #[serde(rename = "XYZ")]
+    Xyz,
+    #[serde(rename = "XYZ_LENGTH")]
+    XyzLength,
+    #[serde(rename = "COLOR_GAMMA")]
+    ColorGamma,
+    #[serde(rename = "COORDINATES")]
+    Coordinates,
+    #[serde(rename = "LAYER")]
+    Layer,
+    #[serde(rename = "LAYER_MEMBER")]
+    LayerMember,
+
+    #[serde(rename = "")]
+    Absent,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyPropertyItem {
+    pub identifier: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub prop_type: BpyType,
+    pub unit: BpyUnit,
+    pub subtype: BpySubtype,
+    pub is_required: bool,
+    #[serde(default)]
+    pub is_argument_optional: bool,
+    pub is_runtime: bool,
+    pub is_output: bool,
+    pub is_never_none: bool,
+    /// Whether Python can't assign the property (`ID.users`, `Object.type`).
+    #[serde(default)]
+    pub is_readonly: bool,
+    /// Whether the property can be keyframed.
+    #[serde(default)]
+    pub is_animatable: bool,
+    /// Whether a library override can change the property.
+    #[serde(default)]
+    pub is_overridable: bool,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyPropertyArray<T> {
+    pub default: Vec<T>,
+    pub dimensions: [u32; 3],
+    pub length: u32,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyPropertyNumber<T> {
+    pub hard_min: T,
+    pub hard_max: T,
+    pub soft_min: T,
+    pub soft_max: T,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyPropertyEnumItem {
+    pub id: String,
+    pub name: String,
+    pub value: i32,
+    pub description: String,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub enum BpyProperty {
+    #[serde(rename = "bool")]
+    Boolean {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        #[serde(default)]
+        default: bool,
+    },
+
+    #[serde(rename = "bool[]")]
+    BooleanArray {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        #[serde(flatten)]
+        array: BpyPropertyArray<bool>,
+    },
+
+    #[serde(rename = "int")]
+    Int {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        #[serde(flatten)]
+        number: BpyPropertyNumber<i64>,
+        #[serde(default)]
+        default: i64,
+    },
+
+    #[serde(rename = "int[]")]
+    IntArray {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        #[serde(flatten)]
+        array: BpyPropertyArray<i64>,
+        #[serde(flatten)]
+        number: BpyPropertyNumber<i64>,
+    },
+
+    #[serde(rename = "float")]
+    Float {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        #[serde(flatten)]
+        number: BpyPropertyNumber<f64>,
+        #[serde(default)]
+        default: f64,
+    },
+
+    #[serde(rename = "float[]")]
+    FloatArray {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        #[serde(flatten)]
+        array: BpyPropertyArray<f32>,
+        #[serde(flatten)]
+        number: BpyPropertyNumber<f64>,
+    },
+
+    #[serde(rename = "string")]
+    String {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        length_max: u32,
+        default: Option<String>,
+    },
+
+    #[serde(rename = "enum")]
+    Enum {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        items: Vec<BpyPropertyEnumItem>,
+        #[serde(default)]
+        default: Option<String>,
+        /// Whether the property holds a set of the items rather than one.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_flag: bool,
+    },
+
+    #[serde(rename = "pointer")]
+    Pointer {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        fixed_type: String,
+        /// The type Blender reported, when it isn't in `bpy.types` and
+        /// `fixed_type` fell back to `bpy_struct`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unresolved_type: Option<String>,
+    },
+
+    #[serde(rename = "collection")]
+    Collection {
+        #[serde(flatten)]
+        item: BpyPropertyItem,
+        fixed_type: String,
+        collection: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unresolved_type: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(tag = "type", content = "item")]
+pub enum BpyMethod {
+    #[serde(rename = "rna")]
+    Rna {
+        description: String,
+        use_self: bool,
+        use_self_type: bool,
+        parameters: Vec<BpyProperty>,
+        /// The name to generate, when it differs from the name the host knows
+        /// the function by (set by overlay renames).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        identifier: Option<String>,
+    },
+
+    #[serde(rename = "builtin_function_or_method")]
+    Builtin,
+
+    #[serde(rename = "method_descriptor")]
+    MethodDescriptor,
+    #[serde(rename = "_PropertyDeferred")]
+    PropertyDeferred,
+    #[serde(rename = "function")]
+    Function,
+    #[serde(rename = "method")]
+    Method,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyStructure {
+    pub name: String,
+    pub parent: String,
+    /// Every direct base, in Python's order, for classes with more than one:
+    /// `parent` plus the Python mixins (or other classes) it's combined
+    /// with, such as `Bone`'s `_GenericBone`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bases: Vec<String>,
+    /// Whether this is a Python mixin rather than an RNA class. Mixins have
+    /// no `bl_rna`; they only contribute Python-level members to the
+    /// classes that list them in `bases`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mixin: bool,
+    pub properties: BTreeMap<String, BpyProperty>,
+    pub methods: BTreeMap<String, BpyMethod>,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyOperator {
+    pub description: String,
+    pub parameters: Vec<BpyProperty>,
+    /// The name to generate, when it differs from the name the host knows
+    /// the operator by (set by overlay renames).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct Schema {
+    /// The Blender version the schema was reflected from, `"4.0"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub classes: Vec<BpyStructure>,
+    pub operators: BTreeMap<String, BTreeMap<String, BpyOperator>>,
+    /// Classes genschema found but could not place in the class tree: those
+    /// with several bases, none of them in `bpy.types`.
+    #[serde(default)]
+    pub unsupported: Vec<BpyUnsupportedClass>,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+pub struct BpyUnsupportedClass {
+    pub name: String,
+    pub bases: Vec<String>,
+}
+
+impl BpyProperty {
+    pub fn as_item(&self) -> &BpyPropertyItem {
+        match self {
+            BpyProperty::Boolean { item, .. } => item,
+            BpyProperty::BooleanArray { item, .. } => item,
+            BpyProperty::Int { item, .. } => item,
+            BpyProperty::IntArray { item, .. } => item,
+            BpyProperty::Float { item, .. } => item,
+            BpyProperty::FloatArray { item, .. } => item,
+            BpyProperty::String { item, .. } => item,
+            BpyProperty::Enum { item, .. } => item,
+            BpyProperty::Pointer { item, .. } => item,
+            BpyProperty::Collection { item, .. } => item,
+        }
+    }
+
+    pub fn is_output(&self) -> bool {
+        self.as_item().is_output
+    }
+
+    /// Whether reading this property yields a handle to another `bpy_struct`
+    /// (a pointer or a collection) rather than a plain value.
+    pub fn is_struct(&self) -> bool {
+        matches!(self, BpyProperty::Pointer { .. } | BpyProperty::Collection { .. })
+    }
+}
+
+impl BpyStructure {
+    /// The classes this one directly extends: `parent`, then the rest of
+    /// `bases`.
+    pub fn supers(&self) -> impl Iterator<Item = &str> {
+        let parent = self.parent.as_str();
+        std::iter::once(parent).chain(self.bases.iter().map(|xs| xs.as_str()).filter(move |xs| *xs != parent))
+    }
+}
+
+impl Schema {
+    pub fn class(&self, name: &str) -> Option<&BpyStructure> {
+        self.classes.iter().find(|xs| xs.name == name)
+    }
+}
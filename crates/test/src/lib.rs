@@ -0,0 +1,17 @@
+//! Test harnesses for plugins built on `blextism`.
+//!
+//! Plugins normally only run inside Blender, under `just run`. The harnesses
+//! here implement [`blextism::Transport`] so plugin logic can run under a plain
+//! `cargo test` instead:
+//!
+//! - [`MockHost`] answers from a script and records every call, for asserting
+//!   on exactly what a plugin function did.
+//! - [`Simulator`] keeps an in-memory object graph built from `schema.json`,
+//!   for running a plugin end to end and inspecting the resulting state.
+//!   With the `host` feature it also implements `blextism_host::Backend`, so
+//!   a compiled `.wasm` plugin can run against it.
+mod mock;
+mod sim;
+
+pub use crate::mock::{ptr, Call, MockHost};
+pub use crate::sim::{Hook, Invocation, SimError, Simulator};
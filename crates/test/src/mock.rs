@@ -0,0 +1,429 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use blextism::{PyArgs, Transport};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// A single host call made by the plugin, in the order it was made.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call {
+    GetAttr {
+        ptr: i64,
+        attr: String,
+    },
+    SetAttr {
+        ptr: i64,
+        attr: String,
+        value: Value,
+    },
+    CallMethod {
+        ptr: i64,
+        method: String,
+        args: Vec<Value>,
+        kwargs: Map<String, Value>,
+    },
+    /// A class-level function, `bpy.types.<class>.<function>(..)`.
+    ClassFunction {
+        class: String,
+        function: String,
+        args: Vec<Value>,
+        kwargs: Map<String, Value>,
+    },
+    Operator {
+        opmod: String,
+        operator: String,
+        kwargs: Map<String, Value>,
+    },
+}
+
+impl Call {
+    pub fn getattr(ptr: i64, attr: &str) -> Self {
+        Call::GetAttr {
+            ptr,
+            attr: attr.to_string(),
+        }
+    }
+
+    pub fn setattr(ptr: i64, attr: &str, value: impl Serialize) -> Self {
+        Call::SetAttr {
+            ptr,
+            attr: attr.to_string(),
+            value: serde_json::to_value(value).expect("value must be serializable"),
+        }
+    }
+
+    pub fn callmethod(ptr: i64, method: &str, args: Vec<Value>) -> Self {
+        Call::CallMethod {
+            ptr,
+            method: method.to_string(),
+            args,
+            kwargs: Map::new(),
+        }
+    }
+
+    pub fn class_function(class: &str, function: &str, args: Vec<Value>) -> Self {
+        Call::ClassFunction {
+            class: class.to_string(),
+            function: function.to_string(),
+            args,
+            kwargs: Map::new(),
+        }
+    }
+
+    pub fn operator(opmod: &str, operator: &str) -> Self {
+        Call::Operator {
+            opmod: opmod.to_string(),
+            operator: operator.to_string(),
+            kwargs: Map::new(),
+        }
+    }
+
+    /// Attach kwargs to a `CallMethod`, `ClassFunction` or `Operator`
+    /// expectation.
+    pub fn with_kwargs(mut self, value: Value) -> Self {
+        let Value::Object(map) = value else {
+            panic!("kwargs must be a JSON object")
+        };
+
+        match &mut self {
+            Call::CallMethod { kwargs, .. }
+            | Call::ClassFunction { kwargs, .. }
+            | Call::Operator { kwargs, .. } => *kwargs = map,
+            _ => panic!("only method and operator calls take kwargs"),
+        }
+        self
+    }
+}
+
+/// The JSON a host sends for a `bpy_struct`: `{"@ptr": ptr}`.
+pub fn ptr(ptr: i64) -> Value {
+    serde_json::json!({ "@ptr": ptr })
+}
+
+type Fallback = Box<dyn Fn(&Call) -> Value>;
+
+struct State {
+    next_ptr: i64,
+    bpy_data: Map<String, Value>,
+    config: HashMap<String, String>,
+    attrs: HashMap<(i64, String), Value>,
+    methods: HashMap<(i64, String), Value>,
+    class_functions: HashMap<(String, String), Value>,
+    operators: HashMap<(String, String), Value>,
+    fallback: Option<Fallback>,
+    calls: Vec<Call>,
+}
+
+/// A scripted stand-in for the Blender host.
+///
+/// Every getattr, setattr, callmethod and operator call the plugin makes is
+/// recorded, and return values are scripted per `(pointer, attribute)`. A
+/// setattr on a pointer is visible to later getattrs of the same attribute.
+/// Unscripted getattrs and calls return `null`, except the collection
+/// methods `keys`, `values` and `items`, which return an empty list.
+///
+/// `MockHost` is a cheap handle: clone it, [`install`](Self::install) it for
+/// the current thread, run the plugin code and then inspect the clone.
+///
+/// ```
+/// use blextism::bpy;
+/// use blextism_test::{ptr, MockHost};
+///
+/// let host = MockHost::new();
+/// let (scene, render) = (host.alloc(), host.alloc());
+/// host.on_getattr(host.context(), "scene", ptr(scene));
+/// host.on_getattr(scene, "render", ptr(render));
+/// host.install();
+///
+/// bpy::context().scene().unwrap().render().set_engine("CYCLES");
+///
+/// host.assert_setattr(render, "engine", "CYCLES");
+/// ```
+#[derive(Clone)]
+pub struct MockHost {
+    state: Rc<RefCell<State>>,
+}
+
+const CONTEXT_PTR: i64 = 1;
+
+impl Default for MockHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockHost {
+    pub fn new() -> Self {
+        let mut bpy_data = Map::new();
+        bpy_data.insert("context".to_string(), Value::from(CONTEXT_PTR));
+
+        Self {
+            state: Rc::new(RefCell::new(State {
+                next_ptr: CONTEXT_PTR + 1,
+                bpy_data,
+                config: HashMap::new(),
+                attrs: HashMap::new(),
+                methods: HashMap::new(),
+                class_functions: HashMap::new(),
+                operators: HashMap::new(),
+                fallback: None,
+                calls: Vec::new(),
+            })),
+        }
+    }
+
+    /// Install a handle to this host as the transport for the current thread.
+    pub fn install(&self) {
+        blextism::set_transport(self.clone());
+    }
+
+    /// The pointer `bpy::context()` resolves to.
+    pub fn context(&self) -> i64 {
+        CONTEXT_PTR
+    }
+
+    /// Hand out a fresh pointer that no other object uses.
+    pub fn alloc(&self) -> i64 {
+        let mut state = self.state.borrow_mut();
+        let ptr = state.next_ptr;
+        state.next_ptr += 1;
+        ptr
+    }
+
+    /// Make `bpy::data::<member>()` resolve to `ptr`.
+    pub fn data(&self, member: &str, ptr: i64) -> &Self {
+        self.state
+            .borrow_mut()
+            .bpy_data
+            .insert(member.to_string(), Value::from(ptr));
+        self
+    }
+
+    /// Set an extism config value other than `bpy.data`.
+    pub fn config(&self, key: &str, value: &str) -> &Self {
+        self.state
+            .borrow_mut()
+            .config
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn on_getattr(&self, ptr: i64, attr: &str, value: impl Serialize) -> &Self {
+        self.state
+            .borrow_mut()
+            .attrs
+            .insert((ptr, attr.to_string()), to_value(value));
+        self
+    }
+
+    pub fn on_callmethod(&self, ptr: i64, method: &str, value: impl Serialize) -> &Self {
+        self.state
+            .borrow_mut()
+            .methods
+            .insert((ptr, method.to_string()), to_value(value));
+        self
+    }
+
+    pub fn on_class_function(&self, class: &str, function: &str, value: impl Serialize) -> &Self {
+        self.state
+            .borrow_mut()
+            .class_functions
+            .insert((class.to_string(), function.to_string()), to_value(value));
+        self
+    }
+
+    pub fn on_operator(&self, opmod: &str, operator: &str, value: impl Serialize) -> &Self {
+        self.state
+            .borrow_mut()
+            .operators
+            .insert((opmod.to_string(), operator.to_string()), to_value(value));
+        self
+    }
+
+    /// Answer every unscripted call with `fallback` instead of the defaults.
+    pub fn fallback(&self, fallback: impl Fn(&Call) -> Value + 'static) -> &Self {
+        self.state.borrow_mut().fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// Every call recorded so far.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state.borrow().calls.clone()
+    }
+
+    /// Forget the calls recorded so far, keeping the script.
+    pub fn clear_calls(&self) {
+        self.state.borrow_mut().calls.clear();
+    }
+
+    /// Every value written to `ptr.attr`, oldest first.
+    pub fn setattrs(&self, ptr: i64, attr: &str) -> Vec<Value> {
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .filter_map(|call| match call {
+                Call::SetAttr {
+                    ptr: p,
+                    attr: a,
+                    value,
+                } if *p == ptr && a == attr => Some(value.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Panic unless the plugin wrote `value` to `ptr.attr` at some point.
+    #[track_caller]
+    pub fn assert_setattr(&self, ptr: i64, attr: &str, value: impl Serialize) {
+        let value = to_value(value);
+        let written = self.setattrs(ptr, attr);
+        assert!(
+            written.contains(&value),
+            "expected {}.{} to be set to {}, but it was set to {:?}\ncalls: {:#?}",
+            ptr,
+            attr,
+            value,
+            written,
+            self.calls()
+        );
+    }
+
+    /// Panic unless `expected` appears, in order, within the recorded calls.
+    /// Other calls may be interleaved between the expected ones.
+    #[track_caller]
+    pub fn assert_calls_in_order(&self, expected: &[Call]) {
+        let calls = self.calls();
+        let mut remaining = calls.iter();
+        for (idx, call) in expected.iter().enumerate() {
+            assert!(
+                remaining.any(|xs| xs == call),
+                "expected call #{} ({:?}) was not made in order\ncalls: {:#?}",
+                idx,
+                call,
+                calls
+            );
+        }
+    }
+
+    fn respond(&self, call: Call) -> Value {
+        let mut state = self.state.borrow_mut();
+        state.calls.push(call.clone());
+
+        let scripted = match &call {
+            Call::GetAttr { ptr, attr } => state.attrs.get(&(*ptr, attr.clone())).cloned(),
+            Call::SetAttr { ptr, attr, value } => {
+                state.attrs.insert((*ptr, attr.clone()), value.clone());
+                return Value::Null;
+            }
+            Call::CallMethod { ptr, method, .. } => {
+                state.methods.get(&(*ptr, method.clone())).cloned()
+            }
+            Call::ClassFunction {
+                class, function, ..
+            } => state
+                .class_functions
+                .get(&(class.clone(), function.clone()))
+                .cloned(),
+            Call::Operator {
+                opmod, operator, ..
+            } => state
+                .operators
+                .get(&(opmod.clone(), operator.clone()))
+                .cloned(),
+        };
+
+        if let Some(value) = scripted {
+            return value;
+        }
+
+        if let Some(fallback) = state.fallback.take() {
+            drop(state);
+            let value = fallback(&call);
+            self.state.borrow_mut().fallback = Some(fallback);
+            return value;
+        }
+
+        match &call {
+            Call::CallMethod { method, .. }
+                if matches!(method.as_str(), "keys" | "values" | "items") =>
+            {
+                Value::Array(Vec::new())
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+fn to_value(value: impl Serialize) -> Value {
+    serde_json::to_value(value).expect("scripted value must be serializable")
+}
+
+fn target(args: &PyArgs, what: &str) -> i64 {
+    args.target()
+        .unwrap_or_else(|| panic!("{} called without a target", what))
+        .as_raw()
+}
+
+fn kwargs(args: &PyArgs) -> Map<String, Value> {
+    args.kwargs()
+        .map(|kwargs| {
+            kwargs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Transport for MockHost {
+    fn getattr(&self, attr: &str, args: PyArgs) -> Value {
+        self.respond(Call::GetAttr {
+            ptr: target(&args, attr),
+            attr: attr.to_string(),
+        })
+    }
+
+    fn setattr(&self, attr: &str, args: PyArgs) {
+        let value = args.args().first().cloned().unwrap_or(Value::Null);
+        self.respond(Call::SetAttr {
+            ptr: target(&args, attr),
+            attr: attr.to_string(),
+            value,
+        });
+    }
+
+    fn callmethod(&self, method: &str, args: PyArgs) -> Value {
+        if let (None, Some(class)) = (args.target(), args.class()) {
+            return self.respond(Call::ClassFunction {
+                class: class.to_string(),
+                function: method.to_string(),
+                args: args.args().to_vec(),
+                kwargs: kwargs(&args),
+            });
+        }
+
+        self.respond(Call::CallMethod {
+            ptr: target(&args, method),
+            method: method.to_string(),
+            args: args.args().to_vec(),
+            kwargs: kwargs(&args),
+        })
+    }
+
+    fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> Value {
+        self.respond(Call::Operator {
+            opmod: opmod.to_string(),
+            operator: operator.to_string(),
+            kwargs: kwargs(&args),
+        })
+    }
+
+    fn config(&self, key: &str) -> Option<String> {
+        let state = self.state.borrow();
+        if key == "bpy.data" {
+            return Some(Value::Object(state.bpy_data.clone()).to_string());
+        }
+        state.config.get(key).cloned()
+    }
+}
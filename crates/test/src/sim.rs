@@ -0,0 +1,1099 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use blextism::{PyArgs, Transport};
+use blextism_schema::{BpyProperty, BpyStructure, Schema};
+use serde_json::{Map, Value};
+
+/// Something the simulator refused to do. Over a [`Transport`] these become
+/// panics, which fail the test that triggered them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimError {
+    UnknownPtr(i64),
+    UnknownClass(String),
+    NoAttribute { class: String, attr: String },
+    InvalidEnum { class: String, attr: String, value: Value },
+    BadArgument(String),
+    ReadOnly { class: String, attr: String },
+    Unhandled(String),
+}
+
+impl std::fmt::Display for SimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimError::UnknownPtr(ptr) => write!(f, "unknown pointer {}", ptr),
+            SimError::UnknownClass(class) => write!(f, "unknown class {:?}", class),
+            SimError::NoAttribute { class, attr } => {
+                write!(f, "{:?} object has no attribute {:?}", class, attr)
+            }
+            SimError::InvalidEnum { class, attr, value } => {
+                write!(f, "{} is not a valid item for {}.{}", value, class, attr)
+            }
+            SimError::BadArgument(msg) => write!(f, "bad argument: {}", msg),
+            SimError::ReadOnly { class, attr } => {
+                write!(f, "attribute {:?} from {:?} is read-only", attr, class)
+            }
+            SimError::Unhandled(what) => write!(
+                f,
+                "the simulator cannot emulate {}; install a hook for it",
+                what
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+/// A method or operator call handed to a hook.
+#[derive(Debug, Clone)]
+pub struct Invocation {
+    /// `None` for operators and class-level functions.
+    pub target: Option<i64>,
+    /// `Some(class)` for class-level functions, `bpy.types.<class>.<name>`.
+    pub class: Option<String>,
+    /// `Some(module)` for operators, e.g. `"object"` for `bpy.ops.object.*`.
+    pub opmod: Option<String>,
+    pub name: String,
+    pub args: Vec<Value>,
+    pub kwargs: Map<String, Value>,
+}
+
+impl Invocation {
+    fn describe(&self, sim: &Simulator) -> String {
+        match (&self.opmod, self.target) {
+            (Some(opmod), _) => format!("operator bpy.ops.{}.{}", opmod, self.name),
+
+            (None, Some(ptr)) => format!(
+                "method {}.{}",
+                sim.class_of(ptr).unwrap_or_else(|_| "<unknown>".to_string()),
+                self.name
+            ),
+            (None, None) => match &self.class {
+                Some(class) => format!("function bpy.types.{}.{}", class, self.name),
+                None => format!("function {}", self.name),
+            },
+        }
+    }
+}
+
+pub type Hook = Arc<dyn Fn(&Simulator, &Invocation) -> Result<Value, SimError> + Send + Sync>;
+
+struct Object {
+    class: String,
+    props: HashMap<String, Value>,
+    /// The properties written since creation or their last `property_unset`;
+    /// the rest of `props` are defaults materialized by reads.
+    assigned: HashSet<String>,
+    /// Set for collection objects: the item type and the members, in order.
+    items: Option<(String, Vec<i64>)>,
+}
+
+struct World {
+    schema: Arc<SchemaIndex>,
+    next_ptr: i64,
+    objects: HashMap<i64, Object>,
+    context: i64,
+    data: i64,
+    method_hooks: HashMap<(String, String), Hook>,
+    operator_hooks: HashMap<(String, String), Hook>,
+    unhandled: Option<Hook>,
+}
+
+struct SchemaIndex {
+    classes: HashMap<String, BpyStructure>,
+}
+
+impl SchemaIndex {
+    /// `class`, then its ancestors depth-first: each parent's line before
+    /// any mixin bases.
+    fn lineage(&self, class: &str) -> Vec<&BpyStructure> {
+        let mut lineage: Vec<&BpyStructure> = Vec::new();
+        let mut stack = vec![class];
+        while let Some(name) = stack.pop() {
+            let Some(structure) = self.classes.get(name) else {
+                continue;
+            };
+            if lineage.iter().any(|xs| xs.name == structure.name) {
+                continue;
+            }
+            lineage.push(structure);
+            let supers: Vec<_> = structure.supers().collect();
+            stack.extend(supers.into_iter().rev());
+        }
+        lineage
+    }
+
+    /// Find `attr` on `class` or any of its ancestors.
+    fn property(&self, class: &str, attr: &str) -> Option<&BpyProperty> {
+        self.lineage(class)
+            .into_iter()
+            .find_map(|structure| structure.properties.get(attr))
+    }
+
+    fn is_subclass(&self, class: &str, ancestor: &str) -> bool {
+        self.lineage(class).iter().any(|structure| structure.name == ancestor)
+    }
+}
+
+/// An in-memory stand-in for Blender, driven by the same `schema.json` that
+/// bindgen reads.
+///
+/// Objects are a generic graph: each one has an RNA class from the schema,
+/// and its properties start at their schema defaults the first time they are
+/// read. Pointers that are never `None` are filled with a fresh object of
+/// their `fixed_type`; collections support `new`, `remove`, `link`, `unlink`,
+/// `get`, `keys`, `values` and `items`, and every object supports
+/// `path_resolve`, `property_unset` and `is_property_set`. Everything else
+/// (operators, and RNA functions such as `Object.hide_set`) is delegated to
+/// hooks installed with [`on_operator`](Self::on_operator) and
+/// [`on_method`](Self::on_method).
+///
+/// Like [`MockHost`](crate::MockHost), a `Simulator` is a cheap handle: keep a
+/// clone after [`install`](Self::install)ing it to inspect the resulting
+/// state.
+#[derive(Clone)]
+pub struct Simulator {
+    world: Arc<Mutex<World>>,
+}
+
+impl Simulator {
+    pub fn new(schema: Schema) -> Self {
+        let classes = schema
+            .classes
+            .into_iter()
+            .map(|xs| (xs.name.to_string(), xs))
+            .collect();
+
+        let world = World {
+            schema: Arc::new(SchemaIndex { classes }),
+            next_ptr: 1,
+            objects: HashMap::new(),
+            context: 0,
+            data: 0,
+            method_hooks: HashMap::new(),
+            operator_hooks: HashMap::new(),
+            unhandled: None,
+        };
+
+        let sim = Self {
+            world: Arc::new(Mutex::new(world)),
+        };
+
+        let context = sim.alloc("Context", None);
+        let data = sim.alloc("BlendData", None);
+        {
+            let mut world = sim.world();
+            world.context = context;
+            world.data = data;
+        }
+        sim
+    }
+
+    /// Load a simulator from a `schema.json` generated by `genschema.py`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let file = std::fs::File::open(path)?;
+        let schema: Schema = serde_json::from_reader(std::io::BufReader::new(file))?;
+        Ok(Self::new(schema))
+    }
+
+    /// Install a handle to this simulator as the transport for the current
+    /// thread.
+    pub fn install(&self) {
+        blextism::set_transport(self.clone());
+    }
+
+    /// The `bpy.context` object.
+    pub fn context(&self) -> i64 {
+        self.world().context
+    }
+
+    /// The `bpy.data` object.
+    pub fn data(&self) -> i64 {
+        self.world().data
+    }
+
+    /// Create a free-standing object of `class`, with every property at its
+    /// default.
+    pub fn create(&self, class: &str) -> Result<i64, SimError> {
+        if !self.world().schema.classes.contains_key(class) {
+            return Err(SimError::UnknownClass(class.to_string()));
+        }
+        Ok(self.alloc(class, None))
+    }
+
+    pub fn class_of(&self, ptr: i64) -> Result<String, SimError> {
+        self.with_object(ptr, |obj| obj.class.clone())
+    }
+
+    /// Read `ptr.attr`, materializing its default on first access.
+    pub fn get(&self, ptr: i64, attr: &str) -> Result<Value, SimError> {
+        if let Some(value) = self.with_object(ptr, |obj| obj.props.get(attr).cloned())? {
+            return Ok(value);
+        }
+
+        let class = self.class_of(ptr)?;
+        let schema = self.world().schema.clone();
+        let prop = schema
+            .property(&class, attr)
+            .ok_or_else(|| SimError::NoAttribute {
+                class: class.clone(),
+                attr: attr.to_string(),
+            })?;
+
+        let value = self.default_value(prop);
+        self.with_object_mut(ptr, |obj| {
+            obj.props.insert(attr.to_string(), value.clone());
+        })?;
+        Ok(value)
+    }
+
+    /// Write `ptr.attr`, checking the attribute exists and enum values are
+    /// among the schema's items (a list of them, for flag enums). Read-only properties can be written too, to
+    /// set up a test; the plugin's own writes to them fail.
+    pub fn set(&self, ptr: i64, attr: &str, value: Value) -> Result<(), SimError> {
+        let class = self.class_of(ptr)?;
+        let schema = self.world().schema.clone();
+        let prop = schema
+            .property(&class, attr)
+            .ok_or_else(|| SimError::NoAttribute {
+                class: class.clone(),
+                attr: attr.to_string(),
+            })?;
+
+        if let BpyProperty::Enum { items, is_flag, .. } = prop {
+            let is_item = |id: &Value| id.as_str().is_some_and(|id| items.iter().any(|xs| xs.id == id));
+            let valid = match (&value, is_flag) {
+                (Value::Array(ids), true) => ids.iter().all(is_item),
+                (id, false) => is_item(id),
+                _ => false,
+            };
+            if !valid {
+                return Err(SimError::InvalidEnum {
+                    class,
+                    attr: attr.to_string(),
+                    value,
+                });
+            }
+        }
+
+        self.with_object_mut(ptr, |obj| {
+            obj.assigned.insert(attr.to_string());
+            obj.props.insert(attr.to_string(), value);
+        })
+    }
+
+    /// Return `ptr.attr` to its default, the way `bpy_struct.property_unset`
+    /// does.
+    pub fn unset(&self, ptr: i64, attr: &str) -> Result<(), SimError> {
+        let class = self.class_of(ptr)?;
+        if self.world().schema.property(&class, attr).is_none() {
+            return Err(SimError::NoAttribute {
+                class,
+                attr: attr.to_string(),
+            });
+        }
+        self.with_object_mut(ptr, |obj| {
+            obj.assigned.remove(attr);
+            obj.props.remove(attr);
+        })
+    }
+
+    /// Whether `ptr.attr` has been written since creation or its last
+    /// [`Self::unset`], the way `bpy_struct.is_property_set` reports.
+    pub fn is_set(&self, ptr: i64, attr: &str) -> Result<bool, SimError> {
+        let class = self.class_of(ptr)?;
+        if self.world().schema.property(&class, attr).is_none() {
+            return Err(SimError::NoAttribute {
+                class,
+                attr: attr.to_string(),
+            });
+        }
+        self.with_object(ptr, |obj| obj.assigned.contains(attr))
+    }
+
+    /// A write from the plugin: [`Self::set`], unless the property is
+    /// read-only.
+    fn assign(&self, ptr: i64, attr: &str, value: Value) -> Result<(), SimError> {
+        let class = self.class_of(ptr)?;
+        let readonly = self
+            .world()
+            .schema
+            .property(&class, attr)
+            .is_some_and(|prop| prop.as_item().is_readonly);
+        if readonly {
+            return Err(SimError::ReadOnly {
+                class,
+                attr: attr.to_string(),
+            });
+        }
+        self.set(ptr, attr, value)
+    }
+
+    /// The members of a collection object, in order.
+    pub fn members(&self, collection: i64) -> Result<Vec<i64>, SimError> {
+        self.with_object(collection, |obj| {
+            obj.items.as_ref().map(|(_, items)| items.clone())
+        })?
+        .ok_or_else(|| SimError::BadArgument(format!("{} is not a collection", collection)))
+    }
+
+    /// Find a collection member by its `name`.
+    pub fn lookup(&self, collection: i64, key: &str) -> Result<Option<i64>, SimError> {
+        for member in self.members(collection)? {
+            if self.name_of(member)? == key {
+                return Ok(Some(member));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolve an RNA data path such as `modifiers["GeometryNodes"].show_viewport`
+    /// or `location[2]` relative to `ptr`, the way `bpy_struct.path_resolve`
+    /// does.
+    pub fn path_resolve(&self, ptr: i64, path: &str) -> Result<Value, SimError> {
+        let mut value = self.lower(ptr);
+        for segment in parse_data_path(path)? {
+            value = match (segment, value.get("@ptr").and_then(Value::as_i64)) {
+                (PathSegment::Attr(attr), Some(ptr)) => self.get(ptr, &attr)?,
+                (PathSegment::Key(key), Some(ptr)) => self
+                    .lookup(ptr, &key)?
+                    .map(|xs| self.lower(xs))
+                    .ok_or_else(|| SimError::BadArgument(format!("{:?} not found in {}", key, path)))?,
+                (PathSegment::Index(idx), Some(ptr)) => self
+                    .members(ptr)?
+                    .get(idx)
+                    .map(|xs| self.lower(*xs))
+                    .ok_or_else(|| SimError::BadArgument(format!("index {} out of range in {}", idx, path)))?,
+                (PathSegment::Index(idx), None) => value
+                    .get(idx)
+                    .cloned()
+                    .ok_or_else(|| SimError::BadArgument(format!("index {} out of range in {}", idx, path)))?,
+                (_, None) => {
+                    return Err(SimError::BadArgument(format!(
+                        "{} walks into a value that is not a bpy_struct",
+                        path
+                    )))
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    /// Add a new member to a collection, the way `collection.new(name)` does.
+    /// If `class` is `None` the collection's item type is used.
+    pub fn add(&self, collection: i64, name: &str, class: Option<&str>) -> Result<i64, SimError> {
+        let item_type = self
+            .with_object(collection, |obj| obj.items.as_ref().map(|(ty, _)| ty.clone()))?
+            .ok_or_else(|| SimError::BadArgument(format!("{} is not a collection", collection)))?;
+
+        let class = class.unwrap_or(item_type.as_str()).to_string();
+        let name = self.unique_name(collection, name)?;
+        let ptr = self.create(&class)?;
+        if self.world().schema.property(&class, "name").is_some() {
+            self.set(ptr, "name", Value::from(name))?;
+        }
+
+        self.with_object_mut(collection, |obj| {
+            if let Some((_, items)) = obj.items.as_mut() {
+                items.push(ptr);
+            }
+        })?;
+        Ok(ptr)
+    }
+
+    /// Handle `bpy.types.<class>.<method>` (on `class` or any subclass) with
+    /// `hook` instead of failing. This covers class-level functions called on
+    /// the class itself as well as methods called on instances.
+    pub fn on_method(
+        &self,
+        class: &str,
+        method: &str,
+        hook: impl Fn(&Simulator, &Invocation) -> Result<Value, SimError> + Send + Sync + 'static,
+    ) -> &Self {
+        self.world()
+            .method_hooks
+            .insert((class.to_string(), method.to_string()), Arc::new(hook));
+        self
+    }
+
+    /// Handle `bpy.ops.<opmod>.<operator>` with `hook`.
+    pub fn on_operator(
+        &self,
+        opmod: &str,
+        operator: &str,
+        hook: impl Fn(&Simulator, &Invocation) -> Result<Value, SimError> + Send + Sync + 'static,
+    ) -> &Self {
+        self.world()
+            .operator_hooks
+            .insert((opmod.to_string(), operator.to_string()), Arc::new(hook));
+        self
+    }
+
+    /// Handle every method and operator that has no hook of its own. Without
+    /// this, such calls fail with [`SimError::Unhandled`].
+    pub fn on_unhandled(
+        &self,
+        hook: impl Fn(&Simulator, &Invocation) -> Result<Value, SimError> + Send + Sync + 'static,
+    ) -> &Self {
+        self.world().unhandled = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call a method on `ptr` as the plugin would.
+    pub fn call(&self, invocation: &Invocation) -> Result<Value, SimError> {
+        let Some(ptr) = invocation.target else {
+            return match &invocation.class {
+                Some(class) if invocation.opmod.is_none() => self.class_function(class, invocation),
+                _ => self.operator(invocation),
+            };
+        };
+
+        let class = self.class_of(ptr)?;
+        if let Some(hook) = self.method_hook(&class, &invocation.name) {
+            return hook(self, invocation);
+        }
+
+        let is_collection = self.with_object(ptr, |obj| obj.items.is_some())?;
+        match (is_collection, invocation.name.as_str()) {
+            (_, "__repr__") => Ok(Value::from(format!("<bpy_struct, {}({})>", class, ptr))),
+            (_, "property_unset") => {
+                let attr = string_arg(invocation, 0, "property")?;
+                self.unset(ptr, &attr)?;
+                Ok(Value::Null)
+            }
+            (_, "is_property_set") => {
+                let attr = string_arg(invocation, 0, "property")?;
+                Ok(Value::from(self.is_set(ptr, &attr)?))
+            }
+            (_, "path_resolve") => {
+                let path = string_arg(invocation, 0, "path")?;
+                self.path_resolve(ptr, &path)
+            }
+            (true, "get") => {
+                let key = string_arg(invocation, 0, "key")?;
+                Ok(self.lookup(ptr, &key)?.map(|xs| self.lower(xs)).unwrap_or(Value::Null))
+            }
+            (true, "keys") => Ok(Value::Array(
+                self.members(ptr)?
+                    .into_iter()
+                    .map(|xs| self.name_of(xs).map(Value::from))
+                    .collect::<Result<_, _>>()?,
+            )),
+            (true, "values") => Ok(Value::Array(
+                self.members(ptr)?.into_iter().map(|xs| self.lower(xs)).collect(),
+            )),
+            (true, "items") => Ok(Value::Array(
+                self.members(ptr)?
+                    .into_iter()
+                    .map(|xs| Ok(Value::Array(vec![Value::from(self.name_of(xs)?), self.lower(xs)])))
+                    .collect::<Result<_, SimError>>()?,
+            )),
+            (true, "new") => {
+                // `nodes.new("GeometryNodeMeshCube")` takes a class name;
+                // `objects.new("Cube", data)` takes a name.
+                let first = string_arg(invocation, 0, "name")?;
+                let item_type = self
+                    .with_object(ptr, |obj| obj.items.as_ref().map(|(ty, _)| ty.clone()))?
+                    .unwrap_or_default();
+                let schema = self.world().schema.clone();
+                let class = schema
+                    .is_subclass(&first, &item_type)
+                    .then_some(first.as_str());
+
+                let created = self.add(ptr, &first, class)?;
+                Ok(self.lower(created))
+            }
+            (true, "remove") | (true, "unlink") => {
+                let member = ptr_arg(invocation, 0)?;
+                let removed = self.with_object_mut(ptr, |obj| match obj.items.as_mut() {
+                    Some((_, items)) if items.contains(&member) => {
+                        items.retain(|xs| *xs != member);
+                        true
+                    }
+                    _ => false,
+                })?;
+                if !removed {
+                    return Err(SimError::BadArgument(format!(
+                        "{}: {} is not in the collection",
+                        invocation.name, member
+                    )));
+                }
+                Ok(Value::Null)
+            }
+            (true, "link") => {
+                let member = ptr_arg(invocation, 0)?;
+                self.class_of(member)?;
+                self.with_object_mut(ptr, |obj| {
+                    if let Some((_, items)) = obj.items.as_mut() {
+                        if !items.contains(&member) {
+                            items.push(member);
+                        }
+                    }
+                })?;
+                Ok(Value::Null)
+            }
+            _ => self.unhandled(invocation),
+        }
+    }
+
+    fn class_function(&self, class: &str, invocation: &Invocation) -> Result<Value, SimError> {
+        match self.method_hook(class, &invocation.name) {
+            Some(hook) => hook(self, invocation),
+            None => self.unhandled(invocation),
+        }
+    }
+
+    fn operator(&self, invocation: &Invocation) -> Result<Value, SimError> {
+        let key = (
+            invocation.opmod.clone().unwrap_or_default(),
+            invocation.name.clone(),
+        );
+        let hook = self.world().operator_hooks.get(&key).cloned();
+        match hook {
+            Some(hook) => hook(self, invocation),
+            None => self.unhandled(invocation),
+        }
+    }
+
+    fn unhandled(&self, invocation: &Invocation) -> Result<Value, SimError> {
+        let hook = self.world().unhandled.clone();
+        match hook {
+            Some(hook) => hook(self, invocation),
+            None => Err(SimError::Unhandled(invocation.describe(self))),
+        }
+    }
+
+    fn method_hook(&self, class: &str, method: &str) -> Option<Hook> {
+        let world = self.world();
+        world.schema.lineage(class).into_iter().find_map(|structure| {
+            let key = (structure.name.to_string(), method.to_string());
+            world.method_hooks.get(&key).cloned()
+        })
+    }
+
+    /// The JSON a host sends for an object: `{"@ptr": ptr, "@type": class}`.
+    pub fn lower(&self, ptr: i64) -> Value {
+        let class = self.class_of(ptr).unwrap_or_default();
+        serde_json::json!({ "@ptr": ptr, "@type": class })
+    }
+
+    /// The `bpy.data` config payload: the context, the `BlendData` itself as
+    /// `"data"`, and one pointer per `BlendData` collection.
+    pub fn bpy_data(&self) -> Map<String, Value> {
+        let (context, data, schema) = {
+            let world = self.world();
+            (world.context, world.data, world.schema.clone())
+        };
+
+        let mut payload = Map::new();
+        payload.insert("context".to_string(), Value::from(context));
+        payload.insert("data".to_string(), Value::from(data));
+        if let Some(blend_data) = schema.classes.get("BlendData") {
+            for (name, prop) in &blend_data.properties {
+                if !matches!(prop, BpyProperty::Collection { .. }) {
+                    continue;
+                }
+                if let Ok(value) = self.get(data, name) {
+                    payload.insert(name.to_string(), value.get("@ptr").cloned().unwrap_or_default());
+                }
+            }
+        }
+        payload
+    }
+
+    fn world(&self) -> MutexGuard<'_, World> {
+        self.world.lock().expect("simulator state poisoned")
+    }
+
+    fn alloc(&self, class: &str, items: Option<String>) -> i64 {
+        let mut world = self.world();
+        let ptr = world.next_ptr;
+        world.next_ptr += 1;
+        world.objects.insert(
+            ptr,
+            Object {
+                class: class.to_string(),
+                props: HashMap::new(),
+                assigned: HashSet::new(),
+                items: items.map(|ty| (ty, Vec::new())),
+            },
+        );
+        ptr
+    }
+
+    fn default_value(&self, prop: &BpyProperty) -> Value {
+        match prop {
+            BpyProperty::Boolean { default, .. } => Value::from(*default),
+            BpyProperty::BooleanArray { array, .. } => Value::from(array.default.clone()),
+            BpyProperty::Int { default, .. } => Value::from(*default),
+            BpyProperty::IntArray { array, .. } => Value::from(array.default.clone()),
+            BpyProperty::Float { default, .. } => Value::from(*default),
+            BpyProperty::FloatArray { array, .. } => Value::from(array.default.clone()),
+            BpyProperty::String { default, .. } => {
+                Value::from(default.as_deref().unwrap_or_default())
+            }
+            // Flag enums hold a set of items, which starts out empty.
+            BpyProperty::Enum { is_flag: true, .. } => Value::Array(Vec::new()),
+            BpyProperty::Enum { default, items, .. } => default
+                .as_deref()
+                .or_else(|| items.first().map(|xs| xs.id.as_str()))
+                .map(Value::from)
+                .unwrap_or_default(),
+            BpyProperty::Pointer { item, fixed_type, .. } => {
+                if item.is_never_none {
+                    self.lower(self.alloc(fixed_type, None))
+                } else {
+                    Value::Null
+                }
+            }
+            BpyProperty::Collection {
+                fixed_type,
+                collection,
+                ..
+            } => {
+                let class = collection.as_deref().unwrap_or("bpy_prop_collection");
+                self.lower(self.alloc(class, Some(fixed_type.to_string())))
+            }
+        }
+    }
+
+    fn name_of(&self, ptr: i64) -> Result<String, SimError> {
+        match self.get(ptr, "name") {
+            Ok(Value::String(name)) => Ok(name),
+            Ok(_) | Err(SimError::NoAttribute { .. }) => Ok(String::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Blender's `name`, `name.001`, `name.002`, ... scheme.
+    fn unique_name(&self, collection: i64, name: &str) -> Result<String, SimError> {
+        let taken = self
+            .members(collection)?
+            .into_iter()
+            .map(|xs| self.name_of(xs))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut candidate = name.to_string();
+        let mut suffix = 0;
+        while taken.contains(&candidate) {
+            suffix += 1;
+            candidate = format!("{}.{:03}", name, suffix);
+        }
+        Ok(candidate)
+    }
+
+    fn with_object<R>(&self, ptr: i64, f: impl FnOnce(&Object) -> R) -> Result<R, SimError> {
+        let world = self.world();
+        world.objects.get(&ptr).map(f).ok_or(SimError::UnknownPtr(ptr))
+    }
+
+    fn with_object_mut<R>(&self, ptr: i64, f: impl FnOnce(&mut Object) -> R) -> Result<R, SimError> {
+        let mut world = self.world();
+        world.objects.get_mut(&ptr).map(f).ok_or(SimError::UnknownPtr(ptr))
+    }
+
+    /// Dump an object's materialized properties, for assertions and
+    /// debugging.
+    pub fn snapshot(&self, ptr: i64) -> Result<BTreeMap<String, Value>, SimError> {
+        self.with_object(ptr, |obj| {
+            obj.props
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        })
+    }
+}
+
+fn string_arg(invocation: &Invocation, idx: usize, name: &str) -> Result<String, SimError> {
+    invocation
+        .args
+        .get(idx)
+        .or_else(|| invocation.kwargs.get(name))
+        .and_then(|xs| xs.as_str())
+        .map(|xs| xs.to_string())
+        .ok_or_else(|| SimError::BadArgument(format!("{} expects a string {:?}", invocation.name, name)))
+}
+
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Attr(String),
+    Key(String),
+    Index(usize),
+}
+
+/// Split `a.b["key"][0].c` into its segments.
+fn parse_data_path(path: &str) -> Result<Vec<PathSegment>, SimError> {
+    let bad = || SimError::BadArgument(format!("invalid data path {:?}", path));
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(subscript) = rest.strip_prefix('[') {
+            let end = subscript.find(']').ok_or_else(bad)?;
+            let inner = subscript[..end].trim();
+            let quoted = inner
+                .strip_prefix('"')
+                .and_then(|xs| xs.strip_suffix('"'))
+                .or_else(|| inner.strip_prefix('\'').and_then(|xs| xs.strip_suffix('\'')));
+            segments.push(match quoted {
+                Some(key) => PathSegment::Key(key.to_string()),
+                None => PathSegment::Index(inner.parse().map_err(|_| bad())?),
+            });
+            rest = &subscript[end + 1..];
+        } else {
+            let rest_ = rest.strip_prefix('.').unwrap_or(rest);
+            if rest_.len() != rest.len() && segments.is_empty() {
+                return Err(bad());
+            }
+            let end = rest_
+                .find(['.', '['])
+                .unwrap_or(rest_.len());
+            if end == 0 {
+                return Err(bad());
+            }
+            segments.push(PathSegment::Attr(rest_[..end].to_string()));
+            rest = &rest_[end..];
+        }
+    }
+    Ok(segments)
+}
+
+fn ptr_arg(invocation: &Invocation, idx: usize) -> Result<i64, SimError> {
+    invocation
+        .args
+        .get(idx)
+        .and_then(|xs| xs.get("@ptr"))
+        .and_then(|xs| xs.as_i64())
+        .ok_or_else(|| SimError::BadArgument(format!("{} expects a bpy_struct", invocation.name)))
+}
+
+fn invocation(opmod: Option<&str>, name: &str, args: &PyArgs) -> Invocation {
+    Invocation {
+        target: args.target().map(|xs| xs.as_raw()),
+        class: args.class().map(|xs| xs.to_string()),
+        opmod: opmod.map(|xs| xs.to_string()),
+        name: name.to_string(),
+        args: args.args().to_vec(),
+        kwargs: args
+            .kwargs()
+            .map(|kwargs| kwargs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+            .unwrap_or_default(),
+    }
+}
+
+fn target(args: &PyArgs) -> i64 {
+    args.target().expect("attribute access without a target").as_raw()
+}
+
+fn unwrap<T>(result: Result<T, SimError>) -> T {
+    result.unwrap_or_else(|err| panic!("simulator: {}", err))
+}
+
+impl Transport for Simulator {
+    fn getattr(&self, attr: &str, args: PyArgs) -> Value {
+        unwrap(self.get(target(&args), attr))
+    }
+
+    fn setattr(&self, attr: &str, args: PyArgs) {
+        let value = args.args().first().cloned().unwrap_or(Value::Null);
+        unwrap(self.assign(target(&args), attr, value))
+    }
+
+    fn callmethod(&self, method: &str, args: PyArgs) -> Value {
+        unwrap(self.call(&invocation(None, method, &args)))
+    }
+
+    fn operator(&self, opmod: &str, operator: &str, args: PyArgs) -> Value {
+        unwrap(self.call(&invocation(Some(opmod), operator, &args)))
+    }
+
+    fn config(&self, key: &str) -> Option<String> {
+        (key == "bpy.data").then(|| Value::Object(self.bpy_data()).to_string())
+    }
+}
+
+#[cfg(feature = "host")]
+impl blextism_host::Backend for Simulator {
+    fn bpy_data(&mut self) -> Result<Map<String, Value>, blextism_host::HostError> {
+        Ok(Simulator::bpy_data(self)
+            .into_iter()
+            .map(|(name, ptr)| {
+                let lowered = ptr.as_i64().map(|xs| self.lower(xs)).unwrap_or(ptr);
+                (name, lowered)
+            })
+            .collect())
+    }
+
+    fn getattr(&mut self, target: i64, attr: &str) -> Result<Value, blextism_host::HostError> {
+        self.get(target, attr).map_err(into_host_error)
+    }
+
+    fn setattr(&mut self, target: i64, attr: &str, value: Value) -> Result<(), blextism_host::HostError> {
+        self.assign(target, attr, value).map_err(into_host_error)
+    }
+
+    fn callmethod(
+        &mut self,
+        target: i64,
+        method: &str,
+        args: &mut [Value],
+        kwargs: Map<String, Value>,
+    ) -> Result<Value, blextism_host::HostError> {
+        self.call(&Invocation {
+            target: Some(target),
+            class: None,
+            opmod: None,
+            name: method.to_string(),
+            args: args.to_vec(),
+            kwargs,
+        })
+        .map_err(into_host_error)
+    }
+
+    fn classmethod(
+        &mut self,
+        class: &str,
+        method: &str,
+        args: &mut [Value],
+        kwargs: Map<String, Value>,
+    ) -> Result<Value, blextism_host::HostError> {
+        self.call(&Invocation {
+            target: None,
+            class: Some(class.to_string()),
+            opmod: None,
+            name: method.to_string(),
+            args: args.to_vec(),
+            kwargs,
+        })
+        .map_err(into_host_error)
+    }
+
+    fn operator(
+        &mut self,
+        opmod: &str,
+        operator: &str,
+        args: Vec<Value>,
+        kwargs: Map<String, Value>,
+    ) -> Result<Value, blextism_host::HostError> {
+        self.call(&Invocation {
+            target: None,
+            class: None,
+            opmod: Some(opmod.to_string()),
+            name: operator.to_string(),
+            args,
+            kwargs,
+        })
+        .map_err(into_host_error)
+    }
+}
+
+#[cfg(feature = "host")]
+fn into_host_error(err: SimError) -> blextism_host::HostError {
+    match err {
+        SimError::UnknownPtr(ptr) => blextism_host::HostError::UnknownPtr(ptr),
+        err => blextism_host::HostError::Backend(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn prop(kind: &str, identifier: &str, extra: Value) -> Value {
+        let mut prop = json!({
+            "identifier": identifier,
+            "name": identifier,
+            "description": "",
+            "type": kind.trim_end_matches("[]").to_uppercase().replace("BOOL", "BOOLEAN"),
+            "unit": "NONE",
+            "subtype": "NONE",
+            "is_required": false,
+            "is_runtime": false,
+            "is_output": false,
+            "is_never_none": false,
+        });
+        prop.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        json!({ kind: prop })
+    }
+
+    fn items(ids: &[&str]) -> Value {
+        ids.iter()
+            .enumerate()
+            .map(|(value, id)| json!({ "id": id, "name": id, "value": value, "description": "" }))
+            .collect()
+    }
+
+    fn class(name: &str, parent: &str, properties: Value) -> Value {
+        json!({ "name": name, "parent": parent, "properties": properties, "methods": {} })
+    }
+
+    fn simulator() -> Simulator {
+        let name = prop("string", "name", json!({ "length_max": 0, "default": "" }));
+        let schema = json!({
+            "classes": [
+                class("bpy_struct", "object", json!({})),
+                class("Context", "bpy_struct", json!({})),
+                class("BlendData", "bpy_struct", json!({
+                    "objects": prop("collection", "objects", json!({
+                        "fixed_type": "Object",
+                        "collection": "BlendDataObjects",
+                    })),
+                })),
+                class("Object", "bpy_struct", json!({
+                    "name": name,
+                    "type": prop("enum", "type", json!({
+                        "items": items(&["MESH", "LIGHT"]),
+                        "default": "MESH",
+                    })),
+                    "mode": prop("enum", "mode", json!({
+                        "items": items(&["OBJECT", "EDIT"]),
+                        "is_flag": true,
+                    })),
+                    "lock_location": prop("bool[]", "lock_location", json!({
+                        "default": [false, false, true],
+                        "dimensions": [3, 0, 0],
+                        "length": 3,
+                    })),
+                    "modifiers": prop("collection", "modifiers", json!({
+                        "fixed_type": "Modifier",
+                        "collection": "ObjectModifiers",
+                    })),
+                })),
+                class("Modifier", "bpy_struct", json!({
+                    "name": name,
+                    "show_viewport": prop("bool", "show_viewport", json!({ "default": true })),
+                })),
+                class("NodesModifier", "Modifier", json!({})),
+            ],
+            "operators": {},
+        });
+        Simulator::new(serde_json::from_value(schema).unwrap())
+    }
+
+    fn ptr_of(value: Value) -> i64 {
+        value["@ptr"].as_i64().expect("expected a bpy_struct")
+    }
+
+    fn call(sim: &Simulator, target: i64, name: &str, args: Vec<Value>) -> Result<Value, SimError> {
+        sim.call(&Invocation {
+            target: Some(target),
+            class: None,
+            opmod: None,
+            name: name.to_string(),
+            args,
+            kwargs: Map::new(),
+        })
+    }
+
+    #[test]
+    fn parses_data_paths() {
+        use PathSegment::*;
+
+        assert_eq!(
+            parse_data_path(r#"modifiers["GeometryNodes"].show_viewport"#).unwrap(),
+            [Attr("modifiers".into()), Key("GeometryNodes".into()), Attr("show_viewport".into())]
+        );
+        assert_eq!(
+            parse_data_path("a['b'][0].c").unwrap(),
+            [Attr("a".into()), Key("b".into()), Index(0), Attr("c".into())]
+        );
+        assert_eq!(parse_data_path("location[ 2 ]").unwrap(), [Attr("location".into()), Index(2)]);
+        for path in [".a", "a..b", "a.", "a[0", "a[x]"] {
+            assert!(parse_data_path(path).is_err(), "{:?} should not parse", path);
+        }
+    }
+
+    #[test]
+    fn resolves_paths_through_collections_and_arrays() {
+        let sim = simulator();
+        let object = sim.create("Object").unwrap();
+        let modifiers = ptr_of(sim.get(object, "modifiers").unwrap());
+        let modifier = sim.add(modifiers, "GeometryNodes", None).unwrap();
+        sim.set(modifier, "show_viewport", Value::from(false)).unwrap();
+
+        assert_eq!(
+            sim.path_resolve(object, r#"modifiers["GeometryNodes"].show_viewport"#),
+            Ok(Value::from(false))
+        );
+        assert_eq!(sim.path_resolve(object, "modifiers[0].name"), Ok(Value::from("GeometryNodes")));
+        assert_eq!(sim.path_resolve(object, "lock_location[2]"), Ok(Value::from(true)));
+        assert_eq!(sim.path_resolve(object, "modifiers"), Ok(sim.lower(modifiers)));
+        assert!(matches!(sim.path_resolve(object, r#"modifiers["Missing"]"#), Err(SimError::BadArgument(_))));
+        assert!(matches!(sim.path_resolve(object, "modifiers[1]"), Err(SimError::BadArgument(_))));
+        assert!(matches!(sim.path_resolve(object, "name.upper"), Err(SimError::BadArgument(_))));
+        assert!(matches!(sim.path_resolve(object, "nope"), Err(SimError::NoAttribute { .. })));
+    }
+
+    #[test]
+    fn collections_support_new_remove_and_link() {
+        let sim = simulator();
+        let objects = ptr_of(sim.get(sim.data(), "objects").unwrap());
+
+        let cube = ptr_of(call(&sim, objects, "new", vec![Value::from("Cube")]).unwrap());
+        let again = ptr_of(call(&sim, objects, "new", vec![Value::from("Cube")]).unwrap());
+        assert_eq!(sim.class_of(cube), Ok("Object".to_string()));
+        assert_eq!(sim.get(again, "name"), Ok(Value::from("Cube.001")));
+        assert_eq!(sim.members(objects), Ok(vec![cube, again]));
+
+        // A subclass of the item type names the class to create.
+        let modifiers = ptr_of(sim.get(cube, "modifiers").unwrap());
+        let nodes = ptr_of(call(&sim, modifiers, "new", vec![Value::from("NodesModifier")]).unwrap());
+        assert_eq!(sim.class_of(nodes), Ok("NodesModifier".to_string()));
+
+        call(&sim, objects, "remove", vec![sim.lower(cube)]).unwrap();
+        assert_eq!(sim.members(objects), Ok(vec![again]));
+        assert_eq!(call(&sim, objects, "get", vec![Value::from("Cube")]), Ok(Value::Null));
+
+        call(&sim, objects, "link", vec![sim.lower(cube)]).unwrap();
+        call(&sim, objects, "link", vec![sim.lower(cube)]).unwrap();
+        assert_eq!(sim.members(objects), Ok(vec![again, cube]));
+        assert_eq!(call(&sim, objects, "keys", vec![]), Ok(json!(["Cube.001", "Cube"])));
+        let unknown = call(&sim, objects, "link", vec![json!({ "@ptr": 999 })]);
+        assert!(matches!(unknown, Err(SimError::UnknownPtr(999))));
+
+        // Like Blender, removing something that isn't there is an error.
+        call(&sim, objects, "unlink", vec![sim.lower(cube)]).unwrap();
+        let missing = call(&sim, objects, "unlink", vec![sim.lower(cube)]);
+        assert!(matches!(missing, Err(SimError::BadArgument(_))));
+        let missing = call(&sim, objects, "remove", vec![sim.lower(cube)]);
+        assert!(matches!(missing, Err(SimError::BadArgument(_))));
+    }
+
+    #[test]
+    fn property_unset_restores_the_default() {
+        let sim = simulator();
+        let object = sim.create("Object").unwrap();
+        assert_eq!(call(&sim, object, "is_property_set", vec![Value::from("type")]), Ok(Value::from(false)));
+
+        sim.set(object, "type", Value::from("LIGHT")).unwrap();
+        assert_eq!(call(&sim, object, "is_property_set", vec![Value::from("type")]), Ok(Value::from(true)));
+
+        call(&sim, object, "property_unset", vec![Value::from("type")]).unwrap();
+        assert_eq!(sim.is_set(object, "type"), Ok(false));
+        assert_eq!(sim.get(object, "type"), Ok(Value::from("MESH")));
+        assert!(matches!(
+            call(&sim, object, "property_unset", vec![Value::from("nope")]),
+            Err(SimError::NoAttribute { .. })
+        ));
+    }
+
+    #[test]
+    fn flag_enums_hold_sets_of_items() {
+        let sim = simulator();
+        let object = sim.create("Object").unwrap();
+        assert_eq!(sim.get(object, "mode"), Ok(json!([])));
+
+        sim.set(object, "mode", json!(["OBJECT", "EDIT"])).unwrap();
+        assert_eq!(sim.get(object, "mode"), Ok(json!(["OBJECT", "EDIT"])));
+        sim.set(object, "mode", json!([])).unwrap();
+
+        for value in [json!(["NOPE"]), json!("EDIT")] {
+            assert!(matches!(sim.set(object, "mode", value), Err(SimError::InvalidEnum { .. })));
+        }
+        assert!(matches!(sim.set(object, "type", json!(["MESH"])), Err(SimError::InvalidEnum { .. })));
+    }
+}
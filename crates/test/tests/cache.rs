@@ -0,0 +1,67 @@
+use blextism::bpy;
+use blextism::cache;
+use blextism_test::{ptr, Call, MockHost};
+
+/// A host whose active object has two users.
+fn host() -> (MockHost, i64) {
+    let host = MockHost::new();
+    let object = host.alloc();
+    host.on_getattr(host.context(), "active_object", ptr(object))
+        .on_getattr(object, "users", 2);
+    host.install();
+    (host, object)
+}
+
+fn count(host: &MockHost, call: &Call) -> usize {
+    host.calls().iter().filter(|xs| *xs == call).count()
+}
+
+#[test]
+fn cached_reads_skip_the_host() {
+    let (host, object) = host();
+
+    cache::scope(|| {
+        for _ in 0..3 {
+            let obj = bpy::context().active_object().unwrap();
+            assert_eq!(obj.users(), Some(2));
+        }
+    });
+    assert_eq!(count(&host, &Call::getattr(host.context(), "active_object")), 1);
+    assert_eq!(count(&host, &Call::getattr(object, "users")), 1);
+
+    // Outside the scope every read goes to the host again.
+    bpy::context().active_object().unwrap().users();
+    assert_eq!(count(&host, &Call::getattr(object, "users")), 2);
+}
+
+#[test]
+fn writes_operators_and_methods_invalidate_the_cache() {
+    let (host, object) = host();
+    let objects = host.alloc();
+    host.data("objects", objects).on_callmethod(objects, "get", ptr(object));
+    let users = Call::getattr(object, "users");
+
+    cache::scope(|| {
+        let obj = bpy::context().active_object().unwrap();
+        obj.users();
+        obj.users();
+        assert_eq!(count(&host, &users), 1);
+
+        obj.set_hide_viewport(true);
+        obj.users();
+        assert_eq!(count(&host, &users), 2);
+
+        bpy::ops::object::delete(());
+        obj.users();
+        assert_eq!(count(&host, &users), 3);
+
+        obj.hide_set(true, ().into());
+        obj.users();
+        assert_eq!(count(&host, &users), 4);
+
+        // Collection lookups only read.
+        bpy::data::objects().get("Cube");
+        obj.users();
+        assert_eq!(count(&host, &users), 4);
+    });
+}
@@ -0,0 +1,27 @@
+use blextism_test::{ptr, Call, MockHost};
+
+#[test]
+fn scene_setup_renders_with_cycles() {
+    let host = MockHost::new();
+    let (object, scene, render, worlds, world) =
+        (host.alloc(), host.alloc(), host.alloc(), host.alloc(), host.alloc());
+    host.data("objects", host.alloc()).data("worlds", worlds);
+    host.on_getattr(host.context(), "active_object", ptr(object))
+        .on_getattr(object, "mode", "OBJECT")
+        .on_getattr(host.context(), "scene", ptr(scene))
+        .on_getattr(scene, "render", ptr(render))
+        .on_getattr(render, "image_settings", ptr(host.alloc()))
+        .on_callmethod(worlds, "get", ptr(world));
+    host.install();
+
+    plugin::scene_setup();
+
+    host.assert_calls_in_order(&[
+        Call::operator("object", "select_all").with_kwargs(serde_json::json!({ "action": "SELECT" })),
+        Call::operator("object", "delete"),
+        Call::operator("world", "new"),
+        Call::setattr(scene, "world", ptr(world)),
+        Call::setattr(render, "engine", "CYCLES"),
+    ]);
+    host.assert_setattr(render, "engine", "CYCLES");
+}
@@ -0,0 +1,59 @@
+use blextism::bpy;
+use blextism::profile::{self, CallKind};
+use blextism_test::MockHost;
+use serde_json::json;
+
+#[test]
+fn profiles_count_calls_and_bytes() {
+    let host = MockHost::new();
+    let object = host.alloc();
+    let typed = json!({ "@ptr": object, "@type": "Object" });
+    host.on_getattr(host.context(), "active_object", &typed)
+        .on_getattr(object, "users", 2);
+    host.install();
+
+    // Calls made before profiling starts aren't counted.
+    bpy::context().active_object();
+
+    profile::enable();
+    let obj = bpy::context().active_object().unwrap();
+    bpy::context().active_object();
+    obj.users();
+    obj.set_hide_viewport(true);
+    bpy::ops::object::delete(());
+    let profile = profile::finish().unwrap();
+    assert!(!profile::is_enabled());
+
+    // Ties in call count are ordered by time, so sort them for comparison.
+    let mut stats: Vec<_> = profile
+        .stats()
+        .into_iter()
+        .map(|xs| (xs.kind, xs.rna_type, xs.name, xs.calls, xs.bytes_received))
+        .collect();
+    stats.sort_by(|lhs, rhs| (lhs.0.as_str(), &lhs.2).cmp(&(rhs.0.as_str(), &rhs.2)));
+    let received = typed.to_string().len();
+    assert_eq!(
+        stats,
+        [
+            (CallKind::GetAttr, "<untyped>".to_string(), "active_object".to_string(), 2, 2 * received),
+            (CallKind::GetAttr, "Object".to_string(), "users".to_string(), 1, 1),
+            (CallKind::Operator, "bpy.ops.object".to_string(), "delete".to_string(), 1, 4),
+            (CallKind::SetAttr, "Object".to_string(), "hide_viewport".to_string(), 1, 0),
+        ]
+    );
+
+    // Requests are measured as sent, and summed per call site.
+    let sent: Vec<_> = profile
+        .calls
+        .iter()
+        .filter(|xs| xs.name == "active_object")
+        .map(|xs| xs.bytes_sent)
+        .collect();
+    assert_eq!(sent.len(), 2);
+    assert!(sent[0] > 0 && sent[0] == sent[1]);
+    let stat = profile.stats().into_iter().find(|xs| xs.name == "active_object").unwrap();
+    assert_eq!(stat.bytes_sent, 2 * sent[0]);
+
+    let summary = profile.summary();
+    assert!(summary.starts_with("blextism profile: 5 host calls"), "{}", summary);
+}
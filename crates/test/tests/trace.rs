@@ -0,0 +1,55 @@
+use blextism::{bpy, read_trace, set_transport, RecordingTransport, ReplayTransport, TraceEvent};
+use blextism_test::{ptr, MockHost};
+
+/// Run the example's scene setup against a mock host, recording every call.
+fn record() -> Vec<TraceEvent> {
+    let host = MockHost::new();
+    let (object, scene, render, worlds, world) =
+        (host.alloc(), host.alloc(), host.alloc(), host.alloc(), host.alloc());
+    host.data("objects", host.alloc()).data("worlds", worlds);
+    host.on_getattr(host.context(), "active_object", ptr(object))
+        .on_getattr(object, "mode", "OBJECT")
+        .on_getattr(host.context(), "scene", ptr(scene))
+        .on_getattr(scene, "render", ptr(render))
+        .on_getattr(render, "image_settings", ptr(host.alloc()))
+        .on_callmethod(worlds, "get", ptr(world));
+
+    let recording = RecordingTransport::new(host);
+    set_transport(recording.clone());
+    plugin::scene_setup();
+
+    // Round-trip through the file format, as a trace from run.py would be.
+    let mut file = Vec::new();
+    recording.write_to(&mut file).unwrap();
+    read_trace(file.as_slice()).unwrap()
+}
+
+#[test]
+fn recorded_sessions_replay() {
+    let events = record();
+    assert!(events.iter().any(|xs| matches!(xs, TraceEvent::Config { key, .. } if key == "bpy.data")));
+    assert!(events.iter().any(|xs| matches!(
+        xs,
+        TraceEvent::SetAttr { attr, args } if attr == "engine" && args["args"][0] == "CYCLES"
+    )));
+
+    let replay = ReplayTransport::new(events);
+    set_transport(replay.clone());
+    plugin::scene_setup();
+    replay.assert_finished();
+}
+
+#[test]
+#[should_panic(expected = "trace diverged at call #0")]
+fn replay_fails_on_a_different_call() {
+    set_transport(ReplayTransport::new(record()));
+    bpy::context().scene();
+}
+
+#[test]
+#[should_panic(expected = "trace ended early")]
+fn replay_fails_when_calls_are_missing() {
+    let replay = ReplayTransport::new(record());
+    set_transport(replay.clone());
+    replay.assert_finished();
+}
@@ -1,7 +1,20 @@
 import bpy
 import json
 
+
+
+def primary_base(cls):
+    # the base a class with several bases is placed under: the first one
+    # bpy.types knows about. The rest are Python mixins, like _GenericBone.
+    for base in cls.__bases__:
+        if getattr(bpy.types, base.__name__, None) is base:
+            return base
+    return None
+
+
 all_classes = set()
+MIXINS = set()
+UNSUPPORTED = set()
 root = {}
 for attr in dir(bpy.types):
     value = getattr(bpy.types, attr, None)
@@ -19,7 +32,17 @@ for attr in dir(bpy.types):
             bases.append(current)
             if len(current.__bases__) == 1:
                 current = current.__bases__[0]
+            elif len(current.__bases__) > 1 and primary_base(current) is not None:
+                primary = primary_base(current)
+                MIXINS.update(
+                    base
+                    for base in current.__bases__
+                    if getattr(bpy.types, base.__name__, None) is not base
+                )
+                current = primary
             else:
+                if len(current.__bases__) > 1:
+                    UNSUPPORTED.add(current)
                 break
 
         bases.reverse()
@@ -45,6 +68,9 @@ def unpack_property_metadata(property_descriptor):
         "is_runtime": property_descriptor.is_runtime,
         "is_output": property_descriptor.is_output,
         "is_never_none": property_descriptor.is_never_none,
+        "is_readonly": property_descriptor.is_readonly,
+        "is_animatable": property_descriptor.is_animatable,
+        "is_overridable": property_descriptor.is_overridable,
     }
     match type(property_descriptor):
         case bpy.types.EnumProperty:
@@ -61,6 +87,11 @@ def unpack_property_metadata(property_descriptor):
                     }
                 )
 
+            if property_descriptor.is_enum_flag:
+                output["is_flag"] = True
+            else:
+                output["default"] = property_descriptor.default
+
             return {"enum": output}
 
         case bpy.types.CollectionProperty:
@@ -157,7 +188,7 @@ def inspect_bpy_type(typ, output):
             "item": {
                 "description": descriptor.description,
                 "use_self": descriptor.use_self,
-                "use_self_type": descriptor.use_self,
+                "use_self_type": descriptor.use_self_type,
                 "parameters": [
                     *map(
                         lambda xs: unpack_property_metadata(xs),
@@ -173,31 +204,20 @@ def inspect_bpy_type(typ, output):
         props[prop_name] = unpack_property_metadata(descriptor)
 
 
-def disambiguate_render_property(output):
-    # RenderEngine defines "render" as both a property (for RenderSettings) AND a
-    # method (".render()"), so we disambiguate here.
-    render_prop = output["properties"].pop("render", None)
-    if render_prop is not None:
-        render_prop["pointer"]["identifier"] = "render_settings"
-        output["properties"]["render_settings"] = render_prop
-
-
 def add_context_properties(output):
     # extracted from https://github.com/blender/blender/blob/9c0bffcc89f174f160805de042b00ae7c201c40b/doc/python_api/sphinx_doc_gen.py#L1161
-    # Bone: props commented out until we get Bone types included
-    # in schema generation (they have multiple bases so they're omitted right now)
     items = {
         # context_member: (RNA type, is_collection)
         "active_action": ("Action", False),
         "active_annotation_layer": ("GPencilLayer", False),
-        # "active_bone": ("EditBone", False),
+        "active_bone": ("EditBone", False),
         "active_file": ("FileSelectEntry", False),
         # "active_gpencil_frame": ("GreasePencilLayer", True),
         "active_gpencil_layer": ("GPencilLayer", True),
         "active_node": ("Node", False),
         "active_object": ("Object", False),
         "active_operator": ("Operator", False),
-        # "active_pose_bone": ("PoseBone", False),
+        "active_pose_bone": ("PoseBone", False),
         "active_sequence_strip": ("Sequence", False),
         "active_editable_fcurve": ("FCurve", False),
         "active_nla_strip": ("NlaStrip", False),
@@ -206,7 +226,7 @@ def add_context_properties(output):
         "annotation_data_owner": ("ID", False),
         "armature": ("Armature", False),
         "asset_library_reference": ("AssetLibraryReference", False),
-        # "bone": ("Bone", False),
+        "bone": ("Bone", False),
         "brush": ("Brush", False),
         "camera": ("Camera", False),
         "cloth": ("ClothModifier", False),
@@ -214,13 +234,13 @@ def add_context_properties(output):
         "collision": ("CollisionModifier", False),
         "curve": ("Curve", False),
         "dynamic_paint": ("DynamicPaintModifier", False),
-        # "edit_bone": ("EditBone", False),
+        "edit_bone": ("EditBone", False),
         "edit_image": ("Image", False),
         "edit_mask": ("Mask", False),
         "edit_movieclip": ("MovieClip", False),
         "edit_object": ("Object", False),
         "edit_text": ("Text", False),
-        # "editable_bones": ("EditBone", True),
+        "editable_bones": ("EditBone", True),
         # "editable_gpencil_layers": ("GPencilLayer", True),
         "editable_gpencil_strokes": ("GPencilStroke", True),
         "editable_objects": ("Object", True),
@@ -252,15 +272,15 @@ def add_context_properties(output):
         "particle_system_editable": ("ParticleSystem", False),
         "property": ("ID", False),
         "pointcloud": ("PointCloud", False),
-        # "pose_bone": ("PoseBone", False),
+        "pose_bone": ("PoseBone", False),
         "pose_object": ("Object", False),
         "scene": ("Scene", False),
         "sculpt_object": ("Object", False),
         "selectable_objects": ("Object", True),
         "selected_assets": ("AssetRepresentation", True),
-        # "selected_bones": ("EditBone", True),
+        "selected_bones": ("EditBone", True),
         "selected_editable_actions": ("Action", True),
-        # "selected_editable_bones": ("EditBone", True),
+        "selected_editable_bones": ("EditBone", True),
         "selected_editable_fcurves": ("FCurve", True),
         "selected_editable_keyframes": ("Keyframe", True),
         "selected_editable_objects": ("Object", True),
@@ -271,8 +291,8 @@ def add_context_properties(output):
         "selected_movieclip_tracks": ("MovieTrackingTrack", True),
         "selected_nodes": ("Node", True),
         "selected_objects": ("Object", True),
-        # "selected_pose_bones": ("PoseBone", True),
-        # "selected_pose_bones_from_active_object": ("PoseBone", True),
+        "selected_pose_bones": ("PoseBone", True),
+        "selected_pose_bones_from_active_object": ("PoseBone", True),
         "selected_sequences": ("Sequence", True),
         "selected_visible_actions": ("Action", True),
         "selected_visible_fcurves": ("FCurve", True),
@@ -286,10 +306,10 @@ def add_context_properties(output):
         # "ui_list": ("UIList", False),
         "vertex_paint_object": ("Object", False),
         "view_layer": ("ViewLayer", False),
-        # "visible_bones": ("EditBone", True),
+        "visible_bones": ("EditBone", True),
         "visible_gpencil_layers": ("GPencilLayer", True),
         "visible_objects": ("Object", True),
-        # "visible_pose_bones": ("PoseBone", True),
+        "visible_pose_bones": ("PoseBone", True),
         "visible_fcurves": ("FCurve", True),
         "weight_paint_object": ("Object", False),
         "volume": ("Volume", False),
@@ -309,99 +329,17 @@ def add_context_properties(output):
                 "is_runtime": False,
                 "is_output": False,
                 "is_never_none": False,
+                "is_readonly": True,
+                "is_animatable": False,
+                "is_overridable": False,
             }
         }
 
 
-def add_keyframe_methods(output):
-    create_prop = lambda name, description, typ, required, **kwargs: {
-        "identifier": name,
-        "description": description,
-        "type": typ,
-        "unit": "NONE",
-        "name": name,
-        "subtype": "NONE",
-        "is_argument_optional": not required,
-        "is_required": required,
-        "is_runtime": False,
-        "is_output": False,
-        "is_never_none": False,
-        **kwargs,
-    }
-
-    output["methods"]["keyframe_insert"] = {
-        "type": "rna",
-        "item": {
-            "description": "Insert a keyframe on the property given, adding fcurves and animation data when necessary.",
-            "use_self": True,
-            "use_self_type": False,
-            "parameters": [
-                {
-                    "string": create_prop(
-                        "data_path",
-                        "path to the property to key, analogous to the fcurve’s data path.",
-                        "STRING",
-                        True,
-                        **{"length_max": 0, "default": ""},
-                    )
-                },
-                {
-                    "int": create_prop(
-                        "index",
-                        "array index of the property to key. Defaults to -1 which will key all indices or a single channel if the property is not an array.",
-                        "INT",
-                        False,
-                        **{
-                            "hard_min": -2147483648,
-                            "hard_max": 2147483647,
-                            "soft_min": -2147483648,
-                            "soft_max": 2147483647,
-                            "default": -1,
-                        },
-                    )
-                },
-                {
-                    "float": create_prop(
-                        "frame",
-                        "The frame on which the keyframe is inserted, defaulting to the current frame.",
-                        "FLOAT",
-                        False,
-                        **{
-                            "hard_min": -2147483648,
-                            "hard_max": 2147483647,
-                            "soft_min": -2147483648,
-                            "soft_max": 2147483647,
-                        },
-                    )
-                },
-                {
-                    "string": create_prop(
-                        "group",
-                        "The name of the group the F-Curve should be added to if it doesn’t exist yet.",
-                        "STRING",
-                        False,
-                        **{"length_max": 0, "default": ""},
-                    )
-                },
-                {
-                    "string": create_prop(
-                        "options",
-                        "Optional set of flags",
-                        "STRING",
-                        False,
-                        **{"length_max": 0, "default": ""},
-                    )
-                },
-            ],
-        },
-    }
-
-
 CLASSES = set()
+# Signature fixes that don't need Blender live in overlays/ instead.
 EXTRAS = {
-    bpy.types.bpy_struct: add_keyframe_methods,
     bpy.types.Context: add_context_properties,
-    bpy.types.RenderEngine: disambiguate_render_property,
 }
 
 
@@ -409,6 +347,8 @@ def inspect_recursive(tree, parent=object, output=[]):
     for cls, item in tree.items():
         CLASSES.add(cls.__name__)
         result = {"name": cls.__name__, "parent": parent.__name__}
+        if len(cls.__bases__) > 1:
+            result["bases"] = [base.__name__ for base in cls.__bases__]
         output.append(result)
         inspect_bpy_type(cls, result)
 
@@ -421,14 +361,31 @@ def inspect_recursive(tree, parent=object, output=[]):
 
 classes = inspect_recursive(root[object])
 
+# mixins contribute Python-level members to the classes listing them in
+# "bases"; they have no RNA of their own.
+for mixin in sorted(MIXINS, key=lambda cls: cls.__name__):
+    CLASSES.add(mixin.__name__)
+    result = {"name": mixin.__name__, "parent": "object", "mixin": True}
+    inspect_bpy_type(mixin, result)
+    classes.append(result)
+
 
 # fixup "bad" pointers: pointers and collections may refer to types
 # outside of `bpy.types` (in particular, to objects in the Cycles
 # renderer.)
 for pointer in POINTERS:
     if pointer["fixed_type"] not in CLASSES:
+        pointer["unresolved_type"] = pointer["fixed_type"]
         pointer["fixed_type"] = "bpy_struct"
 
+# classes with several bases, none of which bpy.types knows, don't fit the
+# tree above; record them so bindgen can report them.
+unsupported = [
+    {"name": cls.__name__, "bases": [base.__name__ for base in cls.__bases__]}
+    for cls in sorted(UNSUPPORTED, key=lambda cls: cls.__name__)
+    if hasattr(cls, "bl_rna")
+]
+
 operators = {}
 for opmod_name in dir(bpy.ops):
     if "__" in opmod_name:
@@ -460,4 +417,14 @@ for opmod_name in dir(bpy.ops):
             "parameters": parameters,
         }
 
-print(json.dumps({"classes": classes, "operators": operators}, indent=2))
+print(
+    json.dumps(
+        {
+            "version": "%d.%d" % bpy.app.version[:2],
+            "classes": classes,
+            "operators": operators,
+            "unsupported": unsupported,
+        },
+        indent=2,
+    )
+)
@@ -7,6 +7,7 @@ except ImportError:
 
 import bpy
 import json
+import os
 from typing import Annotated, Any, Optional
 from extism import host_fn, Plugin, Json, set_log_file
 from weakref import WeakValueDictionary
@@ -15,6 +16,19 @@ import mathutils
 
 PTR_TO_BPY_STRUCTS = dict()
 
+# Set BLEXTISM_TRACE to a path to record every host call as JSON lines; the
+# result can be replayed without Blender using `blextism::ReplayTransport`.
+TRACE_PATH = os.environ.get("BLEXTISM_TRACE")
+TRACE = open(TRACE_PATH, "w") if TRACE_PATH else None
+
+
+def trace(event: dict):
+    if TRACE is None:
+        return
+
+    TRACE.write(json.dumps(event) + "\n")
+    TRACE.flush()
+
 
 def encode_bpy_struct(bpy_struct: bpy.types.bpy_struct) -> int:
     ptr = id(bpy_struct)
@@ -85,6 +99,21 @@ def _lower(value) -> Any:
 def bpy_operator(
     mod: str, method: str, args: Annotated[dict, Json]
 ) -> Annotated[dict | list, Json]:
+    request = json.loads(json.dumps(args)) if TRACE else None
+    result = _bpy_operator(mod, method, args)
+    trace(
+        {
+            "call": "operator",
+            "opmod": mod,
+            "operator": method,
+            "args": request,
+            "result": result,
+        }
+    )
+    return result
+
+
+def _bpy_operator(mod: str, method: str, args: dict):
     try:
         args_lifted = _lift(args)
         star_args = args_lifted.pop("args", []) or []
@@ -115,11 +144,23 @@ def bpy_operator(
 def bpy_callmethod(
     method: str, args: Annotated[dict, Json]
 ) -> Annotated[dict | list, Json]:
+    request = json.loads(json.dumps(args)) if TRACE else None
+    result = _bpy_callmethod(method, args)
+    trace({"call": "callmethod", "method": method, "args": request, "result": result})
+    return result
+
+
+def _bpy_callmethod(method: str, args: dict):
     try:
         args_lifted = _lift(args)
         target = args_lifted.pop("self", None)
         star_args = args_lifted.pop("args", []) or []
         kwargs = args_lifted.pop("kwargs", {}) or {}
+        out = args_lifted.pop("@out", None)
+        if target is None and "@class" in args_lifted:
+            # class-level functions are called on the class itself
+            target = getattr(bpy.types, args_lifted.pop("@class"), None)
+
         if target is None:
             raise UnknownPtr()
 
@@ -128,6 +169,9 @@ def bpy_callmethod(
             raise InvalidTarget()
 
         result = attr(*star_args, **kwargs)
+        if out is not None:
+            # the method filled this argument in place (e.g. foreach_get)
+            return _lower(star_args[out])
         return _lower(result)
     except UnknownPtr:
         ...
@@ -141,6 +185,13 @@ def bpy_callmethod(
 def bpy_getattr(
     attr_name: str, args: Annotated[dict, Json]
 ) -> Annotated[dict | list, Json]:
+    request = json.loads(json.dumps(args)) if TRACE else None
+    result = _bpy_getattr(attr_name, args)
+    trace({"call": "getattr", "attr": attr_name, "args": request, "result": result})
+    return result
+
+
+def _bpy_getattr(attr_name: str, args: dict):
     try:
         args = _lift(args)
         target = args.pop("self", None)
@@ -161,6 +212,12 @@ def bpy_getattr(
 
 @host_fn(namespace="chrisdickinson:blender/bpy")
 def bpy_setattr(attr_name: str, args: Annotated[dict, Json]):
+    request = json.loads(json.dumps(args)) if TRACE else None
+    _bpy_setattr(attr_name, args)
+    trace({"call": "setattr", "attr": attr_name, "args": request})
+
+
+def _bpy_setattr(attr_name: str, args: dict):
     try:
         args = _lift(args)
         target = args.pop("self", None)
@@ -181,75 +238,43 @@ def main():
 
     set_log_file("stderr", "info")
 
-    bpy_data = [
-        "actions",
-        "armatures",
-        "batch_remove",
-        "bl_rna",
-        "brushes",
-        "cache_files",
-        "cameras",
-        "collections",
-        "curves",
-        "filepath",
-        "fonts",
-        "grease_pencils",
-        "hair_curves",
-        "images",
-        "is_dirty",
-        "is_saved",
-        "lattices",
-        "libraries",
-        "lightprobes",
-        "lights",
-        "linestyles",
-        "masks",
-        "materials",
-        "meshes",
-        "metaballs",
-        "movieclips",
-        "node_groups",
-        "objects",
-        "orphans_purge",
-        "paint_curves",
-        "palettes",
-        "particles",
-        "pointclouds",
-        "rna_type",
-        "scenes",
-        "screens",
-        "shape_keys",
-        "sounds",
-        "speakers",
-        "temp_data",
-        "texts",
-        "textures",
-        "use_autopack",
-        "user_map",
-        "version",
-        "volumes",
-        "window_managers",
-        "workspaces",
-        "worlds",
-    ]
-
-    plugin = Plugin(
-        manifest,
-        wasi=True,
-        config={
-            "bpy.data": json.dumps(
-                {"context": encode_bpy_struct(bpy.context)}
-                | dict(
-                    (
-                        (key, encode_bpy_struct(getattr(bpy.data, key)))
-                        for key in bpy_data
-                    )
+    # One pointer per BlendData collection the bindings read, as bindgen's
+    # `--data` listed them. Bindings built for several Blender versions may
+    # list collections this one lacks.
+    with open(bpy.path.abspath("//target/bpy-data.json"), "r") as f:
+        bpy_data = [key for key in json.loads(f.read()) if hasattr(bpy.data, key)]
+
+    config = {
+        "bpy.data": json.dumps(
+            {
+                "context": encode_bpy_struct(bpy.context),
+                "data": encode_bpy_struct(bpy.data),
+            }
+            | dict(
+                (
+                    (key, encode_bpy_struct(getattr(bpy.data, key)))
+                    for key in bpy_data
                 )
             )
-        },
-    )
-
-    plugin.call("example", "")
+        )
+    }
+    # "summary" prints a table of host calls; "chrome" writes a Chrome trace
+    # to profile.json next to the .blend file.
+    profile = os.environ.get("BLEXTISM_PROFILE")
+    if profile:
+        config["blextism.profile"] = profile
+
+    for key, value in config.items():
+        trace({"call": "config", "key": key, "value": value})
+
+    plugin = Plugin(manifest, wasi=True, config=config)
+
+    report = bytes(plugin.call("example", "")).decode()
+    if profile == "chrome":
+        with open(bpy.path.abspath("//profile.json"), "w") as f:
+            f.write(report)
+    elif report:
+        print(report)
 
 
 if __name__ == "__main__":